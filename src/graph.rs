@@ -0,0 +1,317 @@
+//! Graphs: vertices joined by weighted edges.
+//!
+//! The README lists adjacency matrices and adjacency lists as the two
+//! representations, and the only graph code in the notes is the undirected
+//! `Graph` of `Linear/Stack.md`, which only adds edges. [`Graph`] is the
+//! interface both representations share: [`AdjacencyList`] keeps each
+//! vertex's outgoing edges in a vector and suits sparse graphs,
+//! [`AdjacencyMatrix`] keeps a weight slot for every pair of vertices and
+//! answers edge queries in O(1). Either converts into the other with `From`.
+//!
+//! Vertices are identified by a [`VertexId`] handed out by `add_vertex`.
+//! Ids are never reused, so one that outlives its vertex stays invalid
+//! instead of naming a newer vertex; algorithms can size per-vertex tables
+//! with [`Graph::vertex_bound`].
+//!
+//! [`traversal`] walks any [`Graph`] depth- or breadth-first, and
+//! [`shortest_path`] finds its shortest paths.
+
+use std::error::Error;
+use std::fmt;
+
+pub mod adjacency_list;
+pub mod adjacency_matrix;
+pub mod shortest_path;
+pub mod traversal;
+
+pub use adjacency_list::AdjacencyList;
+pub use adjacency_matrix::AdjacencyMatrix;
+
+/// Identifies a vertex within one graph.
+pub type VertexId = usize;
+
+/// Whether an edge goes one way or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// An edge from `a` to `b` leads only from `a` to `b`.
+    Directed,
+    /// An edge between `a` and `b` leads both ways and is counted once.
+    Undirected,
+}
+
+/// Errors reported by the graph types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphError {
+    /// The id names no vertex of the graph, or one that was removed.
+    MissingVertex(VertexId),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::MissingVertex(id) => write!(f, "no vertex {id}"),
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+/// A graph with data of type `Vertex` on its vertices and `Weight` on its
+/// edges. Unweighted graphs use `()` weights.
+///
+/// There is at most one edge from one vertex to another; adding it again
+/// replaces its weight. In an undirected graph the edge between `a` and `b`
+/// is the same whichever end is named first.
+pub trait Graph {
+    type Vertex;
+    type Weight;
+
+    /// Whether the graph is directed.
+    fn direction(&self) -> Direction;
+
+    /// Returns `true` for a directed graph.
+    fn is_directed(&self) -> bool {
+        self.direction() == Direction::Directed
+    }
+
+    /// Number of vertices.
+    fn vertex_count(&self) -> usize;
+
+    /// Number of edges; an undirected edge counts once.
+    fn edge_count(&self) -> usize;
+
+    /// One more than the largest id ever handed out, so every id of the
+    /// graph indexes a table of this length.
+    fn vertex_bound(&self) -> usize;
+
+    /// Adds a vertex and returns its id.
+    fn add_vertex(&mut self, data: Self::Vertex) -> VertexId;
+
+    /// Removes `vertex` with all its edges and returns its data.
+    fn remove_vertex(&mut self, vertex: VertexId) -> Option<Self::Vertex>;
+
+    /// Data of `vertex`.
+    fn vertex(&self, vertex: VertexId) -> Option<&Self::Vertex>;
+
+    /// Data of `vertex`, mutably.
+    fn vertex_mut(&mut self, vertex: VertexId) -> Option<&mut Self::Vertex>;
+
+    /// Returns `true` if `vertex` is in the graph.
+    fn contains_vertex(&self, vertex: VertexId) -> bool {
+        self.vertex(vertex).is_some()
+    }
+
+    /// Ids of all vertices, in ascending order.
+    fn vertices(&self) -> impl Iterator<Item = VertexId> + '_;
+
+    /// Adds an edge from `from` to `to`, returning the weight it replaced.
+    fn add_edge(
+        &mut self,
+        from: VertexId,
+        to: VertexId,
+        weight: Self::Weight,
+    ) -> Result<Option<Self::Weight>, GraphError>;
+
+    /// Removes the edge from `from` to `to` and returns its weight.
+    fn remove_edge(&mut self, from: VertexId, to: VertexId) -> Option<Self::Weight>;
+
+    /// Weight of the edge from `from` to `to`.
+    fn edge(&self, from: VertexId, to: VertexId) -> Option<&Self::Weight>;
+
+    /// Returns `true` if there is an edge from `from` to `to`.
+    fn contains_edge(&self, from: VertexId, to: VertexId) -> bool {
+        self.edge(from, to).is_some()
+    }
+
+    /// Vertices reachable from `vertex` over one edge, with the edge
+    /// weights. Empty for a missing vertex.
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &Self::Weight)> + '_;
+
+    /// Number of edges leaving `vertex`.
+    fn degree(&self, vertex: VertexId) -> usize {
+        self.neighbors(vertex).count()
+    }
+
+    /// All edges as `(from, to, weight)`. An undirected edge is listed once,
+    /// with `from <= to`.
+    fn edges(&self) -> impl Iterator<Item = (VertexId, VertexId, &Self::Weight)> + '_ {
+        let directed = self.is_directed();
+        self.vertices().flat_map(move |from| {
+            self.neighbors(from)
+                .filter(move |&(to, _)| directed || from <= to)
+                .map(move |(to, weight)| (from, to, weight))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::rng::SplitMix64;
+
+    /// Checks the counts against what the iterators report.
+    fn check_counts<G: Graph>(graph: &G) {
+        assert_eq!(graph.edge_count(), graph.edges().count());
+        assert_eq!(graph.vertex_count(), graph.vertices().count());
+        assert!(graph.vertices().all(|vertex| vertex < graph.vertex_bound()));
+    }
+
+    /// Edges keyed by their ends, the lower id first when undirected.
+    fn edge_map<G: Graph>(graph: &G) -> BTreeMap<(VertexId, VertexId), G::Weight>
+    where
+        G::Weight: Clone,
+    {
+        graph
+            .edges()
+            .map(|(from, to, weight)| ((from, to), weight.clone()))
+            .collect()
+    }
+
+    fn key(direction: Direction, from: VertexId, to: VertexId) -> (VertexId, VertexId) {
+        match direction {
+            Direction::Directed => (from, to),
+            Direction::Undirected => (from.min(to), from.max(to)),
+        }
+    }
+
+    /// Applies random mutations, checking the graph against a model after
+    /// each one.
+    fn random_mutations<G: Graph<Vertex = usize, Weight = u64>>(mut graph: G, seed: u64) {
+        let direction = graph.direction();
+        let mut rng = SplitMix64::new(seed);
+        let mut vertices = BTreeMap::new();
+        let mut edges = BTreeMap::new();
+        for step in 0..1000 {
+            let bound = graph.vertex_bound() + 1;
+            let (a, b) = (rng.below(bound), rng.below(bound));
+            match rng.below(8) {
+                0 => {
+                    let id = graph.add_vertex(step);
+                    assert_eq!(id, bound - 1);
+                    vertices.insert(id, step);
+                }
+                1 => {
+                    assert_eq!(graph.remove_vertex(a), vertices.remove(&a));
+                    edges.retain(|&(from, to), _| from != a && to != a);
+                }
+                2..=5 => {
+                    let weight = rng.next_u64();
+                    let found = graph.add_edge(a, b, weight);
+                    match [a, b].into_iter().find(|id| !vertices.contains_key(id)) {
+                        Some(missing) => assert_eq!(found, Err(GraphError::MissingVertex(missing))),
+                        None => assert_eq!(found, Ok(edges.insert(key(direction, a, b), weight))),
+                    }
+                }
+                _ => assert_eq!(graph.remove_edge(a, b), edges.remove(&key(direction, a, b))),
+            }
+            check_counts(&graph);
+            assert_eq!(
+                graph.vertices().collect::<Vec<_>>(),
+                vertices.keys().copied().collect::<Vec<_>>()
+            );
+            assert_eq!(edge_map(&graph), edges);
+            if graph.contains_edge(a, b) && direction == Direction::Undirected {
+                assert_eq!(graph.edge(a, b), graph.edge(b, a));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adjacency_list_counts_edges_after_every_mutation() {
+        for (seed, direction) in [(1, Direction::Directed), (2, Direction::Undirected)] {
+            random_mutations(AdjacencyList::new(direction), seed);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adjacency_matrix_counts_edges_after_every_mutation() {
+        for (seed, direction) in [(3, Direction::Directed), (4, Direction::Undirected)] {
+            random_mutations(AdjacencyMatrix::new(direction), seed);
+        }
+    }
+
+    /// A graph on `0..8` with every vertex but 0 linked to two others,
+    /// self-loops included, and vertices 2 and 5 and one edge removed.
+    fn sample(direction: Direction) -> AdjacencyList<usize, u64> {
+        let mut graph = AdjacencyList::new(direction);
+        for id in 0..8 {
+            graph.add_vertex(id * 10);
+        }
+        for from in 1..8 {
+            for to in [from, (from * 3) % 8] {
+                graph.add_edge(from, to, (from * 8 + to) as u64).unwrap();
+            }
+        }
+        graph.add_edge(7, 0, 70).unwrap();
+        graph.remove_vertex(2).unwrap();
+        graph.remove_vertex(5).unwrap();
+        graph.remove_edge(3, 1).unwrap();
+        check_counts(&graph);
+        graph
+    }
+
+    fn assert_same_graph<A, B>(a: &A, b: &B)
+    where
+        A: Graph<Vertex = usize, Weight = u64>,
+        B: Graph<Vertex = usize, Weight = u64>,
+    {
+        assert_eq!(a.direction(), b.direction());
+        assert_eq!(a.vertex_count(), b.vertex_count());
+        assert_eq!(a.edge_count(), b.edge_count());
+        assert_eq!(a.vertex_bound(), b.vertex_bound());
+        assert!(a.vertices().eq(b.vertices()));
+        for vertex in 0..a.vertex_bound() + 1 {
+            assert_eq!(a.vertex(vertex), b.vertex(vertex));
+            assert_eq!(a.degree(vertex), b.degree(vertex));
+        }
+        assert_eq!(edge_map(a), edge_map(b));
+    }
+
+    #[test]
+    fn list_to_matrix_to_list_keeps_removed_vertices_out() {
+        for direction in [Direction::Directed, Direction::Undirected] {
+            let list = sample(direction);
+            let matrix = AdjacencyMatrix::from(list.clone());
+            check_counts(&matrix);
+            assert_same_graph(&list, &matrix);
+            let mut back = AdjacencyList::from(matrix.clone());
+            check_counts(&back);
+            assert_same_graph(&list, &back);
+            // Going back to a matrix gives the same matrix.
+            assert_eq!(AdjacencyMatrix::from(back.clone()), matrix);
+
+            for removed in [2, 5] {
+                assert!(!back.contains_vertex(removed));
+                assert_eq!(
+                    back.add_edge(removed, 1, 0),
+                    Err(GraphError::MissingVertex(removed))
+                );
+                assert_eq!(back.neighbors(removed).count(), 0);
+            }
+            assert_eq!(back.add_vertex(80), 8);
+            back.add_edge(8, 1, 81).unwrap();
+            check_counts(&back);
+        }
+    }
+
+    #[test]
+    fn converts_empty_and_emptied_graphs() {
+        let empty = AdjacencyMatrix::<usize, u64>::from(AdjacencyList::directed());
+        assert_eq!(empty.vertex_bound(), 0);
+        check_counts(&empty);
+
+        let mut list = sample(Direction::Undirected);
+        for vertex in list.vertices().collect::<Vec<_>>() {
+            list.remove_vertex(vertex).unwrap();
+            check_counts(&list);
+        }
+        let matrix = AdjacencyMatrix::from(list.clone());
+        assert_eq!(matrix.vertex_count(), 0);
+        assert_eq!(matrix.edge_count(), 0);
+        assert_same_graph(&list, &AdjacencyList::from(matrix));
+    }
+}
@@ -0,0 +1,239 @@
+//! Graph stored as a list of outgoing edges per vertex.
+
+use super::{AdjacencyMatrix, Direction, Graph, GraphError, VertexId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry<V, W> {
+    data: V,
+    /// Outgoing edges in the order they were added.
+    edges: Vec<(VertexId, W)>,
+}
+
+/// A graph that keeps, for every vertex, a vector of its outgoing edges.
+///
+/// An undirected edge is stored at both ends, except a self-loop, which is
+/// stored once.
+///
+/// | Operation                     | Cost                 |
+/// |-------------------------------|----------------------|
+/// | `add_vertex`                  | O(1) amortized       |
+/// | `add_edge`, `remove_edge`     | O(degree)            |
+/// | `edge`                        | O(degree)            |
+/// | `neighbors`                   | O(1) per neighbor    |
+/// | `remove_vertex`               | O(V + E)             |
+/// | space                         | O(V + E)             |
+///
+/// ```
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut roads = AdjacencyList::undirected();
+/// let lisbon = roads.add_vertex("Lisbon");
+/// let porto = roads.add_vertex("Porto");
+/// roads.add_edge(lisbon, porto, 313).unwrap();
+/// assert_eq!(roads.edge(porto, lisbon), Some(&313));
+/// assert_eq!(roads.edge_count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjacencyList<V, W> {
+    /// Indexed by id; `None` for removed vertices.
+    vertices: Vec<Option<Entry<V, W>>>,
+    direction: Direction,
+    vertex_count: usize,
+    edge_count: usize,
+}
+
+impl<V, W> AdjacencyList<V, W> {
+    /// Creates an empty graph.
+    pub fn new(direction: Direction) -> Self {
+        AdjacencyList {
+            vertices: Vec::new(),
+            direction,
+            vertex_count: 0,
+            edge_count: 0,
+        }
+    }
+
+    /// Creates an empty directed graph.
+    pub fn directed() -> Self {
+        AdjacencyList::new(Direction::Directed)
+    }
+
+    /// Creates an empty undirected graph.
+    pub fn undirected() -> Self {
+        AdjacencyList::new(Direction::Undirected)
+    }
+
+    fn entry(&self, vertex: VertexId) -> Option<&Entry<V, W>> {
+        self.vertices.get(vertex)?.as_ref()
+    }
+
+    fn entry_mut(&mut self, vertex: VertexId) -> Option<&mut Entry<V, W>> {
+        self.vertices.get_mut(vertex)?.as_mut()
+    }
+
+    /// Removes the stored edge from `from` to `to` without touching the
+    /// mirror of an undirected edge.
+    fn unlink(&mut self, from: VertexId, to: VertexId) -> Option<W> {
+        let edges = &mut self.entry_mut(from)?.edges;
+        let index = edges.iter().position(|&(target, _)| target == to)?;
+        Some(edges.remove(index).1)
+    }
+
+    /// Splits the graph into one entry per id, each holding the vertex data
+    /// and its outgoing edges.
+    pub(super) fn into_rows(self) -> impl Iterator<Item = Option<(V, Vec<(VertexId, W)>)>> {
+        self.vertices
+            .into_iter()
+            .map(|entry| entry.map(|entry| (entry.data, entry.edges)))
+    }
+}
+
+impl<V, W: Clone> Graph for AdjacencyList<V, W> {
+    type Vertex = V;
+    type Weight = W;
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn vertex_bound(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn add_vertex(&mut self, data: V) -> VertexId {
+        self.vertices.push(Some(Entry {
+            data,
+            edges: Vec::new(),
+        }));
+        self.vertex_count += 1;
+        self.vertices.len() - 1
+    }
+
+    fn remove_vertex(&mut self, vertex: VertexId) -> Option<V> {
+        let entry = self.vertices.get_mut(vertex)?.take()?;
+        self.vertex_count -= 1;
+        self.edge_count -= entry.edges.len();
+        match self.direction {
+            Direction::Directed => {
+                for other in self.vertices.iter_mut().flatten() {
+                    let before = other.edges.len();
+                    other.edges.retain(|&(target, _)| target != vertex);
+                    self.edge_count -= before - other.edges.len();
+                }
+            }
+            Direction::Undirected => {
+                for &(neighbor, _) in &entry.edges {
+                    if neighbor != vertex {
+                        self.unlink(neighbor, vertex);
+                    }
+                }
+            }
+        }
+        Some(entry.data)
+    }
+
+    fn vertex(&self, vertex: VertexId) -> Option<&V> {
+        self.entry(vertex).map(|entry| &entry.data)
+    }
+
+    fn vertex_mut(&mut self, vertex: VertexId) -> Option<&mut V> {
+        self.entry_mut(vertex).map(|entry| &mut entry.data)
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter_map(|(id, entry)| entry.as_ref().map(|_| id))
+    }
+
+    fn add_edge(
+        &mut self,
+        from: VertexId,
+        to: VertexId,
+        weight: W,
+    ) -> Result<Option<W>, GraphError> {
+        for id in [from, to] {
+            if self.entry(id).is_none() {
+                return Err(GraphError::MissingVertex(id));
+            }
+        }
+        let mirror = self.direction == Direction::Undirected && from != to;
+        let edges = &mut self.entry_mut(from).expect("checked above").edges;
+        if let Some((_, old)) = edges.iter_mut().find(|(target, _)| *target == to) {
+            let old = std::mem::replace(old, weight.clone());
+            if mirror {
+                let back = self.entry_mut(to).expect("checked above");
+                if let Some((_, old)) = back.edges.iter_mut().find(|(target, _)| *target == from) {
+                    *old = weight;
+                }
+            }
+            return Ok(Some(old));
+        }
+        if mirror {
+            edges.push((to, weight.clone()));
+            self.entry_mut(to)
+                .expect("checked above")
+                .edges
+                .push((from, weight));
+        } else {
+            edges.push((to, weight));
+        }
+        self.edge_count += 1;
+        Ok(None)
+    }
+
+    fn remove_edge(&mut self, from: VertexId, to: VertexId) -> Option<W> {
+        let weight = self.unlink(from, to)?;
+        if self.direction == Direction::Undirected && from != to {
+            self.unlink(to, from);
+        }
+        self.edge_count -= 1;
+        Some(weight)
+    }
+
+    fn edge(&self, from: VertexId, to: VertexId) -> Option<&W> {
+        self.entry(from)?
+            .edges
+            .iter()
+            .find(|(target, _)| *target == to)
+            .map(|(_, weight)| weight)
+    }
+
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &W)> + '_ {
+        self.entry(vertex)
+            .into_iter()
+            .flat_map(|entry| entry.edges.iter().map(|(to, weight)| (*to, weight)))
+    }
+
+    fn degree(&self, vertex: VertexId) -> usize {
+        self.entry(vertex).map_or(0, |entry| entry.edges.len())
+    }
+}
+
+impl<V, W: Clone> From<AdjacencyMatrix<V, W>> for AdjacencyList<V, W> {
+    /// Keeps every vertex id; neighbors are listed in ascending id order.
+    fn from(matrix: AdjacencyMatrix<V, W>) -> Self {
+        let direction = matrix.direction();
+        let edge_count = matrix.edge_count();
+        let vertex_count = matrix.vertex_count();
+        let vertices = matrix
+            .into_rows()
+            .map(|row| row.map(|(data, edges)| Entry { data, edges }))
+            .collect();
+        AdjacencyList {
+            vertices,
+            direction,
+            vertex_count,
+            edge_count,
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Graph stored as a matrix of edge weights.
+
+use super::{AdjacencyList, Direction, Graph, GraphError, VertexId};
+
+/// A graph that keeps a weight slot for every ordered pair of vertices.
+///
+/// The matrix is square with side `stride`, which doubles as vertices are
+/// added, so it also has rows for removed vertices. An undirected edge
+/// fills both `(a, b)` and `(b, a)`.
+///
+/// | Operation                     | Cost                 |
+/// |-------------------------------|----------------------|
+/// | `add_vertex`                  | O(V) amortized       |
+/// | `add_edge`, `remove_edge`     | O(1)                 |
+/// | `edge`                        | O(1)                 |
+/// | `neighbors`                   | O(V)                 |
+/// | `remove_vertex`               | O(V)                 |
+/// | space                         | O(V²)                |
+///
+/// ```
+/// use data_structure::graph::{AdjacencyList, AdjacencyMatrix, Graph};
+///
+/// let mut flights = AdjacencyMatrix::directed();
+/// let (a, b) = (flights.add_vertex("OPO"), flights.add_vertex("MAD"));
+/// flights.add_edge(a, b, 55).unwrap();
+/// assert!(flights.contains_edge(a, b) && !flights.contains_edge(b, a));
+///
+/// let flights = AdjacencyList::from(flights);
+/// assert_eq!(flights.neighbors(a).collect::<Vec<_>>(), [(b, &55)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjacencyMatrix<V, W> {
+    /// Indexed by id; `None` for removed vertices.
+    vertices: Vec<Option<V>>,
+    /// Weight of the edge from `a` to `b` at `a * stride + b`.
+    cells: Vec<Option<W>>,
+    stride: usize,
+    direction: Direction,
+    vertex_count: usize,
+    edge_count: usize,
+}
+
+impl<V, W> AdjacencyMatrix<V, W> {
+    /// Creates an empty graph.
+    pub fn new(direction: Direction) -> Self {
+        AdjacencyMatrix {
+            vertices: Vec::new(),
+            cells: Vec::new(),
+            stride: 0,
+            direction,
+            vertex_count: 0,
+            edge_count: 0,
+        }
+    }
+
+    /// Creates an empty directed graph.
+    pub fn directed() -> Self {
+        AdjacencyMatrix::new(Direction::Directed)
+    }
+
+    /// Creates an empty undirected graph.
+    pub fn undirected() -> Self {
+        AdjacencyMatrix::new(Direction::Undirected)
+    }
+
+    fn cell(&self, from: VertexId, to: VertexId) -> Option<&Option<W>> {
+        let live = |id: VertexId| matches!(self.vertices.get(id), Some(Some(_)));
+        if !live(from) || !live(to) {
+            return None;
+        }
+        self.cells.get(from * self.stride + to)
+    }
+
+    /// Takes the weight at `(from, to)`, clearing the mirror cell of an
+    /// undirected edge. Both ids must be in range.
+    fn clear(&mut self, from: VertexId, to: VertexId) -> Option<W> {
+        let weight = self.cells[from * self.stride + to].take();
+        if self.direction == Direction::Undirected {
+            self.cells[to * self.stride + from] = None;
+        }
+        weight
+    }
+
+    /// Grows the matrix so that it has a row for vertex `id`.
+    fn reserve(&mut self, id: VertexId) {
+        if id < self.stride {
+            return;
+        }
+        let mut stride = (self.stride * 2).max(4);
+        while stride <= id {
+            stride *= 2;
+        }
+        let mut cells: Vec<Option<W>> = std::iter::repeat_with(|| None)
+            .take(stride * stride)
+            .collect();
+        for (index, weight) in self.cells.drain(..).enumerate() {
+            let (from, to) = (index / self.stride, index % self.stride);
+            cells[from * stride + to] = weight;
+        }
+        self.cells = cells;
+        self.stride = stride;
+    }
+
+    /// Splits the graph into one entry per id, each holding the vertex data
+    /// and its outgoing edges in ascending id order.
+    pub(super) fn into_rows(self) -> impl Iterator<Item = Option<(V, Vec<(VertexId, W)>)>> {
+        let stride = self.stride;
+        let mut cells = self.cells.into_iter();
+        self.vertices.into_iter().map(move |data| {
+            let row: Vec<Option<W>> = cells.by_ref().take(stride).collect();
+            let data = data?;
+            let edges = row
+                .into_iter()
+                .enumerate()
+                .filter_map(|(to, weight)| weight.map(|weight| (to, weight)))
+                .collect();
+            Some((data, edges))
+        })
+    }
+}
+
+impl<V, W: Clone> Graph for AdjacencyMatrix<V, W> {
+    type Vertex = V;
+    type Weight = W;
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn vertex_bound(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn add_vertex(&mut self, data: V) -> VertexId {
+        let id = self.vertices.len();
+        self.reserve(id);
+        self.vertices.push(Some(data));
+        self.vertex_count += 1;
+        id
+    }
+
+    fn remove_vertex(&mut self, vertex: VertexId) -> Option<V> {
+        let data = self.vertices.get_mut(vertex)?.take()?;
+        self.vertex_count -= 1;
+        for other in 0..self.vertices.len() {
+            let outgoing = self.cells[vertex * self.stride + other].take();
+            // For a self-loop this is the cell just taken, so it counts once.
+            let incoming = self.cells[other * self.stride + vertex].take();
+            self.edge_count -= match self.direction {
+                Direction::Directed => outgoing.is_some() as usize + incoming.is_some() as usize,
+                Direction::Undirected => outgoing.is_some() as usize,
+            };
+        }
+        Some(data)
+    }
+
+    fn vertex(&self, vertex: VertexId) -> Option<&V> {
+        self.vertices.get(vertex)?.as_ref()
+    }
+
+    fn vertex_mut(&mut self, vertex: VertexId) -> Option<&mut V> {
+        self.vertices.get_mut(vertex)?.as_mut()
+    }
+
+    fn vertices(&self) -> impl Iterator<Item = VertexId> + '_ {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter_map(|(id, data)| data.as_ref().map(|_| id))
+    }
+
+    fn add_edge(
+        &mut self,
+        from: VertexId,
+        to: VertexId,
+        weight: W,
+    ) -> Result<Option<W>, GraphError> {
+        for id in [from, to] {
+            if self.vertex(id).is_none() {
+                return Err(GraphError::MissingVertex(id));
+            }
+        }
+        if self.direction == Direction::Undirected && from != to {
+            self.cells[to * self.stride + from] = Some(weight.clone());
+        }
+        let old = self.cells[from * self.stride + to].replace(weight);
+        if old.is_none() {
+            self.edge_count += 1;
+        }
+        Ok(old)
+    }
+
+    fn remove_edge(&mut self, from: VertexId, to: VertexId) -> Option<W> {
+        self.cell(from, to)?.as_ref()?;
+        self.edge_count -= 1;
+        self.clear(from, to)
+    }
+
+    fn edge(&self, from: VertexId, to: VertexId) -> Option<&W> {
+        self.cell(from, to)?.as_ref()
+    }
+
+    fn neighbors(&self, vertex: VertexId) -> impl Iterator<Item = (VertexId, &W)> + '_ {
+        let row = match self.vertex(vertex) {
+            Some(_) => &self.cells[vertex * self.stride..][..self.vertices.len()],
+            None => &[],
+        };
+        row.iter()
+            .enumerate()
+            .filter_map(|(to, weight)| weight.as_ref().map(|weight| (to, weight)))
+    }
+}
+
+impl<V, W: Clone> From<AdjacencyList<V, W>> for AdjacencyMatrix<V, W> {
+    /// Keeps every vertex id.
+    fn from(list: AdjacencyList<V, W>) -> Self {
+        let mut matrix = AdjacencyMatrix::new(list.direction());
+        let (vertex_count, edge_count) = (list.vertex_count(), list.edge_count());
+        let rows: Vec<_> = list.into_rows().collect();
+        if let Some(last) = rows.len().checked_sub(1) {
+            matrix.reserve(last);
+        }
+        for (from, row) in rows.into_iter().enumerate() {
+            let Some((data, edges)) = row else {
+                matrix.vertices.push(None);
+                continue;
+            };
+            matrix.vertices.push(Some(data));
+            for (to, weight) in edges {
+                matrix.cells[from * matrix.stride + to] = Some(weight);
+            }
+        }
+        matrix.vertex_count = vertex_count;
+        matrix.edge_count = edge_count;
+        matrix
+    }
+}
@@ -0,0 +1,618 @@
+//! Single-source shortest paths.
+//!
+//! The README names Dijkstra's algorithm among the graph algorithms.
+//! [`dijkstra`] settles vertices in order of distance, taking the closest
+//! from a [`PriorityQueue`]; [`a_star`] orders the queue by distance plus an
+//! estimate of what is left to a target, and stops there; [`bellman_ford`]
+//! relaxes every edge until nothing changes, which also copes with negative
+//! weights and finds the negative cycles that leave no shortest path.
+//!
+//! Edge weights are the distances, so all three take any [`Graph`] whose
+//! `Weight` is a [`Distance`], and return the distances together with the
+//! tree of shortest paths as [`ShortestPaths`].
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::Add;
+
+use super::traversal::Cycle;
+use super::{Graph, VertexId};
+use crate::linear::PriorityQueue;
+
+/// An edge weight that can be summed along a path.
+///
+/// Sums use `+`, so integer distances that overflow panic in debug builds.
+/// Float distances must not be NaN.
+pub trait Distance: Copy + PartialOrd + Add<Output = Self> {
+    /// Length of the empty path.
+    const ZERO: Self;
+}
+
+macro_rules! distance {
+    ($($ty:ty => $zero:expr),* $(,)?) => {
+        $(impl Distance for $ty {
+            const ZERO: Self = $zero;
+        })*
+    };
+}
+
+distance! {
+    i8 => 0, i16 => 0, i32 => 0, i64 => 0, i128 => 0, isize => 0,
+    u8 => 0, u16 => 0, u32 => 0, u64 => 0, u128 => 0, usize => 0,
+    f32 => 0.0, f64 => 0.0,
+}
+
+/// Why no shortest paths were found.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathError {
+    /// The source or target is not a vertex of the graph.
+    MissingVertex(VertexId),
+    /// Dijkstra's algorithm or A* met the edge `(from, to)` with a weight
+    /// below zero.
+    NegativeWeight(VertexId, VertexId),
+    /// A cycle of negative total weight is reachable from the source, so
+    /// paths through it get shorter without end.
+    NegativeCycle(Cycle),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::MissingVertex(id) => write!(f, "no vertex {id}"),
+            PathError::NegativeWeight(from, to) => {
+                write!(f, "edge {from} -> {to} has a negative weight")
+            }
+            PathError::NegativeCycle(cycle) => write!(f, "negative {cycle}"),
+        }
+    }
+}
+
+impl Error for PathError {}
+
+/// Distances from one source and the tree of shortest paths that achieve
+/// them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPaths<D> {
+    source: VertexId,
+    distances: Vec<Option<D>>,
+    parents: Vec<Option<VertexId>>,
+}
+
+impl<D: Distance> ShortestPaths<D> {
+    fn new(source: VertexId, bound: usize) -> Self {
+        let mut distances = vec![None; bound];
+        distances[source] = Some(D::ZERO);
+        ShortestPaths {
+            source,
+            distances,
+            parents: vec![None; bound],
+        }
+    }
+
+    /// Records a path to `to` through `from` if it is shorter than the
+    /// best so far.
+    fn relax(&mut self, from: VertexId, to: VertexId, distance: D) -> bool {
+        if self.distances[to].is_some_and(|best| distance >= best) {
+            return false;
+        }
+        self.distances[to] = Some(distance);
+        self.parents[to] = Some(from);
+        true
+    }
+
+    /// The vertex the paths start from.
+    pub fn source(&self) -> VertexId {
+        self.source
+    }
+
+    /// Length of the shortest path to `vertex`, or `None` if it was not
+    /// reached.
+    pub fn distance(&self, vertex: VertexId) -> Option<D> {
+        *self.distances.get(vertex)?
+    }
+
+    /// The vertex before `vertex` on its shortest path; `None` for the
+    /// source and vertices not reached.
+    pub fn parent(&self, vertex: VertexId) -> Option<VertexId> {
+        *self.parents.get(vertex)?
+    }
+
+    /// Every vertex reached, in ascending id order, with its distance.
+    pub fn distances(&self) -> impl Iterator<Item = (VertexId, D)> + '_ {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter_map(|(vertex, distance)| Some((vertex, (*distance)?)))
+    }
+
+    /// The vertices of the shortest path from the source to `vertex`, both
+    /// included, or `None` if `vertex` was not reached.
+    pub fn path_to(&self, vertex: VertexId) -> Option<Vec<VertexId>> {
+        self.distance(vertex)?;
+        let mut path = vec![vertex];
+        let mut current = vertex;
+        while let Some(parent) = self.parent(current) {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Orders queue entries by a [`Distance`], which need not be `Ord`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Key<D>(D);
+
+impl<D: PartialEq> Eq for Key<D> {}
+
+impl<D: PartialOrd> PartialOrd for Key<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: PartialOrd> Ord for Key<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+fn check<G: Graph>(graph: &G, vertex: VertexId) -> Result<(), PathError> {
+    match graph.contains_vertex(vertex) {
+        true => Ok(()),
+        false => Err(PathError::MissingVertex(vertex)),
+    }
+}
+
+/// Best-first search shared by Dijkstra's algorithm and A*. Only vertices
+/// that were taken from the queue make it into the result.
+fn search<G, H>(
+    graph: &G,
+    source: VertexId,
+    target: Option<VertexId>,
+    mut heuristic: H,
+) -> Result<ShortestPaths<G::Weight>, PathError>
+where
+    G: Graph,
+    G::Weight: Distance,
+    H: FnMut(VertexId) -> G::Weight,
+{
+    check(graph, source)?;
+    if let Some(target) = target {
+        check(graph, target)?;
+    }
+    let zero = G::Weight::ZERO;
+    let mut paths = ShortestPaths::new(source, graph.vertex_bound());
+    let mut settled = vec![false; graph.vertex_bound()];
+    let mut queue = PriorityQueue::new();
+    queue.push(Key(heuristic(source)), (source, zero));
+    while let Some((_, (vertex, distance))) = queue.pop() {
+        // A shorter path was queued after this one.
+        if paths.distances[vertex].is_some_and(|best| distance > best) {
+            continue;
+        }
+        settled[vertex] = true;
+        if target == Some(vertex) {
+            break;
+        }
+        for (to, &weight) in graph.neighbors(vertex) {
+            if weight < zero {
+                return Err(PathError::NegativeWeight(vertex, to));
+            }
+            let through = distance + weight;
+            if paths.relax(vertex, to, through) {
+                queue.push(Key(through + heuristic(to)), (to, through));
+            }
+        }
+    }
+    for (vertex, settled) in settled.into_iter().enumerate() {
+        if !settled {
+            paths.distances[vertex] = None;
+            paths.parents[vertex] = None;
+        }
+    }
+    Ok(paths)
+}
+
+/// Shortest paths from `source` to every vertex it reaches, by Dijkstra's
+/// algorithm.
+///
+/// Fails on reaching an edge with a negative weight; [`bellman_ford`]
+/// handles those.
+///
+/// ```
+/// use data_structure::graph::shortest_path::dijkstra;
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut roads = AdjacencyList::undirected();
+/// let [porto, coimbra, lisbon, faro] = ["Porto", "Coimbra", "Lisbon", "Faro"]
+///     .map(|city| roads.add_vertex(city));
+/// roads.add_edge(porto, coimbra, 120).unwrap();
+/// roads.add_edge(coimbra, lisbon, 205).unwrap();
+/// roads.add_edge(porto, lisbon, 340).unwrap();
+/// roads.add_edge(lisbon, faro, 280).unwrap();
+///
+/// let paths = dijkstra(&roads, porto).unwrap();
+/// assert_eq!(paths.distance(faro), Some(605));
+/// assert_eq!(paths.path_to(faro), Some(vec![porto, coimbra, lisbon, faro]));
+/// ```
+///
+/// | Operation            | Cost                            |
+/// |----------------------|---------------------------------|
+/// | whole search         | O((V + E) log V) for a list     |
+/// | space                | O(V + E)                        |
+pub fn dijkstra<G>(graph: &G, source: VertexId) -> Result<ShortestPaths<G::Weight>, PathError>
+where
+    G: Graph,
+    G::Weight: Distance,
+{
+    search(graph, source, None, |_| G::Weight::ZERO)
+}
+
+/// Shortest path from `source` to `target` by A*, guided by `heuristic`,
+/// an estimate of the distance from a vertex to `target`.
+///
+/// The path to `target` is shortest as long as the heuristic never
+/// overestimates. The search stops once `target` is reached, so the result
+/// only covers the vertices it settled on the way; their distances are
+/// exact too if the heuristic is also consistent, never dropping by more
+/// than an edge's weight along that edge. A heuristic of zero makes this
+/// Dijkstra's algorithm. Fails on reaching an edge with a negative weight.
+///
+/// ```
+/// use data_structure::graph::shortest_path::a_star;
+/// use data_structure::graph::{AdjacencyMatrix, Graph};
+///
+/// // A 4x4 grid of unit-length streets.
+/// let mut grid = AdjacencyMatrix::undirected();
+/// let cells: Vec<_> = (0..16).map(|cell| grid.add_vertex((cell % 4, cell / 4))).collect();
+/// for cell in 0..16 {
+///     if cell % 4 < 3 {
+///         grid.add_edge(cells[cell], cells[cell + 1], 1).unwrap();
+///     }
+///     if cell < 12 {
+///         grid.add_edge(cells[cell], cells[cell + 4], 1).unwrap();
+///     }
+/// }
+///
+/// let (start, goal) = (cells[0], cells[15]);
+/// let &(gx, gy) = grid.vertex(goal).unwrap();
+/// let manhattan = |cell| {
+///     let &(x, y): &(i32, i32) = grid.vertex(cell).unwrap();
+///     (gx - x).abs() + (gy - y).abs()
+/// };
+/// let paths = a_star(&grid, start, goal, manhattan).unwrap();
+/// assert_eq!(paths.distance(goal), Some(6));
+/// assert_eq!(paths.path_to(goal).unwrap().len(), 7);
+/// ```
+pub fn a_star<G, H>(
+    graph: &G,
+    source: VertexId,
+    target: VertexId,
+    heuristic: H,
+) -> Result<ShortestPaths<G::Weight>, PathError>
+where
+    G: Graph,
+    G::Weight: Distance,
+    H: FnMut(VertexId) -> G::Weight,
+{
+    search(graph, source, Some(target), heuristic)
+}
+
+/// Shortest paths from `source` to every vertex it reaches, by the
+/// Bellman-Ford algorithm, which allows negative weights.
+///
+/// Fails with the offending cycle if a cycle of negative total weight is
+/// reachable from `source`. In an undirected graph an edge leads both
+/// ways, so a single negative edge is such a cycle.
+///
+/// ```
+/// use data_structure::graph::shortest_path::{bellman_ford, PathError};
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut rates = AdjacencyList::directed();
+/// let [a, b, c] = [(); 3].map(|()| rates.add_vertex(()));
+/// rates.add_edge(a, b, 4).unwrap();
+/// rates.add_edge(a, c, 5).unwrap();
+/// rates.add_edge(c, b, -3).unwrap();
+/// assert_eq!(bellman_ford(&rates, a).unwrap().distance(b), Some(2));
+///
+/// rates.add_edge(b, c, 1).unwrap();
+/// let Err(PathError::NegativeCycle(cycle)) = bellman_ford(&rates, a) else {
+///     panic!("expected a negative cycle");
+/// };
+/// assert_eq!(cycle.vertices.len(), 2);
+/// ```
+///
+/// | Operation            | Cost                            |
+/// |----------------------|---------------------------------|
+/// | whole search         | O(V · E) for a list             |
+/// | space                | O(V)                            |
+pub fn bellman_ford<G>(graph: &G, source: VertexId) -> Result<ShortestPaths<G::Weight>, PathError>
+where
+    G: Graph,
+    G::Weight: Distance,
+{
+    check(graph, source)?;
+    let mut paths = ShortestPaths::new(source, graph.vertex_bound());
+    // Without negative cycles every shortest path has fewer edges than
+    // there are vertices, so a pass that still shortens one proves a cycle.
+    let mut shortened = None;
+    for _ in 0..graph.vertex_count() {
+        shortened = None;
+        for from in graph.vertices() {
+            let Some(distance) = paths.distances[from] else {
+                continue;
+            };
+            for (to, &weight) in graph.neighbors(from) {
+                if paths.relax(from, to, distance + weight) {
+                    shortened = Some(to);
+                }
+            }
+        }
+        if shortened.is_none() {
+            return Ok(paths);
+        }
+    }
+    let Some(mut vertex) = shortened else {
+        return Ok(paths);
+    };
+    // Walking back as many edges as there are vertices from a vertex that
+    // was still shortened ends up on the cycle.
+    for _ in 0..graph.vertex_count() {
+        vertex = paths
+            .parent(vertex)
+            .expect("shortened vertices have parents");
+    }
+    let mut vertices = vec![vertex];
+    let mut current = paths.parent(vertex).expect("cycle vertices have parents");
+    while current != vertex {
+        vertices.push(current);
+        current = paths.parent(current).expect("cycle vertices have parents");
+    }
+    vertices.reverse();
+    Err(PathError::NegativeCycle(Cycle { vertices }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{AdjacencyList, AdjacencyMatrix, Direction};
+    use crate::rng::SplitMix64;
+
+    fn graph(
+        direction: Direction,
+        vertices: usize,
+        edges: &[(VertexId, VertexId, i64)],
+    ) -> AdjacencyList<(), i64> {
+        let mut graph = AdjacencyList::new(direction);
+        for _ in 0..vertices {
+            graph.add_vertex(());
+        }
+        for &(from, to, weight) in edges {
+            graph.add_edge(from, to, weight).unwrap();
+        }
+        graph
+    }
+
+    /// A random graph with non-negative weights, a few of its vertices
+    /// removed.
+    fn random_graph(direction: Direction, seed: u64) -> AdjacencyList<(), i64> {
+        let mut rng = SplitMix64::new(seed);
+        let vertices = 30;
+        let mut graph = graph(direction, vertices, &[]);
+        for _ in 0..60 {
+            let (from, to) = (rng.below(vertices), rng.below(vertices));
+            graph.add_edge(from, to, rng.below(100) as i64).unwrap();
+        }
+        for _ in 0..3 {
+            graph.remove_vertex(rng.below(vertices));
+        }
+        graph
+    }
+
+    /// Checks that every path follows edges of `graph` and adds up to its
+    /// distance.
+    fn check_paths<G: Graph<Weight = i64>>(graph: &G, paths: &ShortestPaths<i64>) {
+        for (vertex, distance) in paths.distances() {
+            let path = paths.path_to(vertex).unwrap();
+            assert_eq!(path.first(), Some(&paths.source()));
+            assert_eq!(path.last(), Some(&vertex));
+            let length: i64 = path
+                .windows(2)
+                .map(|step| *graph.edge(step[0], step[1]).unwrap())
+                .sum();
+            assert_eq!(length, distance);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn dijkstra_agrees_with_bellman_ford_on_random_graphs() {
+        for seed in 0..20 {
+            let direction = match seed % 2 {
+                0 => Direction::Directed,
+                _ => Direction::Undirected,
+            };
+            let list = random_graph(direction, seed);
+            let matrix = AdjacencyMatrix::from(list.clone());
+            for source in list.vertices() {
+                let expected = bellman_ford(&list, source).unwrap();
+                check_paths(&list, &expected);
+                for found in [dijkstra(&list, source), dijkstra(&matrix, source)] {
+                    let found = found.unwrap();
+                    assert!(found.distances().eq(expected.distances()));
+                    check_paths(&list, &found);
+                }
+                for target in list.vertices() {
+                    let found = a_star(&list, source, target, |_| 0).unwrap();
+                    assert_eq!(found.distance(target), expected.distance(target));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn path_to_covers_the_source_and_unreached_vertices() {
+        let mut graph = graph(Direction::Directed, 4, &[(0, 1, 2), (2, 0, 1)]);
+        graph.remove_vertex(3);
+        for paths in [
+            dijkstra(&graph, 0),
+            bellman_ford(&graph, 0),
+            a_star(&graph, 0, 1, |_| 0),
+        ] {
+            let paths = paths.unwrap();
+            assert_eq!(paths.source(), 0);
+            assert_eq!(paths.path_to(0), Some(vec![0]));
+            assert_eq!(paths.distance(0), Some(0));
+            assert_eq!(paths.parent(0), None);
+            assert_eq!(paths.path_to(1), Some(vec![0, 1]));
+            for unreached in [2, 3, 99] {
+                assert_eq!(paths.path_to(unreached), None);
+                assert_eq!(paths.distance(unreached), None);
+                assert_eq!(paths.parent(unreached), None);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_missing_vertices() {
+        let mut graph = graph(Direction::Directed, 2, &[(0, 1, 1)]);
+        graph.remove_vertex(1);
+        assert_eq!(dijkstra(&graph, 1), Err(PathError::MissingVertex(1)));
+        assert_eq!(bellman_ford(&graph, 5), Err(PathError::MissingVertex(5)));
+        assert_eq!(
+            a_star(&graph, 0, 1, |_| 0),
+            Err(PathError::MissingVertex(1))
+        );
+        assert_eq!(PathError::MissingVertex(1).to_string(), "no vertex 1");
+    }
+
+    #[test]
+    fn dijkstra_and_a_star_reject_negative_weights() {
+        let graph = graph(Direction::Directed, 4, &[(0, 1, 1), (1, 2, -1), (3, 0, -5)]);
+        assert_eq!(dijkstra(&graph, 0), Err(PathError::NegativeWeight(1, 2)));
+        assert_eq!(
+            a_star(&graph, 0, 2, |_| 0),
+            Err(PathError::NegativeWeight(1, 2))
+        );
+        assert_eq!(
+            PathError::NegativeWeight(1, 2).to_string(),
+            "edge 1 -> 2 has a negative weight"
+        );
+        // Only edges that are reached count.
+        assert_eq!(dijkstra(&graph, 2).unwrap().distances().count(), 1);
+        assert_eq!(a_star(&graph, 0, 1, |_| 0).unwrap().distance(1), Some(1));
+        assert_eq!(bellman_ford(&graph, 3).unwrap().distance(2), Some(-5));
+    }
+
+    #[test]
+    fn a_star_finds_the_shortest_path_with_an_inconsistent_heuristic() {
+        // The estimate for `a` is exact but drops by more than the edge to
+        // `c`, so `c` is first settled at distance 3 through the direct edge
+        // and must be opened again once `a` shows the way at distance 2.
+        let [s, a, c, t] = [0, 1, 2, 3];
+        let graph = graph(
+            Direction::Directed,
+            4,
+            &[(s, a, 1), (a, c, 1), (s, c, 3), (c, t, 5)],
+        );
+        let heuristic = |vertex| if vertex == a { 6 } else { 0 };
+        let paths = a_star(&graph, s, t, heuristic).unwrap();
+        assert_eq!(paths.distance(t), Some(7));
+        assert_eq!(paths.path_to(t), Some(vec![s, a, c, t]));
+        assert_eq!(paths.distance(c), Some(2));
+        check_paths(&graph, &paths);
+    }
+
+    #[test]
+    fn a_star_stops_at_the_target() {
+        let graph = graph(Direction::Undirected, 4, &[(0, 1, 1), (1, 2, 1), (2, 3, 1)]);
+        let paths = a_star(&graph, 0, 1, |_| 0).unwrap();
+        assert_eq!(paths.path_to(1), Some(vec![0, 1]));
+        assert_eq!(paths.distance(3), None);
+    }
+
+    #[test]
+    fn bellman_ford_handles_negative_weights() {
+        let graph = graph(
+            Direction::Directed,
+            4,
+            &[(0, 1, 4), (0, 2, 1), (2, 1, -2), (1, 3, -1)],
+        );
+        let paths = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(
+            paths.distances().collect::<Vec<_>>(),
+            [(0, 0), (1, -1), (2, 1), (3, -2)]
+        );
+        assert_eq!(paths.path_to(3), Some(vec![0, 2, 1, 3]));
+    }
+
+    /// Asserts that `result` is a negative cycle of `graph` on `vertices`,
+    /// in some rotation.
+    fn assert_negative_cycle<G: Graph<Weight = i64>>(
+        graph: &G,
+        result: Result<ShortestPaths<i64>, PathError>,
+        vertices: &[VertexId],
+    ) {
+        let Err(PathError::NegativeCycle(cycle)) = result else {
+            panic!("expected a negative cycle, got {result:?}");
+        };
+        let mut sorted = cycle.vertices.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vertices);
+        let closed = cycle.vertices.iter().chain(cycle.vertices.first());
+        let length: i64 = closed
+            .clone()
+            .zip(closed.skip(1))
+            .map(|(&from, &to)| *graph.edge(from, to).unwrap())
+            .sum();
+        assert!(length < 0);
+    }
+
+    #[test]
+    fn bellman_ford_returns_a_reachable_negative_cycle() {
+        let graph = graph(
+            Direction::Directed,
+            5,
+            &[(0, 1, 1), (1, 2, 1), (2, 3, -1), (3, 1, -1), (3, 4, 1)],
+        );
+        assert_negative_cycle(&graph, bellman_ford(&graph, 0), &[1, 2, 3]);
+        let undirected = self::graph(Direction::Undirected, 3, &[(0, 1, 2), (1, 2, -1)]);
+        assert_negative_cycle(&undirected, bellman_ford(&undirected, 0), &[1, 2]);
+        let looped = self::graph(Direction::Directed, 2, &[(0, 1, 2), (1, 1, -1)]);
+        assert_negative_cycle(&looped, bellman_ford(&looped, 0), &[1]);
+    }
+
+    #[test]
+    fn bellman_ford_ignores_a_negative_cycle_out_of_reach() {
+        let graph = graph(
+            Direction::Directed,
+            5,
+            &[(0, 1, 3), (1, 4, 2), (2, 3, -1), (3, 2, -1), (3, 1, 1)],
+        );
+        let paths = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(
+            paths.distances().collect::<Vec<_>>(),
+            [(0, 0), (1, 3), (4, 5)]
+        );
+        assert_eq!(paths.path_to(2), None);
+        assert_negative_cycle(&graph, bellman_ford(&graph, 2), &[2, 3]);
+    }
+
+    #[test]
+    fn works_with_float_distances() {
+        let mut graph = AdjacencyMatrix::undirected();
+        let [a, b, c] = [(); 3].map(|()| graph.add_vertex(()));
+        graph.add_edge(a, b, 0.5).unwrap();
+        graph.add_edge(b, c, 0.25).unwrap();
+        graph.add_edge(a, c, 1.0).unwrap();
+        assert_eq!(dijkstra(&graph, a).unwrap().distance(c), Some(0.75));
+        assert_eq!(
+            bellman_ford(&graph, c).unwrap().path_to(a),
+            Some(vec![c, b, a])
+        );
+    }
+}
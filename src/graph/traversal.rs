@@ -0,0 +1,950 @@
+//! Depth-first and breadth-first traversal.
+//!
+//! `Linear/Stack.md` walks a graph depth-first with an explicit stack and
+//! `Linear/Queue.md` breadth-first with a queue; both return the order in
+//! which vertices were reached. [`Dfs`] and [`Bfs`] keep the explicit stack
+//! and queue, so no graph is too deep for them, but yield [`Event`]s lazily
+//! instead: each vertex is discovered and finished, and each edge examined
+//! is classified. Both record the predecessor and depth of every vertex
+//! they reach.
+//!
+//! [`topological_sort`], [`find_cycle`] and [`connected_components`] are
+//! built on top of them.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use super::{Direction, Graph, VertexId};
+use crate::linear::stack::{ArrayStack, Stack};
+
+/// Something a traversal did.
+///
+/// In an undirected graph every edge is reported once, from the end
+/// examined first, and the edge back to a vertex's parent is never
+/// reported. BFS never finds forward edges, nor back edges in an undirected
+/// graph other than self-loops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The vertex was reached for the first time.
+    Discover(VertexId),
+    /// The edge `(from, to)` reached `to`, which is discovered next.
+    TreeEdge(VertexId, VertexId),
+    /// The edge leads to an ancestor of `from` in the traversal tree, or
+    /// to `from` itself: a cycle.
+    BackEdge(VertexId, VertexId),
+    /// The edge leads to a finished descendant. Directed DFS only.
+    ForwardEdge(VertexId, VertexId),
+    /// The edge leads to an already discovered vertex that is neither
+    /// ancestor nor descendant. Undirected DFS never finds one.
+    CrossEdge(VertexId, VertexId),
+    /// Every edge leaving the vertex has been examined.
+    Finish(VertexId),
+}
+
+/// What a traversal knows about a vertex it has reached.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    parent: Option<VertexId>,
+    /// An ancestor to skip to when looking for the one at a given depth;
+    /// the vertex itself for a source.
+    jump: VertexId,
+    depth: usize,
+    /// Position in discovery order.
+    order: usize,
+    finished: bool,
+}
+
+/// Per-vertex marks shared by both traversals.
+#[derive(Debug, Clone)]
+struct Marks {
+    marks: Vec<Option<Mark>>,
+    discovered: usize,
+}
+
+impl Marks {
+    fn new(bound: usize) -> Self {
+        Marks {
+            marks: vec![None; bound],
+            discovered: 0,
+        }
+    }
+
+    fn get(&self, vertex: VertexId) -> Option<&Mark> {
+        self.marks.get(vertex)?.as_ref()
+    }
+
+    fn discover(&mut self, vertex: VertexId, parent: Option<VertexId>) {
+        let (jump, depth) = match parent {
+            None => (vertex, 0),
+            Some(parent) => (self.jump_from(parent), self.mark(parent).depth + 1),
+        };
+        self.marks[vertex] = Some(Mark {
+            parent,
+            jump,
+            depth,
+            order: self.discovered,
+            finished: false,
+        });
+        self.discovered += 1;
+    }
+
+    fn mark(&self, vertex: VertexId) -> &Mark {
+        self.get(vertex).expect("vertex was discovered")
+    }
+
+    /// Jump pointer of a child of `parent`. Jumps skip runs of ancestors
+    /// whose lengths follow the skew-binary numbers, so any ancestor is
+    /// found in O(log depth) steps.
+    fn jump_from(&self, parent: VertexId) -> VertexId {
+        let first = self.mark(parent);
+        let second = self.mark(first.jump);
+        let third = self.mark(second.jump);
+        if first.depth - second.depth == second.depth - third.depth {
+            second.jump
+        } else {
+            parent
+        }
+    }
+
+    /// Returns `true` if `ancestor` is `vertex` or one of its ancestors;
+    /// both must have been discovered.
+    fn is_ancestor(&self, ancestor: VertexId, vertex: VertexId) -> bool {
+        let depth = self.mark(ancestor).depth;
+        let mut current = vertex;
+        loop {
+            let mark = self.mark(current);
+            if mark.depth <= depth {
+                return mark.depth == depth && current == ancestor;
+            }
+            current = match self.mark(mark.jump).depth >= depth {
+                true => mark.jump,
+                false => mark.parent.expect("only sources have depth 0"),
+            };
+        }
+    }
+
+    fn finish(&mut self, vertex: VertexId) {
+        if let Some(mark) = &mut self.marks[vertex] {
+            mark.finished = true;
+        }
+    }
+}
+
+type Neighbors<'a> = Box<dyn Iterator<Item = VertexId> + 'a>;
+
+fn neighbors<G: Graph>(graph: &G, vertex: VertexId) -> Neighbors<'_> {
+    Box::new(graph.neighbors(vertex).map(|(to, _)| to))
+}
+
+/// Sources that are vertices of `graph`, in the order given, to be taken
+/// from the back.
+fn sources<G: Graph>(graph: &G, sources: impl IntoIterator<Item = VertexId>) -> Vec<VertexId> {
+    let mut sources: Vec<VertexId> = sources
+        .into_iter()
+        .filter(|&source| graph.contains_vertex(source))
+        .collect();
+    sources.reverse();
+    sources
+}
+
+macro_rules! tree_accessors {
+    () => {
+        /// Returns `true` once `vertex` has been discovered.
+        pub fn is_discovered(&self, vertex: VertexId) -> bool {
+            self.marks.get(vertex).is_some()
+        }
+
+        /// Returns `true` once `vertex` has been finished.
+        pub fn is_finished(&self, vertex: VertexId) -> bool {
+            self.marks.get(vertex).is_some_and(|mark| mark.finished)
+        }
+
+        /// The vertex `vertex` was discovered from; `None` for a source or a
+        /// vertex not yet discovered.
+        pub fn parent(&self, vertex: VertexId) -> Option<VertexId> {
+            self.marks.get(vertex)?.parent
+        }
+
+        /// Number of tree edges between the source `vertex` was reached from
+        /// and `vertex`.
+        pub fn depth(&self, vertex: VertexId) -> Option<usize> {
+            self.marks.get(vertex).map(|mark| mark.depth)
+        }
+    };
+}
+
+struct Frame<'a> {
+    vertex: VertexId,
+    neighbors: Neighbors<'a>,
+}
+
+/// Lazy depth-first traversal.
+///
+/// Sources are explored one after the other; a source already reached from
+/// an earlier one is skipped, and so are ids that are not vertices of the
+/// graph. Neighbors are visited in the order [`Graph::neighbors`] lists
+/// them. The parent and depth of a vertex are known from its
+/// [`Discover`](Event::Discover) event on.
+///
+/// ```
+/// use data_structure::graph::traversal::{Dfs, Event};
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut graph = AdjacencyList::directed();
+/// let [a, b, c] = [(); 3].map(|()| graph.add_vertex(()));
+/// graph.add_edge(a, b, ()).unwrap();
+/// graph.add_edge(b, c, ()).unwrap();
+/// graph.add_edge(c, a, ()).unwrap();
+///
+/// let mut dfs = Dfs::new(&graph, [a]);
+/// assert!(dfs.by_ref().any(|event| event == Event::BackEdge(c, a)));
+/// assert_eq!(dfs.depth(c), Some(2));
+/// assert_eq!(dfs.parent(c), Some(b));
+/// ```
+///
+/// | Operation            | Cost                            |
+/// |----------------------|---------------------------------|
+/// | whole traversal      | O(V + E) for an adjacency list  |
+/// | space                | O(V)                            |
+pub struct Dfs<'a, G: Graph> {
+    graph: &'a G,
+    sources: Vec<VertexId>,
+    stack: ArrayStack<Frame<'a>>,
+    marks: Marks,
+    /// Discovery that follows a tree edge.
+    pending: Option<Event>,
+}
+
+impl<'a, G: Graph> Dfs<'a, G> {
+    /// Starts a traversal of `graph` from `sources`.
+    pub fn new(graph: &'a G, sources: impl IntoIterator<Item = VertexId>) -> Self {
+        Dfs {
+            graph,
+            sources: self::sources(graph, sources),
+            stack: ArrayStack::new(),
+            marks: Marks::new(graph.vertex_bound()),
+            pending: None,
+        }
+    }
+
+    /// Starts a traversal that reaches every vertex, taking sources in
+    /// ascending id order.
+    pub fn all(graph: &'a G) -> Self {
+        Dfs::new(graph, graph.vertices())
+    }
+
+    tree_accessors!();
+
+    fn enter(&mut self, vertex: VertexId, parent: Option<VertexId>) {
+        self.marks.discover(vertex, parent);
+        self.stack.push(Frame {
+            vertex,
+            neighbors: neighbors(self.graph, vertex),
+        });
+    }
+
+    fn classify(&self, from: VertexId, to: VertexId) -> Option<Event> {
+        let (source, target) = (self.marks.get(from)?, self.marks.get(to)?);
+        match self.graph.direction() {
+            Direction::Undirected if !target.finished && source.parent != Some(to) => {
+                Some(Event::BackEdge(from, to))
+            }
+            // Either the edge to the parent, or one already reported as a
+            // back edge from its other end.
+            Direction::Undirected => None,
+            Direction::Directed if !target.finished => Some(Event::BackEdge(from, to)),
+            Direction::Directed if source.order < target.order => {
+                Some(Event::ForwardEdge(from, to))
+            }
+            Direction::Directed => Some(Event::CrossEdge(from, to)),
+        }
+    }
+}
+
+impl<G: Graph> Iterator for Dfs<'_, G> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        loop {
+            let Ok(frame) = self.stack.peek_mut() else {
+                let source = self.sources.pop()?;
+                if self.marks.get(source).is_none() {
+                    self.enter(source, None);
+                    return Some(Event::Discover(source));
+                }
+                continue;
+            };
+            let from = frame.vertex;
+            let Some(to) = frame.neighbors.next() else {
+                let _ = self.stack.pop();
+                self.marks.finish(from);
+                return Some(Event::Finish(from));
+            };
+            if self.marks.get(to).is_none() {
+                self.enter(to, Some(from));
+                self.pending = Some(Event::Discover(to));
+                return Some(Event::TreeEdge(from, to));
+            }
+            if let Some(event) = self.classify(from, to) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Lazy breadth-first traversal.
+///
+/// All sources are discovered first, at depth 0, so each vertex ends up at
+/// the depth of its nearest source. Ids that are not vertices of the graph
+/// are skipped. A vertex is finished once its edges have been examined,
+/// in the order it was discovered.
+///
+/// An edge that does not reach a new vertex is a back edge if it leads to
+/// an ancestor and a cross edge otherwise. All the ancestors of a vertex
+/// are finished by the time its edges are examined, so only edges to
+/// finished vertices of a directed graph need an ancestor lookup, which
+/// follows jump pointers up the tree in O(log V) steps.
+///
+/// ```
+/// use data_structure::graph::traversal::Bfs;
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut graph = AdjacencyList::undirected();
+/// let [a, b, c, d] = [(); 4].map(|()| graph.add_vertex(()));
+/// for (from, to) in [(a, b), (b, c), (c, d)] {
+///     graph.add_edge(from, to, ()).unwrap();
+/// }
+///
+/// let mut bfs = Bfs::new(&graph, [a, d]);
+/// bfs.by_ref().for_each(drop);
+/// assert_eq!(bfs.depth(b), Some(1));
+/// assert_eq!(bfs.parent(c), Some(d));
+/// ```
+///
+/// | Operation            | Cost                                   |
+/// |----------------------|----------------------------------------|
+/// | whole traversal      | O(V + E) for an adjacency list, plus   |
+/// |                      | O(log V) per directed non-tree edge    |
+/// | space                | O(V)                                   |
+pub struct Bfs<'a, G: Graph> {
+    graph: &'a G,
+    sources: Vec<VertexId>,
+    queue: VecDeque<VertexId>,
+    /// The vertex whose edges are being examined.
+    current: Option<Frame<'a>>,
+    marks: Marks,
+    pending: Option<Event>,
+}
+
+impl<'a, G: Graph> Bfs<'a, G> {
+    /// Starts a traversal of `graph` from `sources`.
+    pub fn new(graph: &'a G, sources: impl IntoIterator<Item = VertexId>) -> Self {
+        Bfs {
+            graph,
+            sources: self::sources(graph, sources),
+            queue: VecDeque::new(),
+            current: None,
+            marks: Marks::new(graph.vertex_bound()),
+            pending: None,
+        }
+    }
+
+    tree_accessors!();
+
+    fn enter(&mut self, vertex: VertexId, parent: Option<VertexId>) {
+        self.marks.discover(vertex, parent);
+        self.queue.push_back(vertex);
+    }
+}
+
+impl<G: Graph> Iterator for Bfs<'_, G> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        while let Some(source) = self.sources.pop() {
+            if self.marks.get(source).is_none() {
+                self.enter(source, None);
+                return Some(Event::Discover(source));
+            }
+        }
+        loop {
+            let Some(frame) = &mut self.current else {
+                let vertex = self.queue.pop_front()?;
+                self.current = Some(Frame {
+                    vertex,
+                    neighbors: neighbors(self.graph, vertex),
+                });
+                continue;
+            };
+            let from = frame.vertex;
+            let Some(to) = frame.neighbors.next() else {
+                self.current = None;
+                self.marks.finish(from);
+                return Some(Event::Finish(from));
+            };
+            let Some(mark) = self.marks.get(to) else {
+                self.enter(to, Some(from));
+                self.pending = Some(Event::Discover(to));
+                return Some(Event::TreeEdge(from, to));
+            };
+            if from == to {
+                return Some(Event::BackEdge(from, to));
+            }
+            match self.graph.direction() {
+                // Reported from `to` when its edges were examined.
+                Direction::Undirected if mark.finished => {}
+                Direction::Directed if mark.finished && self.marks.is_ancestor(to, from) => {
+                    return Some(Event::BackEdge(from, to));
+                }
+                _ => return Some(Event::CrossEdge(from, to)),
+            }
+        }
+    }
+}
+
+/// A cycle found in a graph, as the vertices along it; the last one has an
+/// edge back to the first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub vertices: Vec<VertexId>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cycle")?;
+        for (index, vertex) in self
+            .vertices
+            .iter()
+            .chain(self.vertices.first())
+            .enumerate()
+        {
+            let separator = if index == 0 { " " } else { " -> " };
+            write!(f, "{separator}{vertex}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Cycle {}
+
+/// Follows parents from `from` up to its ancestor `to`, giving the cycle
+/// closed by the back edge `(from, to)`.
+fn close_cycle<G: Graph>(dfs: &Dfs<'_, G>, from: VertexId, to: VertexId) -> Cycle {
+    let mut vertices = vec![from];
+    let mut vertex = from;
+    while vertex != to {
+        vertex = dfs.parent(vertex).expect("`to` is an ancestor of `from`");
+        vertices.push(vertex);
+    }
+    vertices.reverse();
+    Cycle { vertices }
+}
+
+/// Finds a cycle, if the graph has one.
+///
+/// In an undirected graph, going along an edge and straight back is not a
+/// cycle; a self-loop is.
+pub fn find_cycle<G: Graph>(graph: &G) -> Option<Cycle> {
+    let mut dfs = Dfs::all(graph);
+    while let Some(event) = dfs.next() {
+        if let Event::BackEdge(from, to) = event {
+            return Some(close_cycle(&dfs, from, to));
+        }
+    }
+    None
+}
+
+/// Orders the vertices so that every edge leads from an earlier vertex to a
+/// later one, or returns a cycle that makes that impossible.
+///
+/// Every undirected edge leads both ways, so an undirected graph only has
+/// an order if it has no edges; otherwise the cycle returned is one edge,
+/// there and back.
+///
+/// ```
+/// use data_structure::graph::traversal::topological_sort;
+/// use data_structure::graph::{AdjacencyList, Graph};
+///
+/// let mut tasks = AdjacencyList::directed();
+/// let [dress, shoes, socks] = ["dress", "shoes", "socks"].map(|task| tasks.add_vertex(task));
+/// tasks.add_edge(socks, shoes, ()).unwrap();
+/// tasks.add_edge(dress, shoes, ()).unwrap();
+/// let order = topological_sort(&tasks).unwrap();
+/// assert_eq!(order.last(), Some(&shoes));
+///
+/// tasks.add_edge(shoes, socks, ()).unwrap();
+/// assert_eq!(topological_sort(&tasks).unwrap_err().vertices, [shoes, socks]);
+/// ```
+pub fn topological_sort<G: Graph>(graph: &G) -> Result<Vec<VertexId>, Cycle> {
+    if !graph.is_directed() {
+        return match graph.edges().next() {
+            Some((from, to, _)) if from == to => Err(Cycle {
+                vertices: vec![from],
+            }),
+            Some((from, to, _)) => Err(Cycle {
+                vertices: vec![from, to],
+            }),
+            None => Ok(graph.vertices().collect()),
+        };
+    }
+    let mut order = Vec::with_capacity(graph.vertex_count());
+    let mut dfs = Dfs::all(graph);
+    while let Some(event) = dfs.next() {
+        match event {
+            Event::BackEdge(from, to) => return Err(close_cycle(&dfs, from, to)),
+            Event::Finish(vertex) => order.push(vertex),
+            _ => {}
+        }
+    }
+    order.reverse();
+    Ok(order)
+}
+
+/// Groups the vertices into connected components, each in ascending id
+/// order, the components ordered by their smallest id.
+///
+/// Edge direction is ignored, so a directed graph is split into its weakly
+/// connected components.
+///
+/// ```
+/// use data_structure::graph::traversal::connected_components;
+/// use data_structure::graph::{AdjacencyMatrix, Graph};
+///
+/// let mut graph = AdjacencyMatrix::directed();
+/// let [a, b, c, d] = [(); 4].map(|()| graph.add_vertex(()));
+/// graph.add_edge(c, a, ()).unwrap();
+/// graph.add_edge(d, b, ()).unwrap();
+/// assert_eq!(connected_components(&graph), [vec![a, c], vec![b, d]]);
+/// ```
+pub fn connected_components<G: Graph>(graph: &G) -> Vec<Vec<VertexId>> {
+    let mut components: Vec<Vec<VertexId>> = Vec::new();
+    if !graph.is_directed() {
+        let mut dfs = Dfs::all(graph);
+        while let Some(event) = dfs.next() {
+            if let Event::Discover(vertex) = event {
+                match dfs.parent(vertex) {
+                    None => components.push(vec![vertex]),
+                    Some(_) => components.last_mut().unwrap().push(vertex),
+                }
+            }
+        }
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        return components;
+    }
+    // Without incoming edges a traversal cannot walk edges backwards, so
+    // directed graphs join the ends of every edge in a disjoint-set forest.
+    let mut forest = DisjointSet::new(graph.vertex_bound());
+    for (from, to, _) in graph.edges() {
+        forest.union(from, to);
+    }
+    let mut index = vec![None; graph.vertex_bound()];
+    for vertex in graph.vertices() {
+        let root = forest.find(vertex);
+        let component = *index[root].get_or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[component].push(vertex);
+    }
+    components
+}
+
+/// Union-find over `0..len`, with union by size and path halving.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        DisjointSet {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut item: usize) -> usize {
+        while self.parent[item] != item {
+            self.parent[item] = self.parent[self.parent[item]];
+            item = self.parent[item];
+        }
+        item
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{AdjacencyList, AdjacencyMatrix};
+    use crate::rng::SplitMix64;
+
+    use Event::*;
+
+    fn graph(
+        direction: Direction,
+        vertices: usize,
+        edges: &[(VertexId, VertexId)],
+    ) -> AdjacencyList<(), ()> {
+        let mut graph = AdjacencyList::new(direction);
+        for _ in 0..vertices {
+            graph.add_vertex(());
+        }
+        for &(from, to) in edges {
+            graph.add_edge(from, to, ()).unwrap();
+        }
+        graph
+    }
+
+    /// Directed: 0 reaches 1, 2 and 3, 1 reaches 3, and 3 leads back to 0;
+    /// 4 is only reached by itself.
+    fn directed() -> AdjacencyList<(), ()> {
+        graph(
+            Direction::Directed,
+            5,
+            &[(0, 1), (0, 2), (0, 3), (1, 3), (3, 0), (2, 3), (4, 2)],
+        )
+    }
+
+    /// Undirected: the triangle 0, 1, 2 with a self-loop on 2 and 3 hanging
+    /// off 0.
+    fn undirected() -> AdjacencyList<(), ()> {
+        graph(
+            Direction::Undirected,
+            4,
+            &[(0, 1), (1, 2), (2, 0), (2, 2), (0, 3)],
+        )
+    }
+
+    #[test]
+    fn dfs_classifies_directed_edges_in_order() {
+        let graph = directed();
+        let events: Vec<_> = Dfs::all(&graph).collect();
+        assert_eq!(
+            events,
+            [
+                Discover(0),
+                TreeEdge(0, 1),
+                Discover(1),
+                TreeEdge(1, 3),
+                Discover(3),
+                BackEdge(3, 0),
+                Finish(3),
+                Finish(1),
+                TreeEdge(0, 2),
+                Discover(2),
+                CrossEdge(2, 3),
+                Finish(2),
+                ForwardEdge(0, 3),
+                Finish(0),
+                Discover(4),
+                CrossEdge(4, 2),
+                Finish(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn dfs_reports_undirected_edges_once() {
+        let graph = undirected();
+        let events: Vec<_> = Dfs::new(&graph, [0]).collect();
+        assert_eq!(
+            events,
+            [
+                Discover(0),
+                TreeEdge(0, 1),
+                Discover(1),
+                TreeEdge(1, 2),
+                Discover(2),
+                BackEdge(2, 0),
+                BackEdge(2, 2),
+                Finish(2),
+                Finish(1),
+                TreeEdge(0, 3),
+                Discover(3),
+                Finish(3),
+                Finish(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn dfs_records_parents_and_depths_as_it_goes() {
+        let graph = directed();
+        let mut dfs = Dfs::new(&graph, [0]);
+        assert_eq!(dfs.next(), Some(Discover(0)));
+        assert_eq!((dfs.parent(0), dfs.depth(0)), (None, Some(0)));
+        assert!(!dfs.is_discovered(1));
+        assert_eq!(dfs.next(), Some(TreeEdge(0, 1)));
+        assert_eq!((dfs.parent(1), dfs.depth(1)), (Some(0), Some(1)));
+        dfs.by_ref().for_each(drop);
+        assert_eq!(dfs.depth(3), Some(2));
+        assert!(dfs.is_finished(3));
+        assert!(!dfs.is_discovered(4));
+        assert_eq!(dfs.depth(4), None);
+    }
+
+    #[test]
+    fn dfs_takes_sources_in_order_and_skips_reached_ones() {
+        let graph = graph(Direction::Directed, 3, &[(0, 1), (2, 1)]);
+        let mut dfs = Dfs::new(&graph, [2, 7, 0, 2]);
+        let events: Vec<_> = dfs.by_ref().collect();
+        assert_eq!(
+            events,
+            [
+                Discover(2),
+                TreeEdge(2, 1),
+                Discover(1),
+                Finish(1),
+                Finish(2),
+                Discover(0),
+                CrossEdge(0, 1),
+                Finish(0),
+            ]
+        );
+        assert_eq!(dfs.parent(1), Some(2));
+        assert_eq!(dfs.depth(0), Some(0));
+        assert!(!dfs.is_discovered(7));
+    }
+
+    #[test]
+    fn bfs_reports_directed_edges_in_order() {
+        let graph = directed();
+        let events: Vec<_> = Bfs::new(&graph, [0]).collect();
+        assert_eq!(
+            events,
+            [
+                Discover(0),
+                TreeEdge(0, 1),
+                Discover(1),
+                TreeEdge(0, 2),
+                Discover(2),
+                TreeEdge(0, 3),
+                Discover(3),
+                Finish(0),
+                CrossEdge(1, 3),
+                Finish(1),
+                CrossEdge(2, 3),
+                Finish(2),
+                BackEdge(3, 0),
+                Finish(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn bfs_reports_undirected_edges_once() {
+        let graph = undirected();
+        let events: Vec<_> = Bfs::new(&graph, [0]).collect();
+        assert_eq!(
+            events,
+            [
+                Discover(0),
+                TreeEdge(0, 1),
+                Discover(1),
+                TreeEdge(0, 2),
+                Discover(2),
+                TreeEdge(0, 3),
+                Discover(3),
+                Finish(0),
+                CrossEdge(1, 2),
+                Finish(1),
+                BackEdge(2, 2),
+                Finish(2),
+                Finish(3),
+            ]
+        );
+    }
+
+    /// Checks every non-tree edge a traversal reports against the parents
+    /// it recorded.
+    fn check_classification<G: Graph>(
+        graph: &G,
+        events: &[Event],
+        parent: impl Fn(VertexId) -> Option<VertexId>,
+    ) {
+        let is_ancestor = |ancestor, mut vertex| loop {
+            if vertex == ancestor {
+                return true;
+            }
+            match parent(vertex) {
+                Some(next) => vertex = next,
+                None => return false,
+            }
+        };
+        let mut reported = 0;
+        for &event in events {
+            match event {
+                TreeEdge(from, to) => assert_eq!(parent(to), Some(from)),
+                BackEdge(from, to) => assert!(is_ancestor(to, from), "{event:?}"),
+                ForwardEdge(from, to) => assert!(is_ancestor(from, to), "{event:?}"),
+                CrossEdge(from, to) => {
+                    assert!(
+                        !is_ancestor(to, from) && !is_ancestor(from, to),
+                        "{event:?}"
+                    );
+                }
+                Discover(_) | Finish(_) => continue,
+            }
+            reported += 1;
+        }
+        // Every edge leaving a reached vertex is reported once, an
+        // undirected one from just one of its ends.
+        let reached: Vec<_> = events
+            .iter()
+            .filter(|event| matches!(event, Discover(_)))
+            .collect();
+        let edges = graph
+            .edges()
+            .filter(|&(from, _, _)| reached.contains(&&Discover(from)))
+            .count();
+        assert_eq!(reported, edges);
+    }
+
+    #[test]
+    fn classifies_edges_on_random_graphs() {
+        for seed in 0..40 {
+            let mut rng = SplitMix64::new(seed);
+            let direction = match seed % 2 {
+                0 => Direction::Directed,
+                _ => Direction::Undirected,
+            };
+            let vertices = 1 + rng.below(40);
+            let edges: Vec<_> = (0..rng.below(3 * vertices))
+                .map(|_| (rng.below(vertices), rng.below(vertices)))
+                .collect();
+            let graph = graph(direction, vertices, &edges);
+            let sources: Vec<_> = (0..3).map(|_| rng.below(vertices)).collect();
+
+            let mut dfs = Dfs::new(&graph, sources.iter().copied());
+            let events: Vec<_> = dfs.by_ref().collect();
+            check_classification(&graph, &events, |vertex| dfs.parent(vertex));
+            let mut bfs = Bfs::new(&graph, sources.iter().copied());
+            let events: Vec<_> = bfs.by_ref().collect();
+            assert!(!events.iter().any(|event| matches!(event, ForwardEdge(..))));
+            check_classification(&graph, &events, |vertex| bfs.parent(vertex));
+        }
+    }
+
+    #[test]
+    fn bfs_finds_back_edges_to_distant_ancestors() {
+        // A path 0 -> 1 -> ... -> 40 with edges back to earlier vertices
+        // and a branch 0 -> 41 that the path's edges cross into.
+        let mut edges: Vec<_> = (0..40).map(|vertex| (vertex, vertex + 1)).collect();
+        edges.extend([(0, 41), (40, 0), (37, 12), (25, 24), (30, 41)]);
+        let graph = graph(Direction::Directed, 42, &edges);
+        let events: Vec<_> = Bfs::new(&graph, [0]).collect();
+        for edge in [
+            BackEdge(40, 0),
+            BackEdge(37, 12),
+            BackEdge(25, 24),
+            CrossEdge(30, 41),
+        ] {
+            assert!(events.contains(&edge), "{edge:?}");
+        }
+    }
+
+    #[test]
+    fn bfs_depths_come_from_the_nearest_source() {
+        let edges: Vec<_> = (0..6).map(|vertex| (vertex, vertex + 1)).collect();
+        let graph = graph(Direction::Undirected, 7, &edges);
+        let mut bfs = Bfs::new(&graph, [0, 6, 99, 0]);
+        assert_eq!(bfs.next(), Some(Discover(0)));
+        assert_eq!(bfs.next(), Some(Discover(6)));
+        bfs.by_ref().for_each(drop);
+        let depths: Vec<_> = (0..7).map(|vertex| bfs.depth(vertex).unwrap()).collect();
+        assert_eq!(depths, [0, 1, 2, 3, 2, 1, 0]);
+        assert_eq!(bfs.parent(3), Some(2));
+        assert_eq!(bfs.parent(4), Some(5));
+        assert_eq!(bfs.parent(6), None);
+        assert!(!bfs.is_discovered(99));
+        assert!((0..7).all(|vertex| bfs.is_finished(vertex)));
+    }
+
+    #[test]
+    fn traversals_agree_across_representations() {
+        let list = directed();
+        let matrix = AdjacencyMatrix::from(list.clone());
+        assert!(Dfs::all(&list).eq(Dfs::all(&matrix)));
+        assert!(Bfs::new(&list, [4, 0]).eq(Bfs::new(&matrix, [4, 0])));
+    }
+
+    #[test]
+    fn finds_cycles() {
+        assert_eq!(find_cycle(&directed()).unwrap().vertices, [0, 1, 3]);
+        assert_eq!(find_cycle(&undirected()).unwrap().vertices, [0, 1, 2]);
+        let tree = graph(Direction::Undirected, 4, &[(0, 1), (0, 2), (2, 3)]);
+        assert_eq!(find_cycle(&tree), None);
+        let looped = graph(Direction::Undirected, 2, &[(0, 1), (1, 1)]);
+        assert_eq!(find_cycle(&looped).unwrap().to_string(), "cycle 1 -> 1");
+    }
+
+    #[test]
+    fn topological_order_respects_every_edge() {
+        let mut graph = directed();
+        graph.remove_edge(3, 0).unwrap();
+        let order = topological_sort(&graph).unwrap();
+        let mut position = vec![0; graph.vertex_bound()];
+        for (index, &vertex) in order.iter().enumerate() {
+            position[vertex] = index;
+        }
+        assert_eq!(order.len(), graph.vertex_count());
+        assert!(graph
+            .edges()
+            .all(|(from, to, _)| position[from] < position[to]));
+        assert_eq!(
+            topological_sort(&directed()).unwrap_err().vertices,
+            [0, 1, 3]
+        );
+    }
+
+    #[test]
+    fn splits_components() {
+        let mut graph = undirected();
+        let lone = graph.add_vertex(());
+        assert_eq!(connected_components(&graph), [vec![0, 1, 2, 3], vec![lone]]);
+        assert_eq!(connected_components(&directed()), [vec![0, 1, 2, 3, 4]]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn walks_a_million_vertex_path() {
+        const LEN: usize = 1_000_000;
+        let edges: Vec<_> = (1..LEN).map(|vertex| (vertex - 1, vertex)).collect();
+        let mut path = graph(Direction::Directed, LEN, &edges);
+
+        let mut dfs = Dfs::new(&path, [0]);
+        assert_eq!(dfs.by_ref().count(), 3 * LEN - 1);
+        assert_eq!(dfs.depth(LEN - 1), Some(LEN - 1));
+        let mut bfs = Bfs::new(&path, [0]);
+        assert_eq!(bfs.by_ref().count(), 3 * LEN - 1);
+        assert_eq!(bfs.parent(LEN - 1), Some(LEN - 2));
+        drop((dfs, bfs));
+
+        assert!(topological_sort(&path).unwrap().into_iter().eq(0..LEN));
+        assert_eq!(connected_components(&path).len(), 1);
+        assert_eq!(find_cycle(&path), None);
+        path.add_edge(LEN - 1, 0, ()).unwrap();
+        assert_eq!(find_cycle(&path).unwrap().vertices.len(), LEN);
+    }
+}
@@ -0,0 +1,12 @@
+//! Rust implementations of the data structures discussed in this repository.
+//!
+//! Modules follow the layout of the documentation: everything described under
+//! `Linear/` lives in [`linear`]. Graphs, which the README covers but no
+//! folder does yet, live in [`graph`].
+
+pub mod graph;
+pub mod linear;
+
+mod rng;
+#[cfg(test)]
+mod testing;
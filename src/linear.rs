@@ -0,0 +1,35 @@
+//! Linear data structures: see `Linear/*.md` for the accompanying explanations.
+
+pub mod brackets;
+pub mod circular_doubly_list;
+pub mod circular_list;
+pub mod cycle;
+pub mod doubly_linked_list;
+pub mod index_list;
+pub mod linked_list;
+pub mod persistent_list;
+pub mod playlist;
+pub mod priority_queue;
+pub mod rpn;
+pub mod skip_list;
+pub mod stack;
+pub mod undo;
+pub mod unrolled_list;
+
+pub use brackets::BracketMatcher;
+pub use circular_doubly_list::CircularDoublyList;
+pub use circular_list::CircularList;
+pub use doubly_linked_list::DoublyLinkedList;
+pub use index_list::IndexList;
+pub use linked_list::{LinkedList, ListError};
+pub use persistent_list::PersistentList;
+pub use playlist::Playlist;
+pub use priority_queue::PriorityQueue;
+pub use rpn::RpnCalculator;
+pub use skip_list::SkipList;
+pub use stack::{
+    AggQueue, AggStack, ArrayStack, BoundedStack, InlineBoundedStack, ListStack, Stack, StackError,
+    TreiberStack,
+};
+pub use undo::UndoStack;
+pub use unrolled_list::UnrolledList;
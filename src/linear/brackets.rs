@@ -0,0 +1,501 @@
+//! Bracket matching with configurable delimiters.
+//!
+//! `Linear/Stack.md` checks balance with `balanced_parentheses?`, which
+//! knows only `([{`/`)]}` and answers true or false. [`BracketMatcher`] runs
+//! the same stack algorithm over a table of delimiter pairs of any length,
+//! including keywords such as `begin`/`end`, skips string literals and
+//! comments, and reports where the first problem is.
+
+use std::error::Error;
+use std::fmt;
+
+use super::stack::{ArrayStack, Stack};
+
+/// A 1-based line and column; columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A delimiter found in the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Delimiter {
+    pub text: String,
+    pub position: Position,
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` at {}", self.text, self.position)
+    }
+}
+
+/// Why the input is not balanced.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BracketError {
+    /// `close` does not belong to the innermost open delimiter `open`.
+    Mismatched { open: Delimiter, close: Delimiter },
+    /// `close` has no open delimiter at all.
+    Unopened { close: Delimiter },
+    /// `open` is still open at the end of the input.
+    Unclosed { open: Delimiter },
+    /// A string or block comment starting at `start` never ends.
+    Unterminated { start: Delimiter },
+}
+
+impl fmt::Display for BracketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BracketError::Mismatched { open, close } => {
+                write!(f, "{close} does not close {open}")
+            }
+            BracketError::Unopened { close } => write!(f, "{close} closes nothing"),
+            BracketError::Unclosed { open } => write!(f, "{open} is never closed"),
+            BracketError::Unterminated { start } => write!(f, "{start} is never terminated"),
+        }
+    }
+}
+
+impl Error for BracketError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Open(usize),
+    Close(usize),
+    /// Opener and closer are the same text, like `|` in `|x|`.
+    Toggle(usize),
+    String {
+        end: String,
+        escape: Option<char>,
+    },
+    LineComment,
+    BlockComment {
+        end: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    text: String,
+    kind: Kind,
+}
+
+/// A bracket checker over a configurable delimiter table.
+///
+/// ```
+/// use data_structure::linear::brackets::BracketMatcher;
+///
+/// let matcher = BracketMatcher::standard()
+///     .pair("begin", "end")
+///     .string("\"", "\"", Some('\\'))
+///     .line_comment("#");
+/// assert!(matcher.check("begin f(\")\") end # (").is_ok());
+/// assert!(matcher.check("begin ( end").is_err());
+/// ```
+///
+/// Delimiters made of word characters only match whole words, so `end`
+/// does not match inside `append`. Where several delimiters start at the
+/// same place the longest wins, so `/*` beats `/`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BracketMatcher {
+    /// Sorted longest first.
+    rules: Vec<Rule>,
+    pairs: usize,
+}
+
+impl BracketMatcher {
+    /// Creates a matcher that knows no delimiters.
+    pub fn new() -> Self {
+        BracketMatcher::default()
+    }
+
+    /// Creates a matcher for `()`, `[]` and `{}`, like `balanced_parentheses?`.
+    pub fn standard() -> Self {
+        BracketMatcher::new()
+            .pair("(", ")")
+            .pair("[", "]")
+            .pair("{", "}")
+    }
+
+    /// Adds a pair of delimiters that must nest properly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either delimiter is empty.
+    pub fn pair(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        let (open, close) = (open.into(), close.into());
+        let id = self.pairs;
+        self.pairs += 1;
+        if open == close {
+            return self.rule(open, Kind::Toggle(id));
+        }
+        self.rule(open, Kind::Open(id)).rule(close, Kind::Close(id))
+    }
+
+    /// Adds a string literal running from `open` to `close`. Inside it,
+    /// `escape` makes the next character literal.
+    pub fn string(
+        self,
+        open: impl Into<String>,
+        close: impl Into<String>,
+        escape: Option<char>,
+    ) -> Self {
+        let end = close.into();
+        assert!(!end.is_empty(), "string terminator must not be empty");
+        self.rule(open.into(), Kind::String { end, escape })
+    }
+
+    /// Adds a comment running from `start` to the end of the line.
+    pub fn line_comment(self, start: impl Into<String>) -> Self {
+        self.rule(start.into(), Kind::LineComment)
+    }
+
+    /// Adds a comment running from `start` to `end`. Block comments do not
+    /// nest.
+    pub fn block_comment(self, start: impl Into<String>, end: impl Into<String>) -> Self {
+        let end = end.into();
+        assert!(!end.is_empty(), "comment terminator must not be empty");
+        self.rule(start.into(), Kind::BlockComment { end })
+    }
+
+    fn rule(mut self, text: String, kind: Kind) -> Self {
+        assert!(!text.is_empty(), "delimiters must not be empty");
+        let at = self
+            .rules
+            .partition_point(|rule| rule.text.len() >= text.len());
+        self.rules.insert(at, Rule { text, kind });
+        self
+    }
+
+    /// Checks that every delimiter in `text` is closed by its partner in
+    /// the right order, and returns the first problem otherwise.
+    pub fn check(&self, text: &str) -> Result<(), BracketError> {
+        let mut scanner = Scanner::new(text);
+        let mut open: ArrayStack<(usize, Delimiter)> = ArrayStack::new();
+        while !scanner.at_end() {
+            let Some(rule) = self.rules.iter().find(|rule| scanner.matches(&rule.text)) else {
+                scanner.advance(1);
+                continue;
+            };
+            let found = scanner.delimiter(&rule.text);
+            scanner.advance(rule.text.chars().count());
+            match &rule.kind {
+                Kind::Open(id) => open.push((*id, found)),
+                Kind::Toggle(id) => match open.peek() {
+                    Ok((top, _)) if top == id => {
+                        let _ = open.pop();
+                    }
+                    _ => open.push((*id, found)),
+                },
+                Kind::Close(id) => match open.pop() {
+                    Ok((top, _)) if top == *id => {}
+                    Ok((_, opener)) => {
+                        return Err(BracketError::Mismatched {
+                            open: opener,
+                            close: found,
+                        })
+                    }
+                    Err(_) => return Err(BracketError::Unopened { close: found }),
+                },
+                Kind::String { end, escape } => {
+                    scanner.skip_string(end, *escape, found)?;
+                }
+                Kind::LineComment => scanner.skip_line(),
+                Kind::BlockComment { end } => {
+                    scanner.skip_until(end, found)?;
+                }
+            }
+        }
+        match open.pop() {
+            Ok((_, opener)) => Err(BracketError::Unclosed { open: opener }),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Walks the input a character at a time, tracking the position.
+struct Scanner<'a> {
+    text: &'a str,
+    offset: usize,
+    position: Position,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Scanner {
+            text,
+            offset: 0,
+            position: Position { line: 1, column: 1 },
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.offset >= self.text.len()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    /// Returns `true` if `delimiter` starts here, on word boundaries if it
+    /// begins or ends with a word character.
+    fn matches(&self, delimiter: &str) -> bool {
+        if !self.rest().starts_with(delimiter) {
+            return false;
+        }
+        let starts_word = delimiter.chars().next().is_some_and(is_word);
+        let ends_word = delimiter.chars().next_back().is_some_and(is_word);
+        let before = self.text[..self.offset].chars().next_back();
+        let after = self.rest()[delimiter.len()..].chars().next();
+        !(starts_word && before.is_some_and(is_word) || ends_word && after.is_some_and(is_word))
+    }
+
+    fn delimiter(&self, text: &str) -> Delimiter {
+        Delimiter {
+            text: text.to_owned(),
+            position: self.position,
+        }
+    }
+
+    /// Moves past `count` characters, stopping at the end of the input.
+    fn advance(&mut self, count: usize) {
+        for c in self.rest().chars().take(count) {
+            self.offset += c.len_utf8();
+            if c == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while !self.at_end() && !self.rest().starts_with('\n') {
+            self.advance(1);
+        }
+    }
+
+    fn skip_until(&mut self, end: &str, start: Delimiter) -> Result<(), BracketError> {
+        while !self.at_end() {
+            if self.rest().starts_with(end) {
+                self.advance(end.chars().count());
+                return Ok(());
+            }
+            self.advance(1);
+        }
+        Err(BracketError::Unterminated { start })
+    }
+
+    fn skip_string(
+        &mut self,
+        end: &str,
+        escape: Option<char>,
+        start: Delimiter,
+    ) -> Result<(), BracketError> {
+        while !self.at_end() {
+            if escape.is_some_and(|escape| self.rest().starts_with(escape)) {
+                // An escape as the last character escapes nothing, and the
+                // string is left unterminated.
+                self.advance(1);
+                if self.at_end() {
+                    break;
+                }
+                self.advance(1);
+            } else if self.rest().starts_with(end) {
+                self.advance(end.chars().count());
+                return Ok(());
+            } else {
+                self.advance(1);
+            }
+        }
+        Err(BracketError::Unterminated { start })
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize, column: usize, text: &str) -> Delimiter {
+        Delimiter {
+            text: text.to_owned(),
+            position: Position { line, column },
+        }
+    }
+
+    fn code() -> BracketMatcher {
+        BracketMatcher::standard()
+            .pair("begin", "end")
+            .pair("|", "|")
+            .string("\"", "\"", Some('\\'))
+            .string("'", "'", None)
+            .line_comment("//")
+            .block_comment("/*", "*/")
+    }
+
+    #[test]
+    fn balanced_inputs() {
+        let matcher = code();
+        for text in [
+            "",
+            "([]{})",
+            "f(\")\") // (",
+            "/* ( */ [x]",
+            "begin |x| ( ) end",
+            "'\\' ()",
+            "\"a\\\"b\" ()",
+            "begin\n  append(x); ending_soon;\nend",
+        ] {
+            assert_eq!(matcher.check(text), Ok(()), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn mismatched_reports_both_delimiters() {
+        assert_eq!(
+            code().check("{\n  (x]\n}"),
+            Err(BracketError::Mismatched {
+                open: at(2, 3, "("),
+                close: at(2, 5, "]"),
+            })
+        );
+        assert_eq!(
+            code().check("begin ( end )"),
+            Err(BracketError::Mismatched {
+                open: at(1, 7, "("),
+                close: at(1, 9, "end"),
+            })
+        );
+    }
+
+    #[test]
+    fn unopened_reports_the_closer() {
+        assert_eq!(
+            code().check("()\n\n  x)"),
+            Err(BracketError::Unopened {
+                close: at(3, 4, ")")
+            })
+        );
+        let error = code().check("end").unwrap_err();
+        assert_eq!(error.to_string(), "`end` at 1:1 closes nothing");
+    }
+
+    #[test]
+    fn unclosed_reports_the_innermost_opener() {
+        assert_eq!(
+            code().check("( [\n] {"),
+            Err(BracketError::Unclosed {
+                open: at(2, 3, "{")
+            })
+        );
+        assert_eq!(
+            code().check("|x"),
+            Err(BracketError::Unclosed {
+                open: at(1, 1, "|")
+            })
+        );
+    }
+
+    #[test]
+    fn unterminated_strings_and_comments() {
+        let unterminated = |line, column, text| {
+            Err(BracketError::Unterminated {
+                start: at(line, column, text),
+            })
+        };
+        assert_eq!(code().check("x(\n  \"abc)"), unterminated(2, 3, "\""));
+        assert_eq!(code().check("'it\\'s'"), unterminated(1, 7, "'"));
+        assert_eq!(code().check("() /* (\n"), unterminated(1, 4, "/*"));
+        assert_eq!(code().check("/*/"), unterminated(1, 1, "/*"));
+        let error = code().check("\"a").unwrap_err();
+        assert_eq!(error.to_string(), "`\"` at 1:1 is never terminated");
+    }
+
+    #[test]
+    fn escape_as_the_last_character() {
+        assert_eq!(
+            code().check("(\"ab\\"),
+            Err(BracketError::Unterminated {
+                start: at(1, 2, "\"")
+            })
+        );
+        assert_eq!(
+            code().check("\"\\"),
+            Err(BracketError::Unterminated {
+                start: at(1, 1, "\"")
+            })
+        );
+        // The escaped character may be multi-byte.
+        assert_eq!(code().check("(\"\\é\")"), Ok(()));
+    }
+
+    #[test]
+    fn keywords_match_whole_words_only() {
+        let matcher = BracketMatcher::new().pair("begin", "end");
+        assert_eq!(matcher.check("beginning append endless end_ _end"), Ok(()));
+        assert_eq!(matcher.check("(begin)x;end."), Ok(()));
+        assert_eq!(
+            matcher.check("begin\nbackend end2 weekend"),
+            Err(BracketError::Unclosed {
+                open: at(1, 1, "begin")
+            })
+        );
+        assert_eq!(
+            matcher.check("débutend end"),
+            Err(BracketError::Unopened {
+                close: at(1, 10, "end")
+            })
+        );
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        assert_eq!(
+            code().check("é(ü]"),
+            Err(BracketError::Mismatched {
+                open: at(1, 2, "("),
+                close: at(1, 4, "]"),
+            })
+        );
+        assert_eq!(
+            code().check("// ünïcödé (\n日本語 ) x"),
+            Err(BracketError::Unopened {
+                close: at(2, 5, ")")
+            })
+        );
+        assert_eq!(
+            code().check("\"🦀\" 🦀 ["),
+            Err(BracketError::Unclosed {
+                open: at(1, 7, "[")
+            })
+        );
+    }
+
+    #[test]
+    fn longest_delimiter_wins() {
+        let matcher = BracketMatcher::new()
+            .pair("<", ">")
+            .pair("<<", ">>")
+            .block_comment("<!--", "-->");
+        assert_eq!(matcher.check("<< <x> >> <!-- < -->"), Ok(()));
+        assert_eq!(
+            matcher.check("<< >"),
+            Err(BracketError::Mismatched {
+                open: at(1, 1, "<<"),
+                close: at(1, 4, ">"),
+            })
+        );
+    }
+}
@@ -0,0 +1,838 @@
+//! Circular doubly linked list.
+//!
+//! Every node links both ways and the ring closes on itself, so the list only
+//! stores its head (the tail is `head.prev`). Both ends are O(1), rotation
+//! walks whichever direction is shorter, and the [`CursorMut`] moves forwards
+//! and backwards indefinitely.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use super::ListError;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    data: T,
+    prev: NonNull<Node<T>>,
+    next: NonNull<Node<T>>,
+}
+
+impl<T> Node<T> {
+    /// Allocates a node that links to itself in both directions.
+    fn alloc(data: T) -> NonNull<Node<T>> {
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            data,
+            prev: NonNull::dangling(),
+            next: NonNull::dangling(),
+        })));
+        // SAFETY: `node` was just allocated and nothing else points to it.
+        unsafe {
+            (*node.as_ptr()).prev = node;
+            (*node.as_ptr()).next = node;
+        }
+        node
+    }
+}
+
+/// A circular doubly linked list.
+///
+/// | Operation                 | Cost                  |
+/// |---------------------------|-----------------------|
+/// | `insert_at_beginning`     | O(1)                  |
+/// | `insert_at_end`           | O(1)                  |
+/// | `delete_from_beginning`   | O(1)                  |
+/// | `delete_from_end`         | O(1)                  |
+/// | `rotate_left(k)`          | O(min(k, n - k) mod n)|
+/// | `rotate_right(k)`         | O(min(k, n - k) mod n)|
+pub struct CircularDoublyList<T> {
+    head: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list exclusively owns its nodes, exactly like `Box<Node<T>>`.
+unsafe impl<T: Send> Send for CircularDoublyList<T> {}
+unsafe impl<T: Sync> Sync for CircularDoublyList<T> {}
+
+impl<T> CircularDoublyList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        CircularDoublyList {
+            head: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        // SAFETY: nodes reachable from the list are live while `&self` is.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        // SAFETY: as in `head`.
+        self.tail_node()
+            .map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Adds an element to the front of the list in O(1).
+    pub fn insert_at_beginning(&mut self, data: T) {
+        self.insert_at_end(data);
+        self.head = self.tail_node();
+    }
+
+    /// Adds an element to the back of the list in O(1).
+    pub fn insert_at_end(&mut self, data: T) {
+        let node = Node::alloc(data);
+        match self.head {
+            // SAFETY: `head` belongs to this list and `node` is fresh.
+            Some(head) => unsafe { link_before(head, node) },
+            None => self.head = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// Removes and returns the first element in O(1).
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        let head = self.head.ok_or(ListError::Empty)?;
+        // SAFETY: `head` belongs to this list.
+        Ok(unsafe { self.unlink(head) })
+    }
+
+    /// Removes and returns the last element in O(1).
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        let tail = self.tail_node().ok_or(ListError::Empty)?;
+        // SAFETY: `tail` belongs to this list.
+        Ok(unsafe { self.unlink(tail) })
+    }
+
+    /// Rotates the list so that the element at index `k` becomes the head.
+    pub fn rotate_left(&mut self, k: usize) {
+        if self.len > 0 {
+            self.rotate_by(k % self.len);
+        }
+    }
+
+    /// Rotates the list so that the last `k` elements move to the front.
+    pub fn rotate_right(&mut self, k: usize) {
+        if self.len > 0 {
+            self.rotate_by(self.len - k % self.len);
+        }
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        while self.delete_from_beginning().is_ok() {}
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns a double-ended iterator over one lap of the ring.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail_node(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a double-ended mutable iterator over one lap of the ring.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail_node(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator that keeps going around the ring forever.
+    ///
+    /// It yields nothing for an empty list.
+    pub fn cycle(&self) -> Cycle<'_, T> {
+        Cycle {
+            next: self.head,
+            forward: true,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator that goes around the ring backwards forever,
+    /// starting at the tail.
+    pub fn cycle_rev(&self) -> Cycle<'_, T> {
+        Cycle {
+            next: self.tail_node(),
+            forward: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an endless editing cursor positioned at the head.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            list: self,
+        }
+    }
+
+    /// Returns a draining iterator that walks the ring and removes every
+    /// `k`-th element, starting the count at the head.
+    ///
+    /// Behaves like
+    /// [`CircularList::remove_every_kth`](super::CircularList::remove_every_kth),
+    /// leaving unyielded elements in the list with the head at the next
+    /// element that would have been counted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn remove_every_kth(&mut self, k: usize) -> RemoveEveryKth<'_, T> {
+        assert!(k > 0, "remove_every_kth needs k >= 1");
+        RemoveEveryKth { k, list: self }
+    }
+
+    fn tail_node(&self) -> Link<T> {
+        // SAFETY: `head` belongs to this list.
+        self.head.map(|head| unsafe { (*head.as_ptr()).prev })
+    }
+
+    /// Moves the head `k` steps forward (`k < len`), walking backwards when
+    /// that is shorter.
+    fn rotate_by(&mut self, k: usize) {
+        let Some(mut head) = self.head else {
+            return;
+        };
+        if k <= self.len - k {
+            for _ in 0..k {
+                // SAFETY: every node in the ring belongs to this list.
+                head = unsafe { (*head.as_ptr()).next };
+            }
+        } else {
+            for _ in k..self.len {
+                // SAFETY: as above.
+                head = unsafe { (*head.as_ptr()).prev };
+            }
+        }
+        self.head = Some(head);
+    }
+
+    /// Unlinks and frees `node`, moving `head` forward if it was the head.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let boxed = Box::from_raw(node.as_ptr());
+        if boxed.next == node {
+            self.head = None;
+        } else {
+            (*boxed.prev.as_ptr()).next = boxed.next;
+            (*boxed.next.as_ptr()).prev = boxed.prev;
+            if self.head == Some(node) {
+                self.head = Some(boxed.next);
+            }
+        }
+        self.len -= 1;
+        boxed.data
+    }
+}
+
+/// Links the unlinked `node` right before `next`.
+///
+/// # Safety
+///
+/// `next` must be live and `node` must link only to itself.
+unsafe fn link_before<T>(next: NonNull<Node<T>>, node: NonNull<Node<T>>) {
+    let prev = (*next.as_ptr()).prev;
+    (*node.as_ptr()).prev = prev;
+    (*node.as_ptr()).next = next;
+    (*prev.as_ptr()).next = node;
+    (*next.as_ptr()).prev = node;
+}
+
+impl<T> Default for CircularDoublyList<T> {
+    fn default() -> Self {
+        CircularDoublyList::new()
+    }
+}
+
+impl<T> Drop for CircularDoublyList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for CircularDoublyList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CircularDoublyList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for CircularDoublyList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for CircularDoublyList<T> {}
+
+impl<T: Hash> Hash for CircularDoublyList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Extend<T> for CircularDoublyList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for CircularDoublyList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = CircularDoublyList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for CircularDoublyList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CircularDoublyList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut CircularDoublyList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`CircularDoublyList::iter`].
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list is borrowed for `'a`.
+            let node = unsafe { &*node.as_ptr() };
+            self.head = Some(node.next);
+            self.len -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: as in `next`.
+            let node = unsafe { &*node.as_ptr() };
+            self.tail = Some(node.prev);
+            self.len -= 1;
+            &node.data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable iterator returned by [`CircularDoublyList::iter_mut`].
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list is mutably borrowed for `'a` and `len` keeps
+            // any node from being yielded twice.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.head = Some(node.next);
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: as in `next`.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.tail = Some(node.prev);
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator returned by [`CircularDoublyList::into_iter`].
+pub struct IntoIter<T> {
+    list: CircularDoublyList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.delete_from_end().ok()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Endless iterator returned by [`CircularDoublyList::cycle`] and
+/// [`CircularDoublyList::cycle_rev`].
+pub struct Cycle<'a, T> {
+    next: Link<T>,
+    forward: bool,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Cycle<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            // SAFETY: the list is borrowed for `'a`.
+            let node = unsafe { &*node.as_ptr() };
+            self.next = Some(if self.forward { node.next } else { node.prev });
+            &node.data
+        })
+    }
+}
+
+impl<T> Clone for Cycle<'_, T> {
+    fn clone(&self) -> Self {
+        Cycle { ..*self }
+    }
+}
+
+/// Endless editing cursor over a [`CircularDoublyList`].
+///
+/// Moving past either end wraps around; the cursor only has no current
+/// element when the list is empty.
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    list: &'a mut CircularDoublyList<T>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// The element under the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// The element after the current one, wrapping around the ring.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*(*node.as_ptr()).next.as_ptr()).data })
+    }
+
+    /// The element before the current one, wrapping around the ring.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*(*node.as_ptr()).prev.as_ptr()).data })
+    }
+
+    /// Advances to the next element, wrapping from the tail to the head.
+    pub fn move_next(&mut self) {
+        // SAFETY: `node` belongs to the borrowed list.
+        self.current = self.current.map(|node| unsafe { (*node.as_ptr()).next });
+    }
+
+    /// Steps back to the previous element, wrapping from the head to the tail.
+    pub fn move_prev(&mut self) {
+        // SAFETY: `node` belongs to the borrowed list.
+        self.current = self.current.map(|node| unsafe { (*node.as_ptr()).prev });
+    }
+
+    /// Returns `true` if the cursor is on the head of the list.
+    pub fn is_at_head(&self) -> bool {
+        self.current.is_some() && self.current == self.list.head
+    }
+
+    /// Inserts `data` after the current element without moving the cursor.
+    ///
+    /// Inserting after the tail appends, so the new element becomes the tail.
+    /// On an empty list the new element becomes current.
+    pub fn insert_after(&mut self, data: T) {
+        match self.current {
+            Some(current) => {
+                let node = Node::alloc(data);
+                // SAFETY: `current` belongs to the list and `node` is fresh.
+                unsafe { link_before((*current.as_ptr()).next, node) };
+                self.list.len += 1;
+            }
+            None => {
+                self.list.insert_at_end(data);
+                self.current = self.list.head;
+            }
+        }
+    }
+
+    /// Inserts `data` before the current element without moving the cursor.
+    ///
+    /// Inserting before the head prepends, so the new element becomes the
+    /// head. On an empty list the new element becomes current.
+    pub fn insert_before(&mut self, data: T) {
+        match self.current {
+            Some(current) => {
+                let node = Node::alloc(data);
+                // SAFETY: `current` belongs to the list and `node` is fresh.
+                unsafe { link_before(current, node) };
+                if self.list.head == Some(current) {
+                    self.list.head = Some(node);
+                }
+                self.list.len += 1;
+            }
+            None => {
+                self.list.insert_at_end(data);
+                self.current = self.list.head;
+            }
+        }
+    }
+
+    /// Removes the current element and moves the cursor to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: `node` belongs to the borrowed list.
+        self.current = unsafe { Some((*node.as_ptr()).next) };
+        // SAFETY: as above.
+        let data = unsafe { self.list.unlink(node) };
+        if self.list.is_empty() {
+            self.current = None;
+        }
+        Some(data)
+    }
+}
+
+/// Draining iterator returned by [`CircularDoublyList::remove_every_kth`].
+pub struct RemoveEveryKth<'a, T> {
+    k: usize,
+    list: &'a mut CircularDoublyList<T>,
+}
+
+impl<T> Iterator for RemoveEveryKth<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let len = self.list.len;
+        if len == 0 {
+            return None;
+        }
+        self.list.rotate_by((self.k - 1) % len);
+        // The count restarts from the element following the removed one,
+        // which `delete_from_beginning` leaves as the new head.
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> ExactSizeIterator for RemoveEveryKth<'_, T> {}
+impl<T> FusedIterator for RemoveEveryKth<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Checks that the ring closes after `len` steps and that every `prev`
+    /// link mirrors a `next` link.
+    fn check<T>(list: &CircularDoublyList<T>) {
+        let Some(head) = list.head else {
+            assert_eq!(list.len, 0);
+            return;
+        };
+        let mut node = head;
+        for step in 0..list.len {
+            // SAFETY: the list is borrowed and owns its nodes.
+            let next = unsafe { (*node.as_ptr()).next };
+            assert_eq!(
+                unsafe { (*next.as_ptr()).prev },
+                node,
+                "prev link of node {step}"
+            );
+            assert_eq!(next == head, step + 1 == list.len, "ring closes early");
+            node = next;
+        }
+        assert!(!list.is_empty());
+    }
+
+    fn items<T: Clone>(list: &CircularDoublyList<T>) -> Vec<T> {
+        check(list);
+        let forward: Vec<T> = list.iter().cloned().collect();
+        let mut backward: Vec<T> = list.iter().rev().cloned().collect();
+        backward.reverse();
+        assert_eq!(forward.len(), backward.len());
+        forward
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: CircularDoublyList<i32> = CircularDoublyList::new();
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.cycle().next(), None);
+        assert_eq!(list.cycle_rev().next(), None);
+        list.rotate_left(3);
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.remove_current(), None);
+    }
+
+    #[test]
+    fn single_element_links_to_itself() {
+        let mut list = CircularDoublyList::new();
+        list.insert_at_beginning(1);
+        assert_eq!(items(&list), [1]);
+        assert_eq!(list.head(), list.tail());
+        assert_eq!(list.cycle_rev().take(2).collect::<Vec<_>>(), [&1, &1]);
+        assert_eq!(list.delete_from_end(), Ok(1));
+        check(&list);
+    }
+
+    #[test]
+    fn insert_and_delete_at_both_ends() {
+        let mut list = CircularDoublyList::new();
+        list.insert_at_end(2);
+        list.insert_at_beginning(1);
+        list.insert_at_end(3);
+        assert_eq!(items(&list), [1, 2, 3]);
+        assert_eq!((list.head(), list.tail()), (Some(&1), Some(&3)));
+        assert_eq!(list.delete_from_end(), Ok(3));
+        assert_eq!(items(&list), [1, 2]);
+        assert_eq!(list.delete_from_beginning(), Ok(1));
+        assert_eq!(list.delete_from_beginning(), Ok(2));
+        check(&list);
+    }
+
+    #[test]
+    fn rotation_takes_the_short_way() {
+        let mut list: CircularDoublyList<_> = (0..5).collect();
+        list.rotate_left(4);
+        assert_eq!(items(&list), [4, 0, 1, 2, 3]);
+        list.rotate_right(11);
+        assert_eq!(items(&list), [3, 4, 0, 1, 2]);
+        list.rotate_left(5);
+        assert_eq!(items(&list), [3, 4, 0, 1, 2]);
+    }
+
+    #[test]
+    fn cycles_wrap_in_both_directions() {
+        let list: CircularDoublyList<_> = (0..3).collect();
+        let forward: Vec<_> = list.cycle().take(5).copied().collect();
+        let backward: Vec<_> = list.cycle_rev().take(5).copied().collect();
+        assert_eq!(forward, [0, 1, 2, 0, 1]);
+        assert_eq!(backward, [2, 1, 0, 2, 1]);
+    }
+
+    #[test]
+    fn iterators_meet_in_the_middle() {
+        let mut list: CircularDoublyList<_> = (0..5).collect();
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() += 10;
+        *iter.next_back().unwrap() += 10;
+        assert_eq!(iter.len(), 3);
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(14));
+        assert_eq!(into_iter.next(), Some(10));
+        assert_eq!(into_iter.collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn cursor_insert_on_single_element_keeps_position() {
+        let mut list = CircularDoublyList::new();
+        list.insert_at_end(1);
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(2);
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), Some(&mut 0));
+        assert_eq!(items(&list), [0, 1, 2]);
+    }
+
+    #[test]
+    fn cursor_moves_and_edits_wrap_around() {
+        let mut list: CircularDoublyList<_> = (1..=3).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.insert_after(4);
+        cursor.move_next();
+        cursor.move_next();
+        assert!(cursor.is_at_head());
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert!(!cursor.is_at_head());
+        assert_eq!(items(&list), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn cursor_remove_current() {
+        let counter = DropCounter::new();
+        let mut list: CircularDoublyList<_> = (0..3).map(|i| counter.item(i)).collect();
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(0));
+        assert!(cursor.is_at_head());
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(2));
+        assert_eq!(cursor.current().map(|item| item.value), Some(1));
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(1));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(counter.dropped(), 3);
+        check(&list);
+    }
+
+    #[test]
+    fn remove_every_kth_is_josephus_order() {
+        let mut list: CircularDoublyList<_> = (1..=7).collect();
+        let order: Vec<_> = list.remove_every_kth(3).collect();
+        assert_eq!(order, [3, 6, 2, 7, 5, 1, 4]);
+        let mut list: CircularDoublyList<_> = (1..=7).collect();
+        assert_eq!(list.remove_every_kth(3).take(2).count(), 2);
+        assert_eq!(items(&list), [7, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn drop_frees_every_node() {
+        let counter = DropCounter::new();
+        let list: CircularDoublyList<_> = (0..5).map(|i| counter.item(i)).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back().map(|item| item.value), Some(4));
+        drop(iter);
+        assert_eq!(counter.dropped(), 5);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_long_lists() {
+        let list: CircularDoublyList<u32> = (0..1_000_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn clone_eq_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let list: CircularDoublyList<_> = (0..3).collect();
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(hash_of(&copy), hash_of(&list));
+        copy.rotate_right(1);
+        assert_ne!(copy, list);
+        assert_eq!(items(&copy), [2, 0, 1]);
+    }
+}
@@ -0,0 +1,796 @@
+//! Circular singly linked list.
+//!
+//! The last node links back to the first, as described under "Circular Linked
+//! List" in `Linear/LinkedList.md`. The list only stores its tail: the head is
+//! always `tail.next`, which makes insertion at both ends and rotation cheap.
+//! Besides the finite iterators it offers an endless [`Cycle`] iterator, a
+//! [`CursorMut`] that wraps around instead of stopping, and
+//! [`RemoveEveryKth`] for Josephus-style elimination.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use super::ListError;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    data: T,
+    next: NonNull<Node<T>>,
+}
+
+impl<T> Node<T> {
+    /// Allocates a node that links to itself.
+    fn alloc(data: T) -> NonNull<Node<T>> {
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            data,
+            next: NonNull::dangling(),
+        })));
+        // SAFETY: `node` was just allocated and nothing else points to it.
+        unsafe { (*node.as_ptr()).next = node };
+        node
+    }
+}
+
+/// A circular singly linked list.
+///
+/// | Operation                 | Cost          |
+/// |---------------------------|---------------|
+/// | `insert_at_beginning`     | O(1)          |
+/// | `insert_at_end`           | O(1)          |
+/// | `delete_from_beginning`   | O(1)          |
+/// | `delete_from_end`         | O(n)          |
+/// | `rotate_left(k)`          | O(k mod n)    |
+/// | `rotate_right(k)`         | O(n - k mod n)|
+pub struct CircularList<T> {
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list exclusively owns its nodes, exactly like `Box<Node<T>>`.
+unsafe impl<T: Send> Send for CircularList<T> {}
+unsafe impl<T: Sync> Sync for CircularList<T> {}
+
+impl<T> CircularList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        CircularList {
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tail.is_none()
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        // SAFETY: nodes reachable from the list are live while `&self` is.
+        self.head_node()
+            .map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        // SAFETY: as in `head`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Adds an element to the front of the list in O(1).
+    pub fn insert_at_beginning(&mut self, data: T) {
+        let node = Node::alloc(data);
+        match self.tail {
+            // SAFETY: `tail` belongs to this list and `node` is fresh.
+            Some(tail) => unsafe { link_after(tail, node) },
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// Adds an element to the back of the list in O(1).
+    pub fn insert_at_end(&mut self, data: T) {
+        self.insert_at_beginning(data);
+        self.tail = self.head_node();
+    }
+
+    /// Removes and returns the first element in O(1).
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        let tail = self.tail.ok_or(ListError::Empty)?;
+        // SAFETY: `tail` belongs to this list.
+        Ok(unsafe { self.unlink_after(tail) })
+    }
+
+    /// Removes and returns the last element.
+    ///
+    /// Only forward links exist, so finding the new tail is O(n); see
+    /// [`CircularDoublyList`](super::CircularDoublyList) for O(1) removal at
+    /// both ends.
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        let tail = self.tail.ok_or(ListError::Empty)?;
+        let mut prev = tail;
+        for _ in 1..self.len {
+            // SAFETY: every node in the ring belongs to this list.
+            prev = unsafe { (*prev.as_ptr()).next };
+        }
+        // SAFETY: `prev` is the node before `tail`.
+        Ok(unsafe { self.unlink_after(prev) })
+    }
+
+    /// Rotates the list so that the element at index `k` becomes the head.
+    ///
+    /// Only the tail pointer moves; no element is relinked.
+    pub fn rotate_left(&mut self, k: usize) {
+        let Some(mut tail) = self.tail else {
+            return;
+        };
+        for _ in 0..k % self.len {
+            // SAFETY: every node in the ring belongs to this list.
+            tail = unsafe { (*tail.as_ptr()).next };
+        }
+        self.tail = Some(tail);
+    }
+
+    /// Rotates the list so that the last `k` elements move to the front.
+    pub fn rotate_right(&mut self, k: usize) {
+        if self.len > 0 {
+            self.rotate_left(self.len - k % self.len);
+        }
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        while self.delete_from_beginning().is_ok() {}
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns an iterator over one lap of the ring, head first.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head_node(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a mutable iterator over one lap of the ring, head first.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head_node(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator that keeps going around the ring forever.
+    ///
+    /// It yields nothing for an empty list.
+    pub fn cycle(&self) -> Cycle<'_, T> {
+        Cycle {
+            next: self.head_node(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an endless editing cursor positioned at the head.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            prev: self.tail,
+            list: self,
+        }
+    }
+
+    /// Returns a draining iterator that walks the ring and removes every
+    /// `k`-th element, starting the count at the head.
+    ///
+    /// With `n` elements this yields the Josephus elimination order in
+    /// O(n·k). Elements not yet yielded when the iterator is dropped stay in
+    /// the list, with the head at the next element that would have been
+    /// counted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn remove_every_kth(&mut self, k: usize) -> RemoveEveryKth<'_, T> {
+        assert!(k > 0, "remove_every_kth needs k >= 1");
+        RemoveEveryKth { k, list: self }
+    }
+
+    fn head_node(&self) -> Link<T> {
+        // SAFETY: `tail` belongs to this list.
+        self.tail.map(|tail| unsafe { (*tail.as_ptr()).next })
+    }
+
+    /// Unlinks and frees the node after `prev`, keeping `tail` valid.
+    ///
+    /// # Safety
+    ///
+    /// `prev` must belong to this non-empty list.
+    unsafe fn unlink_after(&mut self, prev: NonNull<Node<T>>) -> T {
+        let node = (*prev.as_ptr()).next;
+        if node == prev {
+            self.tail = None;
+        } else {
+            (*prev.as_ptr()).next = (*node.as_ptr()).next;
+            if self.tail == Some(node) {
+                self.tail = Some(prev);
+            }
+        }
+        self.len -= 1;
+        Box::from_raw(node.as_ptr()).data
+    }
+}
+
+/// Links the unlinked `node` right after `prev`.
+///
+/// # Safety
+///
+/// `prev` must be live and `node` must link only to itself.
+unsafe fn link_after<T>(prev: NonNull<Node<T>>, node: NonNull<Node<T>>) {
+    (*node.as_ptr()).next = (*prev.as_ptr()).next;
+    (*prev.as_ptr()).next = node;
+}
+
+impl<T> Default for CircularList<T> {
+    fn default() -> Self {
+        CircularList::new()
+    }
+}
+
+impl<T> Drop for CircularList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for CircularList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CircularList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for CircularList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for CircularList<T> {}
+
+impl<T: Hash> Hash for CircularList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Extend<T> for CircularList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for CircularList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = CircularList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for CircularList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CircularList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut CircularList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`CircularList::iter`].
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.next.map(|node| {
+            // SAFETY: the list is borrowed for `'a`.
+            let node = unsafe { &*node.as_ptr() };
+            self.next = Some(node.next);
+            self.len -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable iterator returned by [`CircularList::iter_mut`].
+pub struct IterMut<'a, T> {
+    next: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.next.map(|node| {
+            // SAFETY: the list is mutably borrowed for `'a` and `len` stops
+            // the iterator before any node is yielded twice.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.next = Some(node.next);
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator returned by [`CircularList::into_iter`].
+pub struct IntoIter<T> {
+    list: CircularList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Endless iterator returned by [`CircularList::cycle`].
+pub struct Cycle<'a, T> {
+    next: Link<T>,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Cycle<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            // SAFETY: the list is borrowed for `'a`.
+            let node = unsafe { &*node.as_ptr() };
+            self.next = Some(node.next);
+            &node.data
+        })
+    }
+}
+
+impl<T> Clone for Cycle<'_, T> {
+    fn clone(&self) -> Self {
+        Cycle { ..*self }
+    }
+}
+
+/// Endless editing cursor over a [`CircularList`].
+///
+/// The cursor remembers the node before the current one, so insertion on
+/// either side and removal are all O(1). Moving past the tail wraps to the
+/// head; the cursor only has no current element when the list is empty.
+pub struct CursorMut<'a, T> {
+    prev: Link<T>,
+    list: &'a mut CircularList<T>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// The element under the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current_node()
+            .map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// The element after the current one, wrapping around the ring.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current_node()
+            .map(|node| unsafe { &mut (*(*node.as_ptr()).next.as_ptr()).data })
+    }
+
+    /// Advances to the next element, wrapping from the tail to the head.
+    pub fn move_next(&mut self) {
+        self.prev = self.current_node();
+    }
+
+    /// Returns `true` if the cursor is on the head of the list.
+    pub fn is_at_head(&self) -> bool {
+        self.prev.is_some() && self.prev == self.list.tail
+    }
+
+    /// Inserts `data` after the current element without moving the cursor.
+    ///
+    /// Inserting after the tail appends, so the new element becomes the tail.
+    /// On an empty list the new element becomes current.
+    pub fn insert_after(&mut self, data: T) {
+        match self.current_node() {
+            Some(current) => {
+                let node = Node::alloc(data);
+                // SAFETY: `current` belongs to the list and `node` is fresh.
+                unsafe { link_after(current, node) };
+                if self.list.tail == Some(current) {
+                    self.list.tail = Some(node);
+                }
+                // With a single element `prev` is `current` itself, whose
+                // successor is now `node`; step `prev` onto it to stay put.
+                if self.prev == Some(current) {
+                    self.prev = Some(node);
+                }
+                self.list.len += 1;
+            }
+            None => {
+                self.list.insert_at_end(data);
+                self.prev = self.list.tail;
+            }
+        }
+    }
+
+    /// Inserts `data` before the current element without moving the cursor.
+    ///
+    /// Inserting before the head prepends, so the new element becomes the
+    /// head. On an empty list the new element becomes current.
+    pub fn insert_before(&mut self, data: T) {
+        match self.prev {
+            Some(prev) => {
+                let node = Node::alloc(data);
+                // SAFETY: `prev` belongs to the list and `node` is fresh.
+                unsafe { link_after(prev, node) };
+                self.prev = Some(node);
+                self.list.len += 1;
+            }
+            None => {
+                self.list.insert_at_end(data);
+                self.prev = self.list.tail;
+            }
+        }
+    }
+
+    /// Removes the current element and moves the cursor to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let prev = self.prev?;
+        // SAFETY: `prev` belongs to the non-empty list.
+        let data = unsafe { self.list.unlink_after(prev) };
+        if self.list.is_empty() {
+            self.prev = None;
+        }
+        Some(data)
+    }
+
+    fn current_node(&self) -> Link<T> {
+        // SAFETY: `prev` belongs to the list.
+        self.prev.map(|prev| unsafe { (*prev.as_ptr()).next })
+    }
+}
+
+/// Draining iterator returned by [`CircularList::remove_every_kth`].
+pub struct RemoveEveryKth<'a, T> {
+    k: usize,
+    list: &'a mut CircularList<T>,
+}
+
+impl<T> Iterator for RemoveEveryKth<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut prev = self.list.tail?;
+        for _ in 1..self.k {
+            // SAFETY: every node in the ring belongs to this list.
+            prev = unsafe { (*prev.as_ptr()).next };
+        }
+        // SAFETY: `prev` belongs to the non-empty list. Afterwards the count
+        // restarts from the element following the removed one.
+        let data = unsafe { self.list.unlink_after(prev) };
+        if !self.list.is_empty() {
+            self.list.tail = Some(prev);
+        }
+        Some(data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> ExactSizeIterator for RemoveEveryKth<'_, T> {}
+impl<T> FusedIterator for RemoveEveryKth<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Checks that the ring closes back on `tail` after exactly `len` steps.
+    fn check<T>(list: &CircularList<T>) {
+        let Some(tail) = list.tail else {
+            assert_eq!(list.len, 0);
+            return;
+        };
+        let mut node = tail;
+        for step in 0..list.len {
+            // SAFETY: the list is borrowed and owns its nodes.
+            node = unsafe { (*node.as_ptr()).next };
+            assert_eq!(node == tail, step + 1 == list.len, "ring closes early");
+        }
+        assert!(!list.is_empty());
+    }
+
+    fn items<T: Clone>(list: &CircularList<T>) -> Vec<T> {
+        check(list);
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: CircularList<i32> = CircularList::new();
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.cycle().next(), None);
+        list.rotate_left(3);
+        list.rotate_right(3);
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.remove_current(), None);
+        assert!(!cursor.is_at_head());
+    }
+
+    #[test]
+    fn single_element_links_to_itself() {
+        let mut list = CircularList::new();
+        list.insert_at_end(1);
+        assert_eq!(items(&list), [1]);
+        assert_eq!(list.head(), list.tail());
+        assert_eq!(list.cycle().take(3).collect::<Vec<_>>(), [&1, &1, &1]);
+        list.rotate_left(5);
+        assert_eq!(items(&list), [1]);
+        assert_eq!(list.delete_from_end(), Ok(1));
+        check(&list);
+    }
+
+    #[test]
+    fn insert_and_delete_at_both_ends() {
+        let mut list = CircularList::new();
+        list.insert_at_end(2);
+        list.insert_at_beginning(1);
+        list.insert_at_end(3);
+        assert_eq!(items(&list), [1, 2, 3]);
+        assert_eq!((list.head(), list.tail()), (Some(&1), Some(&3)));
+        assert_eq!(list.delete_from_end(), Ok(3));
+        assert_eq!(items(&list), [1, 2]);
+        assert_eq!(list.delete_from_beginning(), Ok(1));
+        assert_eq!(items(&list), [2]);
+        assert_eq!(list.delete_from_beginning(), Ok(2));
+        assert!(list.is_empty());
+        check(&list);
+    }
+
+    #[test]
+    fn rotation_wraps() {
+        let mut list: CircularList<_> = (0..5).collect();
+        list.rotate_left(2);
+        assert_eq!(items(&list), [2, 3, 4, 0, 1]);
+        list.rotate_right(7);
+        assert_eq!(items(&list), [0, 1, 2, 3, 4]);
+        list.rotate_left(5);
+        assert_eq!(items(&list), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let list: CircularList<_> = (0..3).collect();
+        let lap: Vec<_> = list.cycle().take(7).copied().collect();
+        assert_eq!(lap, [0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn iter_mut_and_into_iter_visit_one_lap() {
+        let mut list: CircularList<_> = (0..4).collect();
+        for item in &mut list {
+            *item *= 10;
+        }
+        assert_eq!(list.iter().len(), 4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [0, 10, 20, 30]);
+    }
+
+    #[test]
+    fn cursor_insert_after_single_element_keeps_position() {
+        let mut list = CircularList::new();
+        list.insert_at_end(1);
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(2);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert!(cursor.is_at_head());
+        assert_eq!(items(&list), [1, 2]);
+        assert_eq!(list.tail(), Some(&2));
+    }
+
+    #[test]
+    fn cursor_insert_before_single_element_keeps_position() {
+        let mut list = CircularList::new();
+        list.insert_at_end(1);
+        let mut cursor = list.cursor_mut();
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert!(!cursor.is_at_head());
+        assert_eq!(items(&list), [0, 1]);
+        assert_eq!(list.tail(), Some(&1));
+    }
+
+    #[test]
+    fn cursor_edits_wrap_around() {
+        let mut list: CircularList<_> = (1..=3).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.insert_after(4);
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        cursor.move_next();
+        assert!(cursor.is_at_head());
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(items(&list), [0, 1, 2, 3, 4]);
+        assert_eq!(list.tail(), Some(&4));
+    }
+
+    #[test]
+    fn cursor_insert_on_empty_list() {
+        let mut list = CircularList::new();
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(1);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(items(&list), [0, 1]);
+    }
+
+    #[test]
+    fn cursor_remove_current() {
+        let counter = DropCounter::new();
+        let mut list: CircularList<_> = (0..3).map(|i| counter.item(i)).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(2));
+        assert_eq!(cursor.current().map(|item| item.value), Some(0));
+        assert!(cursor.is_at_head());
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(0));
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(1));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(counter.dropped(), 3);
+        check(&list);
+    }
+
+    #[test]
+    fn remove_every_kth_is_josephus_order() {
+        let mut list: CircularList<_> = (1..=7).collect();
+        let order: Vec<_> = list.remove_every_kth(3).collect();
+        assert_eq!(order, [3, 6, 2, 7, 5, 1, 4]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn remove_every_kth_leaves_the_rest() {
+        let mut list: CircularList<_> = (1..=7).collect();
+        let first: Vec<_> = list.remove_every_kth(3).take(2).collect();
+        assert_eq!(first, [3, 6]);
+        assert_eq!(items(&list), [7, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn drop_frees_every_node() {
+        let counter = DropCounter::new();
+        let list: CircularList<_> = (0..5).map(|i| counter.item(i)).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next().map(|item| item.value), Some(0));
+        drop(iter);
+        assert_eq!(counter.dropped(), 5);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_long_lists() {
+        let list: CircularList<u32> = (0..1_000_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn clone_eq_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let list: CircularList<_> = (0..3).collect();
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(hash_of(&copy), hash_of(&list));
+        copy.rotate_left(1);
+        assert_ne!(copy, list);
+        assert_eq!(items(&list), [0, 1, 2]);
+    }
+}
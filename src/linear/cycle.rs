@@ -0,0 +1,272 @@
+//! Cycle detection for iterated functions and pointer-linked nodes.
+//!
+//! `HasCycle` in `Linear/LinkedList.md` only answers yes or no. The functions
+//! here follow any successor function `x -> f(x)` from a starting state and,
+//! if the walk loops, report where the loop begins and how long it is:
+//!
+//! ```text
+//! x0 -> x1 -> ... -> x(μ-1) -> xμ -> ... -> x(μ+λ-1)
+//!                              ^                 |
+//!                              +-----------------+
+//! ```
+//!
+//! A successor of `None` ends the walk, in which case there is no cycle.
+//! [`floyd`] is the tortoise-and-hare from the Go example; [`brent`] finds the
+//! same answer with fewer successor calls on long tails. The `*_nodes`
+//! variants compare nodes by address, so they work on node types that do not
+//! (or should not) implement `PartialEq`.
+
+use std::ptr;
+
+/// Shape of a cycle found by [`floyd`] or [`brent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CycleInfo<S> {
+    /// First state that lies on the cycle, `x(μ)`.
+    pub start: S,
+    /// Number of states before the cycle, μ.
+    pub tail_len: usize,
+    /// Number of states on the cycle, λ (always at least 1).
+    pub cycle_len: usize,
+}
+
+/// Floyd's tortoise-and-hare cycle detection.
+///
+/// Runs in O(μ + λ) successor calls and O(1) extra space; returns `None`
+/// when the walk from `x0` reaches a state without successor.
+pub fn floyd<S, F>(x0: S, f: F) -> Option<CycleInfo<S>>
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> Option<S>,
+{
+    floyd_by(x0, f, |a, b| a == b)
+}
+
+/// Brent's cycle detection.
+///
+/// Same result and asymptotic cost as [`floyd`], but it evaluates `f` only
+/// once per step while searching for λ, which makes it noticeably cheaper
+/// when successor calls are expensive.
+pub fn brent<S, F>(x0: S, f: F) -> Option<CycleInfo<S>>
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> Option<S>,
+{
+    brent_by(x0, f, |a, b| a == b)
+}
+
+/// [`floyd`] over linked nodes, comparing them by address.
+pub fn floyd_nodes<'a, N, F>(head: &'a N, next: F) -> Option<CycleInfo<&'a N>>
+where
+    F: Fn(&'a N) -> Option<&'a N>,
+{
+    floyd_by(head, |node: &&'a N| next(*node), |a, b| ptr::eq(*a, *b))
+}
+
+/// [`brent`] over linked nodes, comparing them by address.
+pub fn brent_nodes<'a, N, F>(head: &'a N, next: F) -> Option<CycleInfo<&'a N>>
+where
+    F: Fn(&'a N) -> Option<&'a N>,
+{
+    brent_by(head, |node: &&'a N| next(*node), |a, b| ptr::eq(*a, *b))
+}
+
+fn floyd_by<S, F, E>(x0: S, f: F, eq: E) -> Option<CycleInfo<S>>
+where
+    S: Clone,
+    F: Fn(&S) -> Option<S>,
+    E: Fn(&S, &S) -> bool,
+{
+    // Phase 1: the hare runs twice as fast and meets the tortoise somewhere
+    // on the cycle, at a distance from x0 that is a multiple of λ.
+    let mut tortoise = f(&x0)?;
+    let mut hare = f(&f(&x0)?)?;
+    while !eq(&tortoise, &hare) {
+        tortoise = f(&tortoise)?;
+        hare = f(&f(&hare)?)?;
+    }
+
+    // Phase 2: restarting the tortoise from x0 and moving both at the same
+    // speed makes them meet exactly at the start of the cycle.
+    let mut tail_len = 0;
+    tortoise = x0;
+    while !eq(&tortoise, &hare) {
+        tortoise = f(&tortoise)?;
+        hare = f(&hare)?;
+        tail_len += 1;
+    }
+
+    // Phase 3: walk once around the cycle to measure it.
+    let mut cycle_len = 1;
+    hare = f(&tortoise)?;
+    while !eq(&tortoise, &hare) {
+        hare = f(&hare)?;
+        cycle_len += 1;
+    }
+
+    Some(CycleInfo {
+        start: tortoise,
+        tail_len,
+        cycle_len,
+    })
+}
+
+fn brent_by<S, F, E>(x0: S, f: F, eq: E) -> Option<CycleInfo<S>>
+where
+    S: Clone,
+    F: Fn(&S) -> Option<S>,
+    E: Fn(&S, &S) -> bool,
+{
+    // Phase 1: teleport the tortoise to the hare at every power of two; the
+    // hare's distance from it when they meet is λ.
+    let mut power = 1;
+    let mut cycle_len = 1;
+    let mut tortoise = x0.clone();
+    let mut hare = f(&x0)?;
+    while !eq(&tortoise, &hare) {
+        if power == cycle_len {
+            tortoise = hare.clone();
+            power *= 2;
+            cycle_len = 0;
+        }
+        hare = f(&hare)?;
+        cycle_len += 1;
+    }
+
+    // Phase 2: give the hare a head start of λ, then advance both until they
+    // meet at the start of the cycle.
+    let mut tail_len = 0;
+    tortoise = x0.clone();
+    hare = x0;
+    for _ in 0..cycle_len {
+        hare = f(&hare)?;
+    }
+    while !eq(&tortoise, &hare) {
+        tortoise = f(&tortoise)?;
+        hare = f(&hare)?;
+        tail_len += 1;
+    }
+
+    Some(CycleInfo {
+        start: tortoise,
+        tail_len,
+        cycle_len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    /// Both algorithms over nodes `0..next.len()` linked by index.
+    fn both(next: &[Option<usize>], head: usize) -> [Option<CycleInfo<usize>>; 2] {
+        let f = |&node: &usize| next[node];
+        [floyd(head, f), brent(head, f)]
+    }
+
+    fn info(start: usize, tail_len: usize, cycle_len: usize) -> Option<CycleInfo<usize>> {
+        Some(CycleInfo {
+            start,
+            tail_len,
+            cycle_len,
+        })
+    }
+
+    #[test]
+    fn acyclic_walks_return_none() {
+        assert_eq!(both(&[None], 0), [None, None]);
+        assert_eq!(both(&[Some(1), None], 0), [None, None]);
+        assert_eq!(both(&[Some(1), Some(2), Some(3), None], 0), [None, None]);
+    }
+
+    #[test]
+    fn self_loop_has_length_one() {
+        assert_eq!(both(&[Some(0)], 0), [info(0, 0, 1); 2]);
+        let next = [Some(1), Some(2), Some(2)];
+        assert_eq!(both(&next, 0), [info(2, 2, 1); 2]);
+    }
+
+    #[test]
+    fn two_cycles() {
+        assert_eq!(both(&[Some(1), Some(0)], 0), [info(0, 0, 2); 2]);
+        let next = [Some(1), Some(2), Some(3), Some(2)];
+        assert_eq!(both(&next, 0), [info(2, 2, 2); 2]);
+    }
+
+    #[test]
+    fn cycle_starting_at_head() {
+        let next = [Some(1), Some(2), Some(3), Some(4), Some(0)];
+        assert_eq!(both(&next, 0), [info(0, 0, 5); 2]);
+        assert_eq!(both(&next, 3), [info(3, 0, 5); 2]);
+    }
+
+    /// A node whose successor can be set after it is created, so that
+    /// nodes can link back to earlier ones.
+    struct Node<'a> {
+        value: u8,
+        next: Cell<Option<&'a Node<'a>>>,
+    }
+
+    /// Nodes with equal values, linked in a chain whose last node links
+    /// back to `back_to`, if any.
+    fn chain<'a>(nodes: &'a [Node<'a>], back_to: Option<usize>) {
+        for pair in nodes.windows(2) {
+            pair[0].next.set(Some(&pair[1]));
+        }
+        let last = nodes.last().unwrap();
+        last.next.set(back_to.map(|index| &nodes[index]));
+    }
+
+    fn nodes<'a>(len: usize) -> Vec<Node<'a>> {
+        (0..len)
+            .map(|_| Node {
+                value: 7,
+                next: Cell::new(None),
+            })
+            .collect()
+    }
+
+    fn next<'a>(node: &'a Node<'a>) -> Option<&'a Node<'a>> {
+        node.next.get()
+    }
+
+    #[test]
+    fn nodes_are_compared_by_address() {
+        for (len, back_to) in [(1, Some(0)), (6, Some(2)), (5, Some(4)), (4, None)] {
+            let nodes = nodes(len);
+            chain(&nodes, back_to);
+            let found = [floyd_nodes(&nodes[0], next), brent_nodes(&nodes[0], next)];
+            for found in found {
+                let Some(back_to) = back_to else {
+                    assert!(found.is_none());
+                    continue;
+                };
+                let found = found.unwrap();
+                assert!(ptr::eq(found.start, &nodes[back_to]));
+                assert_eq!(found.start.value, 7);
+                assert_eq!(found.tail_len, back_to);
+                assert_eq!(found.cycle_len, len - back_to);
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_brute_force_on_iterated_functions() {
+        for modulus in 1..60 {
+            let f = |x: &usize| Some((x * x + 1) % modulus);
+            for x0 in 0..modulus {
+                let mut first_seen = HashMap::new();
+                let mut x = x0;
+                while !first_seen.contains_key(&x) {
+                    first_seen.insert(x, first_seen.len());
+                    x = f(&x).unwrap();
+                }
+                let tail_len = first_seen[&x];
+                let expected = info(x, tail_len, first_seen.len() - tail_len);
+                assert_eq!(floyd(x0, f), expected, "floyd from {x0} mod {modulus}");
+                assert_eq!(brent(x0, f), expected, "brent from {x0} mod {modulus}");
+            }
+        }
+    }
+}
@@ -0,0 +1,1080 @@
+//! Doubly linked list with O(1) removal at both ends and a mutable cursor.
+//!
+//! The Go `Node` in `Linear/LinkedList.md` carries a `Previous` field "only for
+//! doubly linked lists", yet its `DeleteFromEnd` still walks the list. Here
+//! every node links both ways, so both deletions are O(1), and [`CursorMut`]
+//! exposes in-place editing (insert, remove, splice, split) through a safe API.
+//! Raw pointers stay private to this module.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use super::ListError;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    data: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn alloc(data: T) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node {
+            data,
+            prev: None,
+            next: None,
+        })))
+    }
+}
+
+/// A doubly linked list.
+///
+/// | Operation                 | Cost |
+/// |---------------------------|------|
+/// | `insert_at_beginning`     | O(1) |
+/// | `insert_at_end`           | O(1) |
+/// | `delete_from_beginning`   | O(1) |
+/// | `delete_from_end`         | O(1) |
+/// | `append`                  | O(1) |
+/// | `len`                     | O(1) |
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list exclusively owns its nodes, exactly like `Box<Node<T>>`.
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+impl<T> DoublyLinkedList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        DoublyLinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        // SAFETY: nodes reachable from the list are live while `&self` is.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Returns a mutable reference to the first element.
+    pub fn head_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `head`, with exclusivity guaranteed by `&mut self`.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        // SAFETY: as in `head`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Returns a mutable reference to the last element.
+    pub fn tail_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `head_mut`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Adds an element to the front of the list in O(1).
+    pub fn insert_at_beginning(&mut self, data: T) {
+        let node = Node::alloc(data);
+        // SAFETY: `node` is freshly allocated and the old head is live.
+        unsafe { self.link_after(None, node) };
+    }
+
+    /// Adds an element to the back of the list in O(1).
+    pub fn insert_at_end(&mut self, data: T) {
+        let node = Node::alloc(data);
+        // SAFETY: `node` is freshly allocated and the old tail is live.
+        unsafe { self.link_after(self.tail, node) };
+    }
+
+    /// Removes and returns the first element in O(1).
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        let head = self.head.ok_or(ListError::Empty)?;
+        // SAFETY: `head` belongs to this list.
+        Ok(unsafe { self.unlink(head) })
+    }
+
+    /// Removes and returns the last element in O(1).
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        let tail = self.tail.ok_or(ListError::Empty)?;
+        // SAFETY: `tail` belongs to this list.
+        Ok(unsafe { self.unlink(tail) })
+    }
+
+    /// Moves every element of `other` to the end of `self` in O(1).
+    pub fn append(&mut self, other: &mut DoublyLinkedList<T>) {
+        let other = std::mem::take(other);
+        // SAFETY: `other` is detached from its original owner.
+        unsafe { self.splice_after_node(self.tail, other) };
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        *self = DoublyLinkedList::new();
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns a double-ended iterator over references.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a double-ended iterator over mutable references.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a read-only cursor positioned at the first element.
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// Returns a read-only cursor positioned at the last element.
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    /// Returns an editing cursor positioned at the first element.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// Returns an editing cursor positioned at the last element.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    /// Links `node` right after `prev`, or at the front when `prev` is `None`.
+    ///
+    /// # Safety
+    ///
+    /// `prev` must belong to this list and `node` must be unlinked.
+    unsafe fn link_after(&mut self, prev: Link<T>, node: NonNull<Node<T>>) {
+        let next = match prev {
+            Some(prev) => (*prev.as_ptr()).next,
+            None => self.head,
+        };
+        (*node.as_ptr()).prev = prev;
+        (*node.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// Detaches `node` from the list and returns its data.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let boxed = Box::from_raw(node.as_ptr());
+        match boxed.prev {
+            Some(prev) => (*prev.as_ptr()).next = boxed.next,
+            None => self.head = boxed.next,
+        }
+        match boxed.next {
+            Some(next) => (*next.as_ptr()).prev = boxed.prev,
+            None => self.tail = boxed.prev,
+        }
+        self.len -= 1;
+        boxed.data
+    }
+
+    /// Moves all of `other` in after `prev`, or at the front when `prev` is
+    /// `None`.
+    ///
+    /// # Safety
+    ///
+    /// `prev` must belong to this list.
+    unsafe fn splice_after_node(&mut self, prev: Link<T>, mut other: DoublyLinkedList<T>) {
+        let (Some(first), Some(last)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        let next = match prev {
+            Some(prev) => (*prev.as_ptr()).next,
+            None => self.head,
+        };
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(first),
+            None => self.head = Some(first),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(last),
+            None => self.tail = Some(last),
+        }
+        self.len += std::mem::replace(&mut other.len, 0);
+    }
+
+    /// Splits off every node after `node` into a new list.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list and be followed by `after` elements.
+    unsafe fn split_after_node(&mut self, node: Link<T>, after: usize) -> DoublyLinkedList<T> {
+        let first = match node {
+            Some(node) => (*node.as_ptr()).next.take(),
+            None => self.head.take(),
+        };
+        let Some(first) = first else {
+            return DoublyLinkedList::new();
+        };
+        (*first.as_ptr()).prev = None;
+        let split = DoublyLinkedList {
+            head: Some(first),
+            tail: self.tail,
+            len: after,
+            marker: PhantomData,
+        };
+        self.tail = node;
+        if node.is_none() {
+            self.head = None;
+        }
+        self.len -= after;
+        split
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        DoublyLinkedList::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.delete_from_beginning().is_ok() {}
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`DoublyLinkedList::iter`].
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list is borrowed for `'a` and `len` keeps the two
+            // ends from crossing.
+            let node = unsafe { &*node.as_ptr() };
+            self.head = node.next;
+            self.len -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: as in `next`.
+            let node = unsafe { &*node.as_ptr() };
+            self.tail = node.prev;
+            self.len -= 1;
+            &node.data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+/// Mutable iterator returned by [`DoublyLinkedList::iter_mut`].
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list is mutably borrowed for `'a` and each node is
+            // yielded at most once because `len` keeps the two ends apart.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.head = node.next;
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: as in `next`.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.tail = node.prev;
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator returned by [`DoublyLinkedList::into_iter`].
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.delete_from_end().ok()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Read-only cursor over a [`DoublyLinkedList`].
+///
+/// A cursor points either at an element or at the "ghost" position between
+/// the tail and the head; moving past either end lands on the ghost, and
+/// moving again wraps around to the other end.
+pub struct Cursor<'a, T> {
+    current: Link<T>,
+    index: usize,
+    list: &'a DoublyLinkedList<T>,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// Index of the current element, or `None` on the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// The element under the cursor.
+    pub fn current(&self) -> Option<&'a T> {
+        // SAFETY: the list is borrowed for `'a`.
+        self.current.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Moves the cursor one step towards the tail.
+    pub fn move_next(&mut self) {
+        match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Moves the cursor one step towards the head.
+    pub fn move_prev(&mut self) {
+        match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// The element after the cursor, wrapping from the ghost to the head.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        // SAFETY: as above.
+        next.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// The element before the cursor, wrapping from the ghost to the tail.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: as above.
+        prev.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+/// Editing cursor over a [`DoublyLinkedList`].
+///
+/// Navigation follows the same ghost-position rules as [`Cursor`]. Every
+/// edit is O(1) except that splitting and splicing also adjust lengths,
+/// which the cursor tracks through its index.
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    index: usize,
+    list: &'a mut DoublyLinkedList<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Index of the current element, or `None` on the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// The element under the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is mutably borrowed by the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Moves the cursor one step towards the tail.
+    pub fn move_next(&mut self) {
+        match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Moves the cursor one step towards the head.
+    pub fn move_prev(&mut self) {
+        match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// The element after the cursor, wrapping from the ghost to the head.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        // SAFETY: as above.
+        next.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// The element before the cursor, wrapping from the ghost to the tail.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: as above.
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Returns a read-only view of the cursor.
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.current,
+            index: self.index,
+            list: self.list,
+        }
+    }
+
+    /// Inserts `data` before the current element.
+    ///
+    /// On the ghost position the element becomes the new tail.
+    pub fn insert_before(&mut self, data: T) {
+        let node = Node::alloc(data);
+        let prev = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(current) => unsafe { (*current.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: `prev` belongs to the list and `node` is fresh.
+        unsafe { self.list.link_after(prev, node) };
+        self.index = self.index_after_growth(1);
+    }
+
+    /// Inserts `data` after the current element.
+    ///
+    /// On the ghost position the element becomes the new head.
+    pub fn insert_after(&mut self, data: T) {
+        let node = Node::alloc(data);
+        // SAFETY: `current` belongs to the list and `node` is fresh.
+        unsafe { self.list.link_after(self.current, node) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// Removes the current element and moves the cursor to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: `node` belongs to the borrowed list.
+        self.current = unsafe { (*node.as_ptr()).next };
+        // SAFETY: as above.
+        Some(unsafe { self.list.unlink(node) })
+    }
+
+    /// Moves every element of `other` in before the current element.
+    ///
+    /// On the ghost position the elements are appended at the tail.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        let added = other.len;
+        let prev = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(current) => unsafe { (*current.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: `prev` belongs to the list and `other` is owned.
+        unsafe { self.list.splice_after_node(prev, other) };
+        self.index = self.index_after_growth(added);
+    }
+
+    /// Moves every element of `other` in after the current element.
+    ///
+    /// On the ghost position the elements are prepended at the head.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        // SAFETY: `current` belongs to the list and `other` is owned.
+        unsafe { self.list.splice_after_node(self.current, other) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// Splits the list after the current element, returning the tail part.
+    ///
+    /// On the ghost position the whole list is returned.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let after = match self.current {
+            Some(_) => self.list.len - self.index - 1,
+            None => self.list.len,
+        };
+        // SAFETY: `current` belongs to the list and is followed by `after`
+        // elements.
+        let split = unsafe { self.list.split_after_node(self.current, after) };
+        if self.current.is_none() {
+            self.index = 0;
+        }
+        split
+    }
+
+    /// Splits the list before the current element, returning the head part.
+    ///
+    /// On the ghost position the whole list is returned.
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let before = match self.current {
+            Some(_) => self.index,
+            None => self.list.len,
+        };
+        let prev = match self.current {
+            // SAFETY: `node` belongs to the borrowed list.
+            Some(current) => unsafe { (*current.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: `prev` belongs to the list and is followed by the
+        // remaining `len - before` elements.
+        let rest = unsafe { self.list.split_after_node(prev, self.list.len - before) };
+        let head = std::mem::replace(self.list, rest);
+        self.index = 0;
+        head
+    }
+
+    /// Index of the current element after `added` elements were linked in
+    /// before it; the ghost always sits at `len`.
+    fn index_after_growth(&self, added: usize) -> usize {
+        match self.current {
+            Some(_) => self.index + added,
+            None => self.list.len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Checks `len`, `tail` and that every `prev` link mirrors a `next` link.
+    fn check<T>(list: &DoublyLinkedList<T>) {
+        let mut prev = None;
+        let mut count = 0;
+        let mut current = list.head;
+        while let Some(node) = current {
+            // SAFETY: the list is borrowed and owns its nodes.
+            let node_ref = unsafe { &*node.as_ptr() };
+            assert_eq!(node_ref.prev, prev, "prev link of node {count}");
+            prev = Some(node);
+            current = node_ref.next;
+            count += 1;
+        }
+        assert_eq!(list.tail, prev);
+        assert_eq!(count, list.len);
+        assert_eq!(list.is_empty(), count == 0);
+    }
+
+    fn items<T: Clone>(list: &DoublyLinkedList<T>) -> Vec<T> {
+        let forward: Vec<T> = list.iter().cloned().collect();
+        let mut backward: Vec<T> = list.iter().rev().cloned().collect();
+        backward.reverse();
+        assert_eq!(forward.len(), backward.len());
+        forward
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.iter().next_back(), None);
+        assert_eq!(list.cursor_front().current(), None);
+        assert_eq!(list.cursor_back().index(), None);
+    }
+
+    #[test]
+    fn inserts_and_deletes_at_both_ends() {
+        let mut list = DoublyLinkedList::new();
+        list.insert_at_end(2);
+        check(&list);
+        list.insert_at_beginning(1);
+        check(&list);
+        list.insert_at_end(3);
+        check(&list);
+        assert_eq!(items(&list), [1, 2, 3]);
+        assert_eq!(list.delete_from_end(), Ok(3));
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Ok(1));
+        check(&list);
+        assert_eq!((list.head(), list.tail()), (Some(&2), Some(&2)));
+        assert_eq!(list.delete_from_end(), Ok(2));
+        check(&list);
+        list.insert_at_beginning(4);
+        check(&list);
+        assert_eq!(items(&list), [4]);
+    }
+
+    #[test]
+    fn append_links_both_ways() {
+        let mut list: DoublyLinkedList<i32> = (1..=2).collect();
+        let mut other: DoublyLinkedList<i32> = (3..=4).collect();
+        list.append(&mut other);
+        check(&list);
+        check(&other);
+        assert_eq!(items(&list), [1, 2, 3, 4]);
+
+        let mut empty = DoublyLinkedList::new();
+        empty.append(&mut list);
+        check(&empty);
+        check(&list);
+        assert_eq!(items(&empty), [1, 2, 3, 4]);
+        empty.append(&mut list);
+        check(&empty);
+    }
+
+    #[test]
+    fn reverse_iteration() {
+        let mut list: DoublyLinkedList<i32> = (1..=5).collect();
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            [5, 4, 3, 2, 1]
+        );
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() = 50;
+        *iter.next().unwrap() = 10;
+        assert_eq!(iter.rev().map(|item| *item).collect::<Vec<_>>(), [4, 3, 2]);
+        assert_eq!(items(&list), [10, 2, 3, 4, 50]);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(50));
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.rev().collect::<Vec<_>>(), [4, 3, 2]);
+    }
+
+    #[test]
+    fn cursor_navigation_wraps_through_the_ghost() {
+        let list: DoublyLinkedList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_front();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&1)));
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(
+            (cursor.peek_next(), cursor.peek_prev()),
+            (Some(&1), Some(&3))
+        );
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&3)));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&1)));
+        assert_eq!(cursor.peek_next(), Some(&2));
+        assert_eq!(cursor.peek_prev(), None);
+    }
+
+    #[test]
+    fn cursor_inserts_keep_links_and_index() {
+        let mut list: DoublyLinkedList<i32> = [2, 4].into_iter().collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_before(1);
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(1), Some(2))
+        );
+        cursor.insert_after(3);
+        assert_eq!(cursor.index(), Some(1));
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        // On the ghost, `insert_before` appends and `insert_after` prepends.
+        cursor.insert_before(5);
+        cursor.insert_after(0);
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(0), Some(0))
+        );
+        check(&list);
+        assert_eq!(items(&list), [0, 1, 2, 3, 4, 5]);
+
+        let mut list = DoublyLinkedList::new();
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_after(1);
+        cursor.insert_before(2);
+        check(&list);
+        assert_eq!(items(&list), [1, 2]);
+    }
+
+    #[test]
+    fn cursor_removes_keep_links() {
+        let counter = DropCounter::new();
+        let mut list: DoublyLinkedList<_> = (0..4).map(|value| counter.item(value)).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(1));
+        assert_eq!(cursor.current().map(|item| item.value), Some(2));
+        cursor.move_next();
+        // Removing the tail leaves the cursor on the ghost.
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(3));
+        assert_eq!(cursor.index(), None);
+        assert!(cursor.remove_current().is_none());
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().map(|item| item.value), Some(0));
+        assert_eq!(counter.dropped(), 3);
+        check(&list);
+        assert_eq!(list.iter().map(|item| item.value).collect::<Vec<_>>(), [2]);
+        drop(list);
+        assert_eq!(counter.dropped(), 4);
+    }
+
+    #[test]
+    fn cursor_splices_keep_links() {
+        let mut list: DoublyLinkedList<i32> = [1, 4].into_iter().collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.splice_after([2, 3].into_iter().collect());
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        cursor.splice_before(DoublyLinkedList::new());
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(1), Some(2))
+        );
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.splice_before([5, 6].into_iter().collect());
+        cursor.splice_after([-1, 0].into_iter().collect());
+        assert_eq!(cursor.index(), None);
+        check(&list);
+        assert_eq!(items(&list), [-1, 0, 1, 2, 3, 4, 5, 6]);
+
+        let mut list = DoublyLinkedList::new();
+        list.cursor_front_mut()
+            .splice_before([1, 2].into_iter().collect());
+        check(&list);
+        assert_eq!(items(&list), [1, 2]);
+    }
+
+    #[test]
+    fn cursor_splits_keep_links() {
+        let mut list: DoublyLinkedList<i32> = (0..6).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(2));
+        let before = cursor.split_before();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(0), Some(2))
+        );
+        for (part, expected) in [(&before, &[0, 1][..]), (&list, &[2]), (&after, &[3, 4, 5])] {
+            check(part);
+            assert_eq!(items(part), expected);
+        }
+
+        let mut list: DoublyLinkedList<i32> = (0..3).collect();
+        let mut cursor = list.cursor_front_mut();
+        assert!(cursor.split_before().is_empty());
+        cursor.move_prev();
+        let all = cursor.split_after();
+        check(&all);
+        check(&list);
+        assert_eq!((items(&all), list.len()), (vec![0, 1, 2], 0));
+
+        let mut list: DoublyLinkedList<i32> = (0..3).collect();
+        let mut cursor = list.cursor_back_mut();
+        assert!(cursor.split_after().is_empty());
+        cursor.move_next();
+        let all = cursor.split_before();
+        check(&all);
+        check(&list);
+        assert_eq!((items(&all), list.len()), (vec![0, 1, 2], 0));
+    }
+
+    #[test]
+    fn drop_and_into_iter_free_every_element() {
+        let counter = DropCounter::new();
+        let list: DoublyLinkedList<_> = (0..5).map(|value| counter.item(value)).collect();
+        let mut iter = list.into_iter();
+        drop(iter.next());
+        drop(iter.next_back());
+        assert_eq!(counter.dropped(), 2);
+        drop(iter);
+        assert_eq!(counter.dropped(), 5);
+
+        let mut list: DoublyLinkedList<_> = (0..3).map(|value| counter.item(value)).collect();
+        list.clear();
+        check(&list);
+        assert_eq!(counter.dropped(), 8);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_long_lists() {
+        let list: DoublyLinkedList<u32> = (0..1_000_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn clone_is_deep_and_equal() {
+        let list: DoublyLinkedList<String> = ["a", "b"].map(String::from).into_iter().collect();
+        let mut copy = list.clone();
+        check(&copy);
+        assert_eq!(copy, list);
+        *copy.tail_mut().unwrap() = "z".to_owned();
+        assert_ne!(copy, list);
+        assert_eq!(list.tail().map(String::as_str), Some("b"));
+    }
+}
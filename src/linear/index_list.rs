@@ -0,0 +1,1271 @@
+//! Doubly linked list stored in a `Vec` arena.
+//!
+//! `Linear/LinkedList.md` lists "additional overhead per node for references"
+//! among the costs of a linked list, and the Go version allocates every
+//! `Node` on its own. [`IndexList`] keeps all nodes in one `Vec`, links them
+//! with `u32` indices and recycles removed slots through a free list, so a
+//! list of `n` elements performs O(log n) allocations and its nodes stay close
+//! together in memory.
+//!
+//! Inserting returns a [`Handle`]: a slot index paired with the slot's
+//! generation. Removing an element bumps the generation, so a handle that
+//! outlives its element is reported as [`ListError::StaleHandle`] instead of
+//! silently pointing at whatever reused the slot.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+use super::ListError;
+
+const NIL: u32 = u32::MAX;
+
+/// Generation-checked reference to an element of an [`IndexList`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    generation: u32,
+    state: State<T>,
+}
+
+#[derive(Clone)]
+enum State<T> {
+    Occupied { data: T, prev: u32, next: u32 },
+    Vacant { next_free: u32 },
+}
+
+/// An arena-backed doubly linked list.
+///
+/// It offers the same operations as [`LinkedList`](super::LinkedList), plus
+/// O(1) access, insertion, removal and relinking through [`Handle`]s.
+///
+/// | Operation                          | Cost           |
+/// |------------------------------------|----------------|
+/// | `insert_at_beginning`/`_at_end`    | O(1) amortized |
+/// | `delete_from_beginning`/`_from_end`| O(1)           |
+/// | `get`, `remove`, `insert_after`    | O(1)           |
+/// | `move_after`, `move_before`        | O(1)           |
+/// | `append` (m elements)              | O(m)           |
+/// | `merge`                            | O(n + m)       |
+/// | `sort`                             | O(n log n)     |
+#[derive(Clone)]
+pub struct IndexList<T> {
+    slots: Vec<Slot<T>>,
+    head: u32,
+    tail: u32,
+    free: u32,
+    len: usize,
+}
+
+impl<T> IndexList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        IndexList {
+            slots: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    /// Creates an empty list with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexList {
+            slots: Vec::with_capacity(capacity),
+            ..IndexList::new()
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        self.data(self.head)
+    }
+
+    /// Returns a mutable reference to the first element.
+    pub fn head_mut(&mut self) -> Option<&mut T> {
+        self.data_mut(self.head)
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        self.data(self.tail)
+    }
+
+    /// Returns a mutable reference to the last element.
+    pub fn tail_mut(&mut self) -> Option<&mut T> {
+        self.data_mut(self.tail)
+    }
+
+    /// Handle of the first element.
+    pub fn head_handle(&self) -> Option<Handle> {
+        self.handle(self.head)
+    }
+
+    /// Handle of the last element.
+    pub fn tail_handle(&self) -> Option<Handle> {
+        self.handle(self.tail)
+    }
+
+    /// Adds an element to the front of the list.
+    pub fn insert_at_beginning(&mut self, data: T) -> Handle {
+        let index = self.alloc(data);
+        self.link_between(index, NIL, self.head);
+        self.handle_unchecked(index)
+    }
+
+    /// Adds an element to the back of the list.
+    pub fn insert_at_end(&mut self, data: T) -> Handle {
+        let index = self.alloc(data);
+        self.link_between(index, self.tail, NIL);
+        self.handle_unchecked(index)
+    }
+
+    /// Inserts `data` right after the element `at` refers to.
+    pub fn insert_after(&mut self, at: Handle, data: T) -> Result<Handle, ListError> {
+        let (_, next) = self.links(at)?;
+        let index = self.alloc(data);
+        self.link_between(index, at.index, next);
+        Ok(self.handle_unchecked(index))
+    }
+
+    /// Inserts `data` right before the element `at` refers to.
+    pub fn insert_before(&mut self, at: Handle, data: T) -> Result<Handle, ListError> {
+        let (prev, _) = self.links(at)?;
+        let index = self.alloc(data);
+        self.link_between(index, prev, at.index);
+        Ok(self.handle_unchecked(index))
+    }
+
+    /// Removes and returns the first element in O(1).
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        if self.head == NIL {
+            return Err(ListError::Empty);
+        }
+        Ok(self.release(self.head))
+    }
+
+    /// Removes and returns the last element in O(1).
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        if self.tail == NIL {
+            return Err(ListError::Empty);
+        }
+        Ok(self.release(self.tail))
+    }
+
+    /// Removes the element `handle` refers to; the handle becomes stale.
+    pub fn remove(&mut self, handle: Handle) -> Result<T, ListError> {
+        self.links(handle)?;
+        Ok(self.release(handle.index))
+    }
+
+    /// Returns `true` if `handle` still refers to an element of this list.
+    pub fn contains_handle(&self, handle: Handle) -> bool {
+        self.links(handle).is_ok()
+    }
+
+    /// Returns the element `handle` refers to.
+    pub fn get(&self, handle: Handle) -> Result<&T, ListError> {
+        match self.slots.get(handle.index as usize) {
+            Some(Slot {
+                generation,
+                state: State::Occupied { data, .. },
+            }) if *generation == handle.generation => Ok(data),
+            _ => Err(ListError::StaleHandle),
+        }
+    }
+
+    /// Returns the element `handle` refers to, mutably.
+    pub fn get_mut(&mut self, handle: Handle) -> Result<&mut T, ListError> {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(Slot {
+                generation,
+                state: State::Occupied { data, .. },
+            }) if *generation == handle.generation => Ok(data),
+            _ => Err(ListError::StaleHandle),
+        }
+    }
+
+    /// Handle of the element after `handle`, or `None` at the tail.
+    pub fn next_handle(&self, handle: Handle) -> Result<Option<Handle>, ListError> {
+        let (_, next) = self.links(handle)?;
+        Ok(self.handle(next))
+    }
+
+    /// Handle of the element before `handle`, or `None` at the head.
+    pub fn prev_handle(&self, handle: Handle) -> Result<Option<Handle>, ListError> {
+        let (prev, _) = self.links(handle)?;
+        Ok(self.handle(prev))
+    }
+
+    /// Relinks the element `handle` refers to right after `target`.
+    ///
+    /// Both handles stay valid. Moving an element after itself does nothing.
+    pub fn move_after(&mut self, handle: Handle, target: Handle) -> Result<(), ListError> {
+        self.links(handle)?;
+        self.links(target)?;
+        if handle == target {
+            return Ok(());
+        }
+        self.unlink(handle.index);
+        let (_, next) = self.links(target)?;
+        self.link_between(handle.index, target.index, next);
+        Ok(())
+    }
+
+    /// Relinks the element `handle` refers to right before `target`.
+    ///
+    /// Both handles stay valid. Moving an element before itself does nothing.
+    pub fn move_before(&mut self, handle: Handle, target: Handle) -> Result<(), ListError> {
+        self.links(handle)?;
+        self.links(target)?;
+        if handle == target {
+            return Ok(());
+        }
+        self.unlink(handle.index);
+        let (prev, _) = self.links(target)?;
+        self.link_between(handle.index, prev, target.index);
+        Ok(())
+    }
+
+    /// Removes every element. All outstanding handles become stale.
+    pub fn clear(&mut self) {
+        while self.delete_from_beginning().is_ok() {}
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns a double-ended iterator over references.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+        }
+    }
+
+    /// Returns a double-ended iterator over mutable references.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.as_mut_ptr(),
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a double-ended iterator over the handles of all elements.
+    pub fn handles(&self) -> Handles<'_, T> {
+        Handles { iter: self.iter() }
+    }
+
+    /// Moves every element of `other` to the end of this list, leaving
+    /// `other` empty.
+    ///
+    /// The elements get new slots here, so handles into `other` become stale.
+    /// O(m) for `m` moved elements.
+    pub fn append(&mut self, other: &mut IndexList<T>) {
+        while let Ok(data) = other.delete_from_beginning() {
+            self.insert_at_end(data);
+        }
+    }
+
+    /// Splits the list in two at index `at`: `self` keeps `[0, at)` and the
+    /// returned list holds `[at, len)`.
+    ///
+    /// Handles to the moved elements become stale. O(len - at).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> IndexList<T> {
+        assert!(at <= self.len, "split index {at} out of bounds");
+        let mut back = IndexList::with_capacity(self.len - at);
+        while self.len > at {
+            let data = self.release(self.tail);
+            back.insert_at_beginning(data);
+        }
+        back
+    }
+
+    /// Returns the middle element; an even-length list yields the second of
+    /// the two middle elements, as in [`LinkedList::middle`].
+    ///
+    /// [`LinkedList::middle`]: super::LinkedList::middle
+    pub fn middle(&self) -> Option<&T> {
+        self.iter().nth(self.len / 2)
+    }
+
+    /// Returns the `n`-th element counted from the end, where `n == 0` is the
+    /// tail, as in [`LinkedList::nth_from_end`].
+    ///
+    /// The `prev` links make this a walk back from the tail. O(n).
+    ///
+    /// [`LinkedList::nth_from_end`]: super::LinkedList::nth_from_end
+    pub fn nth_from_end(&self, n: usize) -> Option<&T> {
+        self.iter().rev().nth(n)
+    }
+
+    /// Splits the list at its middle and returns the second half; `self`
+    /// keeps the first `⌈n/2⌉` elements, as in
+    /// [`LinkedList::split_at_middle`].
+    ///
+    /// Handles to the moved elements become stale. O(n).
+    ///
+    /// [`LinkedList::split_at_middle`]: super::LinkedList::split_at_middle
+    pub fn split_at_middle(&mut self) -> IndexList<T> {
+        self.split_off(self.len.div_ceil(2))
+    }
+
+    /// Reverses the list in place by swapping every node's links.
+    ///
+    /// Handles stay valid. O(n).
+    pub fn reverse(&mut self) {
+        let mut index = self.head;
+        while index != NIL {
+            let State::Occupied { prev, next, .. } = &mut self.slots[index as usize].state else {
+                unreachable!("linked slot is occupied");
+            };
+            std::mem::swap(prev, next);
+            index = *prev;
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Reverses every consecutive group of `k` elements in place.
+    ///
+    /// A trailing group shorter than `k` keeps its order; `k < 2` leaves the
+    /// list untouched. Each element of a group is relinked in front of the
+    /// ones before it, so handles stay valid. O(n), O(1) extra space.
+    pub fn reverse_k_groups(&mut self, k: usize) {
+        if k < 2 {
+            return;
+        }
+        // The element the current group follows.
+        let mut before = NIL;
+        let mut index = self.head;
+        let mut remaining = self.len;
+        while remaining >= k {
+            let first = index;
+            for _ in 0..k {
+                let next = self.link_of(index).1;
+                self.unlink(index);
+                let after = match before {
+                    NIL => self.head,
+                    before => self.link_of(before).1,
+                };
+                self.link_between(index, before, after);
+                index = next;
+            }
+            before = first;
+            remaining -= k;
+        }
+    }
+
+    /// Returns `true` if the list reads the same forwards and backwards.
+    ///
+    /// The `prev` links let this compare from both ends without changing the
+    /// list; it takes `&mut self` only to match
+    /// [`LinkedList::is_palindrome`], so either list can stand in for the
+    /// other. O(n), O(1) extra space.
+    ///
+    /// [`LinkedList::is_palindrome`]: super::LinkedList::is_palindrome
+    pub fn is_palindrome(&mut self) -> bool
+    where
+        T: PartialEq,
+    {
+        let half = self.len / 2;
+        self.iter().take(half).eq(self.iter().rev().take(half))
+    }
+
+    /// Sorts the list in ascending order.
+    ///
+    /// See [`sort_by`](IndexList::sort_by) for the algorithm.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Sorts the list by the key `f` extracts from each element.
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sorts the list with the comparator `cmp`.
+    ///
+    /// The slot indices are stably sorted in a temporary `Vec` and relinked in
+    /// that order, so no element moves and every handle stays valid.
+    /// O(n log n) comparisons, O(n) extra space.
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut order: Vec<u32> = self.handles().map(|handle| handle.index).collect();
+        order.sort_by(|&a, &b| cmp(self.occupied(a), self.occupied(b)));
+        self.relink(&order);
+    }
+
+    /// Merges the sorted list `other` into this sorted list, leaving `other`
+    /// empty.
+    ///
+    /// On ties elements of `self` come first. Handles into `self` stay valid;
+    /// handles into `other` become stale. O(n + m) comparisons.
+    pub fn merge(&mut self, other: &mut IndexList<T>)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    /// Like [`merge`](IndexList::merge), with a custom comparator that both
+    /// lists must already be sorted by.
+    pub fn merge_by<F>(&mut self, other: &mut IndexList<T>, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut cursor = self.head;
+        while let Ok(data) = other.delete_from_beginning() {
+            while cursor != NIL && cmp(&data, self.occupied(cursor)) != Ordering::Less {
+                cursor = self.link_of(cursor).1;
+            }
+            let index = self.alloc(data);
+            match cursor {
+                NIL => self.link_between(index, self.tail, NIL),
+                next => self.link_between(index, self.link_of(next).0, next),
+            }
+        }
+    }
+
+    /// Removes consecutive duplicate elements, which on a sorted list removes
+    /// every duplicate. Returns the number of elements removed.
+    ///
+    /// Handles to the kept elements stay valid. O(n) comparisons.
+    pub fn dedup_sorted(&mut self) -> usize
+    where
+        T: PartialEq,
+    {
+        let mut removed = 0;
+        let mut index = self.head;
+        while index != NIL {
+            let mut next = self.link_of(index).1;
+            while next != NIL && self.occupied(next) == self.occupied(index) {
+                self.release(next);
+                removed += 1;
+                next = self.link_of(index).1;
+            }
+            index = next;
+        }
+        removed
+    }
+
+    /// Reorders the list so that every element matching `pred` comes before
+    /// every element that does not, keeping the relative order within both
+    /// groups. Returns the number of matching elements.
+    ///
+    /// Rejected elements are relinked at the tail one by one; handles stay
+    /// valid. O(n), O(1) extra space.
+    pub fn partition<F>(&mut self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut matched = 0;
+        let mut index = self.head;
+        for _ in 0..self.len {
+            let next = self.link_of(index).1;
+            if pred(self.occupied(index)) {
+                matched += 1;
+            } else {
+                self.unlink(index);
+                self.link_between(index, self.tail, NIL);
+            }
+            index = next;
+        }
+        matched
+    }
+
+    fn data(&self, index: u32) -> Option<&T> {
+        match &self.slots.get(index as usize)?.state {
+            State::Occupied { data, .. } => Some(data),
+            State::Vacant { .. } => None,
+        }
+    }
+
+    fn data_mut(&mut self, index: u32) -> Option<&mut T> {
+        match &mut self.slots.get_mut(index as usize)?.state {
+            State::Occupied { data, .. } => Some(data),
+            State::Vacant { .. } => None,
+        }
+    }
+
+    fn occupied(&self, index: u32) -> &T {
+        self.data(index).expect("linked slot is occupied")
+    }
+
+    fn handle(&self, index: u32) -> Option<Handle> {
+        (index != NIL).then(|| self.handle_unchecked(index))
+    }
+
+    fn handle_unchecked(&self, index: u32) -> Handle {
+        Handle {
+            index,
+            generation: self.slots[index as usize].generation,
+        }
+    }
+
+    /// Returns `(prev, next)` of a live element, validating the handle.
+    fn links(&self, handle: Handle) -> Result<(u32, u32), ListError> {
+        match self.slots.get(handle.index as usize) {
+            Some(Slot {
+                generation,
+                state: State::Occupied { prev, next, .. },
+            }) if *generation == handle.generation => Ok((*prev, *next)),
+            _ => Err(ListError::StaleHandle),
+        }
+    }
+
+    /// Returns `(prev, next)` of a linked slot.
+    fn link_of(&self, index: u32) -> (u32, u32) {
+        match self.slots[index as usize].state {
+            State::Occupied { prev, next, .. } => (prev, next),
+            State::Vacant { .. } => unreachable!("linked slot is occupied"),
+        }
+    }
+
+    fn prev_mut(&mut self, index: u32) -> &mut u32 {
+        match &mut self.slots[index as usize].state {
+            State::Occupied { prev, .. } => prev,
+            State::Vacant { .. } => unreachable!("linked slot is occupied"),
+        }
+    }
+
+    fn next_mut(&mut self, index: u32) -> &mut u32 {
+        match &mut self.slots[index as usize].state {
+            State::Occupied { next, .. } => next,
+            State::Vacant { .. } => unreachable!("linked slot is occupied"),
+        }
+    }
+
+    /// Stores `data` in a free slot (or a new one) without linking it.
+    fn alloc(&mut self, data: T) -> u32 {
+        let state = State::Occupied {
+            data,
+            prev: NIL,
+            next: NIL,
+        };
+        if self.free != NIL {
+            let index = self.free;
+            let slot = &mut self.slots[index as usize];
+            let State::Vacant { next_free } = slot.state else {
+                unreachable!("free list only holds vacant slots");
+            };
+            self.free = next_free;
+            slot.state = state;
+            index
+        } else {
+            let index = u32::try_from(self.slots.len())
+                .ok()
+                .filter(|&index| index != NIL)
+                .expect("IndexList capacity overflow");
+            self.slots.push(Slot {
+                generation: 0,
+                state,
+            });
+            index
+        }
+    }
+
+    fn link_between(&mut self, index: u32, prev: u32, next: u32) {
+        *self.prev_mut(index) = prev;
+        *self.next_mut(index) = next;
+        match prev {
+            NIL => self.head = index,
+            prev => *self.next_mut(prev) = index,
+        }
+        match next {
+            NIL => self.tail = index,
+            next => *self.prev_mut(next) = index,
+        }
+        self.len += 1;
+    }
+
+    /// Relinks the live slots in `order`, which must hold each of them
+    /// exactly once.
+    fn relink(&mut self, order: &[u32]) {
+        let mut prev = NIL;
+        for &index in order {
+            *self.prev_mut(index) = prev;
+            match prev {
+                NIL => self.head = index,
+                prev => *self.next_mut(prev) = index,
+            }
+            prev = index;
+        }
+        if prev != NIL {
+            *self.next_mut(prev) = NIL;
+        }
+        self.tail = prev;
+    }
+
+    fn unlink(&mut self, index: u32) {
+        let (prev, next) = self.link_of(index);
+        match prev {
+            NIL => self.head = next,
+            prev => *self.next_mut(prev) = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => *self.prev_mut(next) = prev,
+        }
+        self.len -= 1;
+    }
+
+    /// Unlinks a live slot, vacates it and bumps its generation.
+    ///
+    /// A slot whose generation would wrap around is retired instead of
+    /// being put back on the free list, so old handles can never alias it.
+    fn release(&mut self, index: u32) -> T {
+        self.unlink(index);
+        let slot = &mut self.slots[index as usize];
+        let retired = slot.generation == u32::MAX;
+        let next_free = if retired { NIL } else { self.free };
+        let State::Occupied { data, .. } =
+            std::mem::replace(&mut slot.state, State::Vacant { next_free })
+        else {
+            unreachable!("linked slot is occupied");
+        };
+        if !retired {
+            slot.generation += 1;
+            self.free = index;
+        }
+        data
+    }
+}
+
+impl<T> Default for IndexList<T> {
+    fn default() -> Self {
+        IndexList::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IndexList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for IndexList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for IndexList<T> {}
+
+impl<T: Hash> Hash for IndexList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Extend<T> for IndexList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for IndexList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = IndexList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for IndexList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IndexList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut IndexList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`IndexList::iter`].
+pub struct Iter<'a, T> {
+    list: &'a IndexList<T>,
+    head: u32,
+    tail: u32,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn next_index(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+        let index = self.head;
+        if let State::Occupied { next, .. } = self.list.slots[index as usize].state {
+            self.head = next;
+        }
+        self.len -= 1;
+        Some(index)
+    }
+
+    fn next_back_index(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+        let index = self.tail;
+        if let State::Occupied { prev, .. } = self.list.slots[index as usize].state {
+            self.tail = prev;
+        }
+        self.len -= 1;
+        Some(index)
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let index = self.next_index()?;
+        self.list.data(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let index = self.next_back_index()?;
+        self.list.data(index)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+/// Mutable iterator returned by [`IndexList::iter_mut`].
+pub struct IterMut<'a, T> {
+    slots: *mut Slot<T>,
+    head: u32,
+    tail: u32,
+    len: usize,
+    marker: PhantomData<&'a mut Slot<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `head` is a linked, in-bounds slot of the mutably borrowed
+        // arena, and `len` ensures no slot is visited twice.
+        let slot = unsafe { &mut *self.slots.add(self.head as usize) };
+        let State::Occupied { data, next, .. } = &mut slot.state else {
+            unreachable!("linked slot is occupied");
+        };
+        self.head = *next;
+        self.len -= 1;
+        Some(data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: as in `next`.
+        let slot = unsafe { &mut *self.slots.add(self.tail as usize) };
+        let State::Occupied { data, prev, .. } = &mut slot.state else {
+            unreachable!("linked slot is occupied");
+        };
+        self.tail = *prev;
+        self.len -= 1;
+        Some(data)
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator returned by [`IndexList::into_iter`].
+pub struct IntoIter<T> {
+    list: IndexList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.delete_from_end().ok()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Iterator over element handles returned by [`IndexList::handles`].
+pub struct Handles<'a, T> {
+    iter: Iter<'a, T>,
+}
+
+impl<T> Iterator for Handles<'_, T> {
+    type Item = Handle;
+
+    fn next(&mut self) -> Option<Handle> {
+        let index = self.iter.next_index()?;
+        Some(self.iter.list.handle_unchecked(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Handles<'_, T> {
+    fn next_back(&mut self) -> Option<Handle> {
+        let index = self.iter.next_back_index()?;
+        Some(self.iter.list.handle_unchecked(index))
+    }
+}
+
+impl<T> ExactSizeIterator for Handles<'_, T> {}
+impl<T> FusedIterator for Handles<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+
+    /// Checks `len`, `head`, `tail`, that every `prev` link mirrors a `next`
+    /// link and that the free list holds exactly the vacant slots.
+    fn check<T>(list: &IndexList<T>) {
+        let mut prev = NIL;
+        let mut index = list.head;
+        let mut count = 0;
+        while index != NIL {
+            let (node_prev, next) = list.link_of(index);
+            assert_eq!(node_prev, prev, "prev link of slot {index}");
+            prev = index;
+            index = next;
+            count += 1;
+        }
+        assert_eq!(list.tail, prev);
+        assert_eq!(count, list.len);
+        let mut free = 0;
+        let mut index = list.free;
+        while index != NIL {
+            let State::Vacant { next_free } = list.slots[index as usize].state else {
+                panic!("occupied slot {index} on the free list");
+            };
+            index = next_free;
+            free += 1;
+        }
+        let retired = list
+            .slots
+            .iter()
+            .filter(|slot| {
+                matches!(slot.state, State::Vacant { .. }) && slot.generation == u32::MAX
+            })
+            .count();
+        assert_eq!(count + free + retired, list.slots.len());
+    }
+
+    fn items<T: Clone>(list: &IndexList<T>) -> Vec<T> {
+        check(list);
+        let forward: Vec<T> = list.iter().cloned().collect();
+        let mut backward: Vec<T> = list.iter().rev().cloned().collect();
+        backward.reverse();
+        assert_eq!(forward.len(), backward.len());
+        forward
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: IndexList<i32> = IndexList::new();
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.head_handle(), None);
+        assert_eq!(list.middle(), None);
+    }
+
+    #[test]
+    fn insert_and_delete_at_both_ends() {
+        let mut list = IndexList::new();
+        list.insert_at_end(2);
+        list.insert_at_beginning(1);
+        list.insert_at_end(3);
+        assert_eq!(items(&list), [1, 2, 3]);
+        assert_eq!(list.delete_from_end(), Ok(3));
+        assert_eq!(list.delete_from_beginning(), Ok(1));
+        assert_eq!(items(&list), [2]);
+        assert_eq!(list.delete_from_end(), Ok(2));
+        assert!(list.is_empty());
+        check(&list);
+    }
+
+    #[test]
+    fn handles_go_stale_after_remove() {
+        let mut list = IndexList::new();
+        let a = list.insert_at_end('a');
+        let b = list.insert_at_end('b');
+        assert_eq!(list.remove(a), Ok('a'));
+        assert!(!list.contains_handle(a));
+        assert_eq!(list.get(a), Err(ListError::StaleHandle));
+        assert_eq!(list.get_mut(a), Err(ListError::StaleHandle));
+        assert_eq!(list.remove(a), Err(ListError::StaleHandle));
+        assert_eq!(list.next_handle(a), Err(ListError::StaleHandle));
+        assert_eq!(list.insert_after(a, 'x'), Err(ListError::StaleHandle));
+        assert_eq!(list.move_before(b, a), Err(ListError::StaleHandle));
+        assert_eq!(list.delete_from_beginning(), Ok('b'));
+        assert_eq!(list.get(b), Err(ListError::StaleHandle));
+        check(&list);
+    }
+
+    #[test]
+    fn removed_slots_are_reused_with_a_new_generation() {
+        let mut list = IndexList::new();
+        let a = list.insert_at_end(1);
+        list.insert_at_end(2);
+        list.remove(a).unwrap();
+        let c = list.insert_at_beginning(3);
+        assert_eq!(list.slots.len(), 2, "the freed slot is reused");
+        assert_eq!(c.index, a.index);
+        assert_ne!(c, a);
+        assert_eq!(list.get(a), Err(ListError::StaleHandle));
+        assert_eq!(list.get(c), Ok(&3));
+        assert_eq!(items(&list), [3, 2]);
+        list.clear();
+        let mut reused: Vec<_> = (0..3).map(|i| list.insert_at_end(i).index).collect();
+        reused.sort();
+        assert_eq!(reused, [0, 1, 2]);
+        check(&list);
+    }
+
+    #[test]
+    fn exhausted_slots_are_retired() {
+        let mut list = IndexList::new();
+        let handle = list.insert_at_end(1);
+        list.slots[handle.index as usize].generation = u32::MAX;
+        let handle = list.handle_unchecked(handle.index);
+        list.remove(handle).unwrap();
+        check(&list);
+        let fresh = list.insert_at_end(2);
+        assert_ne!(fresh.index, handle.index);
+        assert_eq!(list.get(handle), Err(ListError::StaleHandle));
+    }
+
+    #[test]
+    fn handle_navigation_and_moves() {
+        let mut list: IndexList<_> = (1..=4).collect();
+        let handles: Vec<_> = list.handles().collect();
+        assert_eq!(list.next_handle(handles[3]), Ok(None));
+        assert_eq!(list.prev_handle(handles[1]), Ok(Some(handles[0])));
+        list.move_after(handles[0], handles[3]).unwrap();
+        assert_eq!(items(&list), [2, 3, 4, 1]);
+        list.move_before(handles[3], handles[1]).unwrap();
+        assert_eq!(items(&list), [4, 2, 3, 1]);
+        list.move_after(handles[2], handles[2]).unwrap();
+        let x = list.insert_before(handles[3], 0).unwrap();
+        list.insert_after(x, 9).unwrap();
+        assert_eq!(items(&list), [0, 9, 4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn append_and_split_off() {
+        let mut list: IndexList<_> = (0..3).collect();
+        let mut other: IndexList<_> = (3..6).collect();
+        let stale = other.head_handle().unwrap();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert!(!other.contains_handle(stale));
+        assert_eq!(items(&list), [0, 1, 2, 3, 4, 5]);
+        let kept = list.head_handle().unwrap();
+        let moved = list.tail_handle().unwrap();
+        let back = list.split_off(4);
+        assert_eq!(items(&list), [0, 1, 2, 3]);
+        assert_eq!(items(&back), [4, 5]);
+        assert_eq!(list.get(kept), Ok(&0));
+        assert_eq!(list.get(moved), Err(ListError::StaleHandle));
+        assert_eq!(items(&list.split_off(4)), []);
+        assert_eq!(items(&list.split_off(0)), [0, 1, 2, 3]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn split_off_past_the_end_panics() {
+        let mut list: IndexList<_> = (0..3).collect();
+        list.split_off(4);
+    }
+
+    #[test]
+    fn reverse_keeps_handles() {
+        for len in 0..4 {
+            let mut list: IndexList<_> = (0..len).collect();
+            let handles: Vec<_> = list.handles().collect();
+            list.reverse();
+            assert_eq!(items(&list), (0..len).rev().collect::<Vec<_>>());
+            for (value, handle) in (0..len).zip(handles) {
+                assert_eq!(list.get(handle), Ok(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn sort_is_stable_and_keeps_handles() {
+        let input = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        let mut list: IndexList<_> = input.into_iter().collect();
+        let handles: Vec<_> = list.handles().collect();
+        list.sort_by_key(|&(key, _)| key);
+        assert_eq!(
+            items(&list),
+            [(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]
+        );
+        assert_eq!(list.get(handles[2]), Ok(&(3, 'c')));
+        for input in [vec![], vec![1], vec![1, 2, 3], vec![3, 2, 1]] {
+            let mut list: IndexList<_> = input.iter().copied().collect();
+            list.sort();
+            let mut expected = input;
+            expected.sort();
+            assert_eq!(items(&list), expected);
+        }
+    }
+
+    #[test]
+    fn merge_prefers_self_on_ties() {
+        let mut left: IndexList<_> = [(1, 'a'), (3, 'a'), (5, 'a')].into_iter().collect();
+        let mut right: IndexList<_> = [(0, 'b'), (1, 'b'), (5, 'b'), (6, 'b')]
+            .into_iter()
+            .collect();
+        let kept = left.head_handle().unwrap();
+        left.merge_by(&mut right, |a, b| a.0.cmp(&b.0));
+        assert!(right.is_empty());
+        assert_eq!(left.get(kept), Ok(&(1, 'a')));
+        let merged: Vec<_> = items(&left)
+            .iter()
+            .map(|(n, side)| format!("{n}{side}"))
+            .collect();
+        assert_eq!(merged, ["0b", "1a", "1b", "3a", "5a", "5b", "6b"]);
+        let mut empty = IndexList::new();
+        empty.merge(&mut (1..3).collect());
+        assert_eq!(items(&empty), [1, 2]);
+    }
+
+    #[test]
+    fn dedup_sorted_releases_duplicates() {
+        let mut list: IndexList<_> = [1, 1, 2, 3, 3, 3].into_iter().collect();
+        let handles: Vec<_> = list.handles().collect();
+        assert_eq!(list.dedup_sorted(), 3);
+        assert_eq!(items(&list), [1, 2, 3]);
+        assert_eq!(list.get(handles[1]), Err(ListError::StaleHandle));
+        assert_eq!(list.get(handles[3]), Ok(&3));
+        assert_eq!(IndexList::<i32>::new().dedup_sorted(), 0);
+    }
+
+    #[test]
+    fn partition_is_stable() {
+        let mut list: IndexList<_> = [5, 2, 8, 1, 4, 7, 6].into_iter().collect();
+        let handles: Vec<_> = list.handles().collect();
+        assert_eq!(list.partition(|n| n % 2 == 0), 4);
+        assert_eq!(items(&list), [2, 8, 4, 6, 5, 1, 7]);
+        assert_eq!(list.get(handles[0]), Ok(&5));
+        assert_eq!(list.partition(|_| true), 7);
+        assert_eq!(list.partition(|_| false), 0);
+        assert_eq!(items(&list), [2, 8, 4, 6, 5, 1, 7]);
+    }
+
+    fn list(items: impl IntoIterator<Item = i32>) -> IndexList<i32> {
+        items.into_iter().collect()
+    }
+
+    #[test]
+    fn nth_from_end_counts_from_the_tail() {
+        let numbers = list(1..=4);
+        assert_eq!(numbers.nth_from_end(0), Some(&4));
+        assert_eq!(numbers.nth_from_end(3), Some(&1));
+        assert_eq!(numbers.nth_from_end(4), None);
+        assert_eq!(list([]).nth_from_end(0), None);
+    }
+
+    #[test]
+    fn split_at_middle_keeps_the_larger_half() {
+        for len in 0..6 {
+            let mut first = list(0..len);
+            let second = first.split_at_middle();
+            let half = (len + 1) / 2;
+            assert_eq!(items(&second), (half..len).collect::<Vec<_>>());
+            assert_eq!(items(&first), (0..half).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn reverse_k_groups_keeps_a_short_trailing_group() {
+        let reversed = |len, k| {
+            let mut numbers = list(1..=len);
+            numbers.reverse_k_groups(k);
+            numbers.insert_at_end(99);
+            let mut numbers = items(&numbers);
+            assert_eq!(numbers.pop(), Some(99));
+            numbers
+        };
+        assert_eq!(reversed(7, 3), [3, 2, 1, 6, 5, 4, 7]);
+        assert_eq!(reversed(6, 3), [3, 2, 1, 6, 5, 4]);
+        assert_eq!(reversed(3, 3), [3, 2, 1]);
+        assert_eq!(reversed(2, 3), [1, 2]);
+        assert_eq!(reversed(3, 1), [1, 2, 3]);
+        assert_eq!(reversed(3, 0), [1, 2, 3]);
+        assert_eq!(reversed(0, 2), []);
+    }
+
+    #[test]
+    fn reverse_k_groups_keeps_handles() {
+        let mut numbers = IndexList::new();
+        let handles: Vec<_> = (0..5).map(|n| numbers.insert_at_end(n)).collect();
+        numbers.reverse_k_groups(2);
+        assert_eq!(items(&numbers), [1, 0, 3, 2, 4]);
+        for (n, &handle) in handles.iter().enumerate() {
+            assert_eq!(numbers.get(handle), Ok(&(n as i32)));
+        }
+        assert_eq!(numbers.head_handle(), Some(handles[1]));
+        assert_eq!(numbers.next_handle(handles[0]), Ok(Some(handles[3])));
+    }
+
+    #[test]
+    fn is_palindrome_leaves_the_list_intact() {
+        for (input, expected) in [
+            (vec![], true),
+            (vec![1], true),
+            (vec![1, 1], true),
+            (vec![1, 2], false),
+            (vec![1, 2, 1], true),
+            (vec![1, 2, 2, 1], true),
+            (vec![1, 2, 3, 1], false),
+        ] {
+            let mut numbers = list(input.clone());
+            assert_eq!(numbers.is_palindrome(), expected, "{input:?}");
+            assert_eq!(items(&numbers), input);
+        }
+    }
+
+    #[test]
+    fn middle_prefers_the_second_of_two() {
+        let list: IndexList<_> = (1..=4).collect();
+        assert_eq!(list.middle(), Some(&3));
+        let list: IndexList<_> = (1..=5).collect();
+        assert_eq!(list.middle(), Some(&3));
+    }
+
+    #[test]
+    fn iter_mut_and_into_iter_from_both_ends() {
+        let mut list: IndexList<_> = (0..4).collect();
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() += 10;
+        *iter.next_back().unwrap() += 10;
+        assert_eq!(iter.len(), 2);
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(13));
+        assert_eq!(into_iter.collect::<Vec<_>>(), [10, 1, 2]);
+    }
+
+    #[test]
+    fn clone_eq_and_hash() {
+        fn hash<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut list: IndexList<_> = (0..3).collect();
+        let handle = list.insert_at_beginning(9);
+        list.remove(handle).unwrap();
+        let copy = list.clone();
+        let rebuilt: IndexList<_> = (0..3).collect();
+        assert_eq!(copy, list);
+        assert_eq!(rebuilt, list);
+        assert_eq!(hash(&rebuilt), hash(&list));
+        assert_ne!(hash(&rebuilt), hash(&IndexList::from_iter(0..2)));
+    }
+}
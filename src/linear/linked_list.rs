@@ -0,0 +1,663 @@
+//! Singly linked list with head and tail tracking.
+//!
+//! This is the Rust counterpart of the Go `LinkedList` in `Linear/LinkedList.md`:
+//! the list owns its nodes through box-like links, keeps a raw pointer to the tail
+//! for O(1) `insert_at_end`, and caches its length. Operations that the Go code
+//! reports through `errors.New("list is empty")` return [`ListError`] instead.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+mod algorithms;
+mod sort;
+
+/// Errors reported by the linked list types in this module tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListError {
+    /// The operation needs at least one element.
+    Empty,
+    /// A handle refers to an element that has since been removed.
+    StaleHandle,
+    /// An index is past the end of the list.
+    IndexOutOfBounds { index: usize, len: usize },
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::Empty => f.write_str("list is empty"),
+            ListError::StaleHandle => f.write_str("handle refers to a removed element"),
+            ListError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} out of bounds for list of length {len}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ListError {}
+
+type Link<T> = Option<NodeBox<T>>;
+
+struct Node<T> {
+    data: T,
+    next: Link<T>,
+}
+
+/// An owning pointer to a node, like `Box<Node<T>>`.
+///
+/// A `Box` asserts unique access whenever it is moved, which would
+/// invalidate the list's raw `tail` pointer each time the chain is relinked.
+/// This pointer makes no such claim, so `tail` stays valid as long as it is
+/// a copy of [`as_ptr`](NodeBox::as_ptr) of the node's owner.
+struct NodeBox<T>(NonNull<Node<T>>);
+
+// SAFETY: `NodeBox` owns its node exactly like a `Box` does.
+unsafe impl<T: Send> Send for NodeBox<T> {}
+unsafe impl<T: Sync> Sync for NodeBox<T> {}
+
+impl<T> NodeBox<T> {
+    fn new(data: T, next: Link<T>) -> Self {
+        let node = Box::into_raw(Box::new(Node { data, next }));
+        // SAFETY: `Box::into_raw` never returns null.
+        NodeBox(unsafe { NonNull::new_unchecked(node) })
+    }
+
+    fn as_ptr(&self) -> NonNull<Node<T>> {
+        self.0
+    }
+
+    fn into_inner(self) -> Node<T> {
+        let node = self.0;
+        std::mem::forget(self);
+        // SAFETY: the pointer came from `Box::into_raw` and ownership is
+        // given up by forgetting `self`.
+        *unsafe { Box::from_raw(node.as_ptr()) }
+    }
+}
+
+impl<T> Deref for NodeBox<T> {
+    type Target = Node<T>;
+
+    fn deref(&self) -> &Node<T> {
+        // SAFETY: the node lives as long as its owner.
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T> DerefMut for NodeBox<T> {
+    fn deref_mut(&mut self) -> &mut Node<T> {
+        // SAFETY: as in `deref`, with exclusivity from `&mut self`.
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl<T> Drop for NodeBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: the pointer came from `Box::into_raw` and is dropped once.
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+/// A singly linked list.
+///
+/// | Operation                 | Cost |
+/// |---------------------------|------|
+/// | `insert_at_beginning`     | O(1) |
+/// | `insert_at_end`           | O(1) |
+/// | `delete_from_beginning`   | O(1) |
+/// | `delete_from_end`         | O(n) |
+/// | `len`                     | O(1) |
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    marker: PhantomData<NodeBox<T>>,
+}
+
+// SAFETY: `tail` only ever points into nodes owned by `head`, so the list has
+// the same ownership semantics as a `Box<Node<T>>` chain.
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+impl<T> LinkedList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_deref().map(|node| &node.data)
+    }
+
+    /// Returns a mutable reference to the first element.
+    pub fn head_mut(&mut self) -> Option<&mut T> {
+        self.head.as_deref_mut().map(|node| &mut node.data)
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        // SAFETY: `tail` points at the last node owned by `head`, and the
+        // returned borrow is tied to `&self`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    /// Returns a mutable reference to the last element.
+    pub fn tail_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `tail`, with exclusivity guaranteed by `&mut self`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Adds an element to the front of the list in O(1).
+    pub fn insert_at_beginning(&mut self, data: T) {
+        let node = NodeBox::new(data, self.head.take());
+        if self.tail.is_none() {
+            self.tail = Some(node.as_ptr());
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Adds an element to the back of the list in O(1).
+    pub fn insert_at_end(&mut self, data: T) {
+        let node = NodeBox::new(data, None);
+        let new_tail = node.as_ptr();
+        match self.tail {
+            // SAFETY: `tail` points at the last node owned by `head`.
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    /// Removes and returns the first element in O(1).
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        let node = self.head.take().ok_or(ListError::Empty)?;
+        let Node { data, next } = node.into_inner();
+        self.head = next;
+        if self.head.is_none() {
+            self.tail = None;
+        }
+        self.len -= 1;
+        Ok(data)
+    }
+
+    /// Removes and returns the last element.
+    ///
+    /// A singly linked list has to walk to the node before the tail, so this is
+    /// O(n); see [`DoublyLinkedList`](super::DoublyLinkedList) when both ends
+    /// need to be cheap.
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        if self.len <= 1 {
+            return self.delete_from_beginning();
+        }
+        let mut current = self.head.as_mut().ok_or(ListError::Empty)?;
+        while current
+            .next
+            .as_ref()
+            .is_some_and(|next| next.next.is_some())
+        {
+            current = current.next.as_mut().unwrap();
+        }
+        let last = current.next.take().ok_or(ListError::Empty)?;
+        self.tail = Some(current.as_ptr());
+        self.len -= 1;
+        Ok(last.into_inner().data)
+    }
+
+    /// Moves every element of `other` to the end of `self` in O(1).
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let Some(other_head) = other.head.take() else {
+            return;
+        };
+        match self.tail {
+            // SAFETY: `tail` points at the last node owned by `head`.
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(other_head) },
+            None => self.head = Some(other_head),
+        }
+        self.tail = other.tail.take();
+        self.len += std::mem::replace(&mut other.len, 0);
+    }
+
+    /// Splits the list in two at `at`, returning everything from index `at`
+    /// onwards. Walks `at` nodes, so this is O(at).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> LinkedList<T> {
+        assert!(at <= self.len, "split index {at} out of bounds");
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        let mut current = self.head.as_mut().expect("list has `at` nodes");
+        for _ in 1..at {
+            current = current.next.as_mut().expect("list has `at` nodes");
+        }
+        let rest = current.next.take();
+        let mut split = LinkedList::new();
+        if rest.is_some() {
+            split.head = rest;
+            split.tail = self.tail;
+            split.len = self.len - at;
+        }
+        self.tail = Some(current.as_ptr());
+        self.len = at;
+        split
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        *self = LinkedList::new();
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns a front-to-back iterator over references.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+
+    /// Returns a front-to-back iterator over mutable references.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+            len: self.len,
+        }
+    }
+
+    /// Points `tail` at the last node again after the chain was relinked.
+    fn reset_tail(&mut self) {
+        let mut tail = None;
+        let mut current = self.head.as_ref();
+        while let Some(node) = current {
+            tail = Some(node.as_ptr());
+            current = node.next.as_ref();
+        }
+        self.tail = tail;
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        LinkedList::new()
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // Unlink iteratively so long lists do not recurse through `Box` drops.
+        let mut link = self.head.take();
+        while let Some(mut node) = link {
+            link = node.next.take();
+        }
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: Hash> Hash for LinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`LinkedList::iter`].
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.len -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            next: self.next,
+            len: self.len,
+        }
+    }
+}
+
+/// Mutable iterator returned by [`LinkedList::iter_mut`].
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.take().map(|node| {
+            self.next = node.next.as_deref_mut();
+            self.len -= 1;
+            &mut node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator returned by [`LinkedList::into_iter`].
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Checks `len` and that `tail` points at the last node of the chain.
+    pub(super) fn check<T>(list: &LinkedList<T>) {
+        let mut last = None;
+        let mut count = 0;
+        let mut current = list.head.as_ref();
+        while let Some(node) = current {
+            last = Some(node.as_ptr());
+            count += 1;
+            current = node.next.as_ref();
+        }
+        assert_eq!(count, list.len);
+        assert_eq!(list.tail, last);
+        assert_eq!(list.is_empty(), count == 0);
+    }
+
+    fn items<T: Clone>(list: &LinkedList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: LinkedList<i32> = LinkedList::new();
+        check(&list);
+        assert_eq!(list.head(), None);
+        assert_eq!(list.tail(), None);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.iter().next(), None);
+        check(&list);
+    }
+
+    #[test]
+    fn one_element() {
+        let mut list = LinkedList::new();
+        list.insert_at_end(7);
+        check(&list);
+        assert_eq!((list.head(), list.tail()), (Some(&7), Some(&7)));
+        assert_eq!(list.delete_from_end(), Ok(7));
+        check(&list);
+
+        list.insert_at_beginning(8);
+        check(&list);
+        *list.tail_mut().unwrap() += 1;
+        assert_eq!(list.head(), Some(&9));
+        assert_eq!(list.delete_from_beginning(), Ok(9));
+        check(&list);
+    }
+
+    #[test]
+    fn inserts_and_deletes_at_both_ends() {
+        let mut list = LinkedList::new();
+        list.insert_at_end(2);
+        list.insert_at_beginning(1);
+        list.insert_at_end(3);
+        list.insert_at_beginning(0);
+        check(&list);
+        assert_eq!(items(&list), [0, 1, 2, 3]);
+
+        assert_eq!(list.delete_from_end(), Ok(3));
+        check(&list);
+        assert_eq!(list.delete_from_beginning(), Ok(0));
+        check(&list);
+        // The tail moved back, so appending must link after the new tail.
+        list.insert_at_end(4);
+        check(&list);
+        assert_eq!(items(&list), [1, 2, 4]);
+
+        while list.delete_from_end().is_ok() {
+            check(&list);
+        }
+        list.insert_at_end(5);
+        check(&list);
+        assert_eq!(items(&list), [5]);
+    }
+
+    #[test]
+    fn head_and_tail_mut() {
+        let mut list: LinkedList<i32> = (1..=3).collect();
+        *list.head_mut().unwrap() *= 10;
+        *list.tail_mut().unwrap() *= 10;
+        for item in &mut list {
+            *item += 1;
+        }
+        assert_eq!(items(&list), [11, 3, 31]);
+        check(&list);
+    }
+
+    #[test]
+    fn drop_frees_every_element() {
+        let counter = DropCounter::new();
+        let mut list = LinkedList::new();
+        for value in 0..5 {
+            list.insert_at_end(counter.item(value));
+        }
+        drop(list.delete_from_end());
+        assert_eq!(counter.dropped(), 1);
+        drop(list);
+        assert_eq!(counter.dropped(), 5);
+    }
+
+    #[test]
+    fn clear_frees_every_element() {
+        let counter = DropCounter::new();
+        let mut list: LinkedList<_> = (0..3).map(|value| counter.item(value)).collect();
+        list.clear();
+        check(&list);
+        assert_eq!(counter.dropped(), 3);
+        list.insert_at_end(counter.item(3));
+        check(&list);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_long_lists() {
+        let list: LinkedList<u32> = (0..1_000_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn into_iter_yields_in_order_and_drops_the_rest() {
+        let list: LinkedList<i32> = (1..=4).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.collect::<Vec<_>>(), [2, 3, 4]);
+
+        let counter = DropCounter::new();
+        let list: LinkedList<_> = (0..4).map(|value| counter.item(value)).collect();
+        let mut iter = list.into_iter();
+        drop(iter.next());
+        assert_eq!(counter.dropped(), 1);
+        drop(iter);
+        assert_eq!(counter.dropped(), 4);
+    }
+
+    #[test]
+    fn iterators_report_exact_length() {
+        let mut list: LinkedList<i32> = (0..3).collect();
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.clone().count(), 2);
+        let mut iter = list.iter_mut();
+        iter.next();
+        assert_eq!(iter.len(), 2);
+    }
+
+    #[test]
+    fn clone_is_deep_and_equal() {
+        let list: LinkedList<String> = ["a", "b", "c"].map(String::from).into_iter().collect();
+        let mut copy = list.clone();
+        check(&copy);
+        assert_eq!(copy, list);
+        assert_eq!(hash(&copy), hash(&list));
+
+        copy.insert_at_end("d".to_owned());
+        assert_ne!(copy, list);
+        *copy.head_mut().unwrap() = "z".to_owned();
+        assert_eq!(list.head().map(String::as_str), Some("a"));
+
+        let empty: LinkedList<String> = LinkedList::new();
+        assert_eq!(empty.clone(), empty);
+        assert_ne!(empty, list);
+    }
+
+    #[test]
+    fn equality_needs_same_length() {
+        let short: LinkedList<i32> = (1..=2).collect();
+        let long: LinkedList<i32> = (1..=3).collect();
+        assert_ne!(short, long);
+        assert_ne!(hash(&short), hash(&long));
+        assert!(long.contains(&3) && !short.contains(&3));
+    }
+}
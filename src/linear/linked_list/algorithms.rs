@@ -0,0 +1,249 @@
+//! Multi-pointer traversals and in-place rearrangements.
+//!
+//! These generalise `FindMiddle` from `Linear/LinkedList.md`. Everything here
+//! walks or relinks the existing nodes: no element is cloned or moved to a
+//! temporary buffer, so the extra space is O(1) throughout.
+
+use super::{Link, LinkedList, NodeBox};
+
+impl<T> LinkedList<T> {
+    /// Returns the middle element using a slow and a fast pointer.
+    ///
+    /// Like the Go `FindMiddle`, an even-length list yields the second of the
+    /// two middle elements. O(n) time, O(1) space.
+    pub fn middle(&self) -> Option<&T> {
+        let mut slow = self.head.as_deref()?;
+        let mut fast = self.head.as_deref();
+        while let Some(next) = fast.and_then(|node| node.next.as_deref()) {
+            slow = slow.next.as_deref()?;
+            fast = next.next.as_deref();
+        }
+        Some(&slow.data)
+    }
+
+    /// Returns the `n`-th element counted from the end, where `n == 0` is the
+    /// tail.
+    ///
+    /// A leading pointer is sent `n` nodes ahead, then both advance until the
+    /// leader falls off the end. O(n) time, O(1) space.
+    pub fn nth_from_end(&self, n: usize) -> Option<&T> {
+        let mut lead = self.head.as_deref();
+        for _ in 0..n {
+            lead = lead?.next.as_deref();
+        }
+        let mut lead = lead?;
+        let mut trail = self.head.as_deref()?;
+        while let Some(next) = lead.next.as_deref() {
+            lead = next;
+            trail = trail.next.as_deref()?;
+        }
+        Some(&trail.data)
+    }
+
+    /// Splits the list at its middle and returns the second half.
+    ///
+    /// `self` keeps the first `⌈n/2⌉` elements, which is the split merge sort
+    /// needs. O(n) time, O(1) space.
+    pub fn split_at_middle(&mut self) -> LinkedList<T> {
+        self.split_off(self.len.div_ceil(2))
+    }
+
+    /// Reverses the list in place by flipping every `next` link.
+    ///
+    /// O(n) time, O(1) space.
+    pub fn reverse(&mut self) {
+        let mut link = self.head.take();
+        // The old head becomes the new tail.
+        self.tail = link.as_ref().map(NodeBox::as_ptr);
+        while let Some(mut node) = link {
+            link = node.next.take();
+            node.next = self.head.take();
+            self.head = Some(node);
+        }
+    }
+
+    /// Reverses every consecutive group of `k` elements in place.
+    ///
+    /// A trailing group shorter than `k` keeps its order; `k < 2` leaves the
+    /// list untouched. O(n) time, O(1) space.
+    pub fn reverse_k_groups(&mut self, k: usize) {
+        if k < 2 || self.len < k {
+            return;
+        }
+        let mut rest = self.head.take();
+        let mut remaining = self.len;
+        let mut slot = &mut self.head;
+        while remaining >= k {
+            let mut group = None;
+            for _ in 0..k {
+                let mut node = rest.take().expect("group has k nodes");
+                rest = node.next.take();
+                node.next = group;
+                group = Some(node);
+            }
+            *slot = group;
+            for _ in 0..k {
+                slot = &mut slot.as_mut().expect("group has k nodes").next;
+            }
+            remaining -= k;
+        }
+        *slot = rest;
+        self.reset_tail();
+    }
+
+    /// Returns `true` if the list reads the same forwards and backwards.
+    ///
+    /// The second half is detached and reversed for the comparison, then
+    /// restored, which is why this takes `&mut self`: the list is unchanged
+    /// when the call returns. O(n) time, O(1) space.
+    pub fn is_palindrome(&mut self) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut second = self.split_at_middle();
+        second.reverse();
+        let result = second.iter().zip(self.iter()).all(|(a, b)| a == b);
+        second.reverse();
+        self.append(&mut second);
+        result
+    }
+
+    /// Reorders the list so that every element matching `pred` comes before
+    /// every element that does not, keeping the relative order within both
+    /// groups. Returns the number of matching elements.
+    ///
+    /// Nodes are relinked into two chains and joined. O(n) time, O(1) space.
+    pub fn partition<F>(&mut self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut rest = self.head.take();
+        let mut rejected: Link<T> = None;
+        let mut matched_count = 0;
+        let mut matched_slot = &mut self.head;
+        let mut rejected_slot = &mut rejected;
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+            if pred(&node.data) {
+                matched_count += 1;
+                matched_slot = &mut matched_slot.insert(node).next;
+            } else {
+                rejected_slot = &mut rejected_slot.insert(node).next;
+            }
+        }
+        *matched_slot = rejected;
+        self.reset_tail();
+        matched_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::check;
+    use super::*;
+
+    fn list(items: impl IntoIterator<Item = i32>) -> LinkedList<i32> {
+        items.into_iter().collect()
+    }
+
+    /// Checks the invariants, then that `tail` is usable by appending to it.
+    fn items(mut list: LinkedList<i32>) -> Vec<i32> {
+        check(&list);
+        list.insert_at_end(99);
+        check(&list);
+        let mut items: Vec<_> = list.into_iter().collect();
+        assert_eq!(items.pop(), Some(99));
+        items
+    }
+
+    #[test]
+    fn middle_prefers_the_second_of_two() {
+        assert_eq!(list([]).middle(), None);
+        assert_eq!(list([1]).middle(), Some(&1));
+        assert_eq!(list([1, 2]).middle(), Some(&2));
+        assert_eq!(list([1, 2, 3]).middle(), Some(&2));
+        assert_eq!(list(1..=6).middle(), Some(&4));
+    }
+
+    #[test]
+    fn nth_from_end_counts_from_the_tail() {
+        let numbers = list(1..=4);
+        assert_eq!(numbers.nth_from_end(0), Some(&4));
+        assert_eq!(numbers.nth_from_end(3), Some(&1));
+        assert_eq!(numbers.nth_from_end(4), None);
+        assert_eq!(list([]).nth_from_end(0), None);
+    }
+
+    #[test]
+    fn split_at_middle_keeps_the_larger_half() {
+        for len in 0..6 {
+            let mut first = list(0..len);
+            let second = first.split_at_middle();
+            let half = (len + 1) / 2;
+            assert_eq!(items(second), (half..len).collect::<Vec<_>>());
+            assert_eq!(items(first), (0..half).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn reverse_moves_the_tail() {
+        for len in 0..5 {
+            let mut numbers = list(0..len);
+            numbers.reverse();
+            assert_eq!(items(numbers), (0..len).rev().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn reverse_k_groups_keeps_a_short_trailing_group() {
+        let reversed = |len, k| {
+            let mut numbers = list(1..=len);
+            numbers.reverse_k_groups(k);
+            items(numbers)
+        };
+        assert_eq!(reversed(7, 3), [3, 2, 1, 6, 5, 4, 7]);
+        assert_eq!(reversed(6, 3), [3, 2, 1, 6, 5, 4]);
+        assert_eq!(reversed(3, 3), [3, 2, 1]);
+        assert_eq!(reversed(2, 3), [1, 2]);
+        assert_eq!(reversed(3, 1), [1, 2, 3]);
+        assert_eq!(reversed(3, 0), [1, 2, 3]);
+        assert_eq!(reversed(0, 2), []);
+    }
+
+    #[test]
+    fn is_palindrome_leaves_the_list_intact() {
+        for (input, expected) in [
+            (vec![], true),
+            (vec![1], true),
+            (vec![1, 1], true),
+            (vec![1, 2], false),
+            (vec![1, 2, 1], true),
+            (vec![1, 2, 2, 1], true),
+            (vec![1, 2, 3, 1], false),
+        ] {
+            let mut numbers = list(input.clone());
+            assert_eq!(numbers.is_palindrome(), expected, "{input:?}");
+            assert_eq!(items(numbers), input);
+        }
+    }
+
+    #[test]
+    fn partition_is_stable() {
+        let mut numbers = list([5, 2, 8, 1, 4, 7, 6]);
+        assert_eq!(numbers.partition(|n| n % 2 == 0), 4);
+        assert_eq!(items(numbers), [2, 8, 4, 6, 5, 1, 7]);
+    }
+
+    #[test]
+    fn partition_with_one_group_empty() {
+        let mut numbers = list(1..=3);
+        assert_eq!(numbers.partition(|_| true), 3);
+        assert_eq!(items(numbers), [1, 2, 3]);
+        let mut numbers = list(1..=3);
+        assert_eq!(numbers.partition(|_| false), 0);
+        assert_eq!(items(numbers), [1, 2, 3]);
+        let mut empty = list([]);
+        assert_eq!(empty.partition(|_| true), 0);
+        assert_eq!(items(empty), []);
+    }
+}
@@ -0,0 +1,250 @@
+//! Ordering operations: stable merge sort, merging and deduplication.
+//!
+//! All of them relink the existing nodes instead of copying elements into a
+//! `Vec`, so sorting or merging never reallocates.
+
+use std::cmp::Ordering;
+
+use super::{Link, LinkedList};
+
+/// Enough bins for any list that fits in memory: bin `i` holds a sorted run
+/// of `2^i` nodes.
+const BINS: usize = usize::BITS as usize;
+
+impl<T> LinkedList<T> {
+    /// Sorts the list in ascending order.
+    ///
+    /// See [`sort_by`](LinkedList::sort_by) for the algorithm.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Sorts the list by the key `f` extracts from each element.
+    ///
+    /// The key is recomputed on every comparison.
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sorts the list with the comparator `cmp`.
+    ///
+    /// This is a stable bottom-up merge sort. Nodes are detached one at a time
+    /// and carried through a fixed array of bins, where bin `i` holds a sorted
+    /// run of `2^i` nodes; equal runs are merged like a binary counter
+    /// increment. O(n log n) comparisons, O(1) extra space (the bin array has
+    /// a fixed size).
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.len < 2 {
+            return;
+        }
+        let mut bins: [Link<T>; BINS] = std::array::from_fn(|_| None);
+        let mut rest = self.head.take();
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+            let mut carry = Some(node);
+            let mut i = 0;
+            // Higher bins hold older runs, so they go on the left to keep
+            // equal elements in their original order.
+            while let Some(run) = bins[i].take() {
+                carry = merge_chains(Some(run), carry, &mut cmp);
+                i += 1;
+            }
+            bins[i] = carry;
+        }
+        let mut sorted = None;
+        for run in bins {
+            sorted = merge_chains(run, sorted, &mut cmp);
+        }
+        self.head = sorted;
+        self.reset_tail();
+    }
+
+    /// Merges the sorted list `other` into this sorted list, leaving `other`
+    /// empty.
+    ///
+    /// On ties elements of `self` come first. O(n + m) comparisons, no
+    /// allocation.
+    pub fn merge(&mut self, other: &mut LinkedList<T>)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    /// Like [`merge`](LinkedList::merge), with a custom comparator that both
+    /// lists must already be sorted by.
+    pub fn merge_by<F>(&mut self, other: &mut LinkedList<T>, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut other = std::mem::take(other);
+        if other.is_empty() {
+            return;
+        }
+        self.head = merge_chains(self.head.take(), other.head.take(), &mut cmp);
+        self.len += std::mem::replace(&mut other.len, 0);
+        self.reset_tail();
+    }
+
+    /// Removes consecutive duplicate elements, which on a sorted list removes
+    /// every duplicate. Returns the number of elements removed.
+    ///
+    /// O(n) comparisons, no allocation.
+    pub fn dedup_sorted(&mut self) -> usize
+    where
+        T: PartialEq,
+    {
+        let mut removed = 0;
+        let mut current = self.head.as_deref_mut();
+        while let Some(node) = current {
+            while let Some(mut next) = node.next.take_if(|next| next.data == node.data) {
+                node.next = next.next.take();
+                removed += 1;
+            }
+            current = node.next.as_deref_mut();
+        }
+        self.len -= removed;
+        self.reset_tail();
+        removed
+    }
+}
+
+/// Merges two sorted chains, preferring `left` on ties.
+fn merge_chains<T, F>(mut left: Link<T>, mut right: Link<T>, cmp: &mut F) -> Link<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut head = None;
+    let mut slot = &mut head;
+    while let (Some(l), Some(r)) = (&left, &right) {
+        let source = if cmp(&r.data, &l.data) == Ordering::Less {
+            &mut right
+        } else {
+            &mut left
+        };
+        let mut node = source.take().expect("source chain is non-empty");
+        *source = node.next.take();
+        slot = &mut slot.insert(node).next;
+    }
+    *slot = left.or(right);
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::check;
+    use super::*;
+
+    /// Checks the invariants, then that `tail` is usable by appending to it.
+    fn items<T: Clone + Default>(mut list: LinkedList<T>) -> Vec<T> {
+        check(&list);
+        list.insert_at_end(T::default());
+        check(&list);
+        let mut items: Vec<_> = list.into_iter().collect();
+        items.pop();
+        items
+    }
+
+    /// Deterministic pseudo-random numbers below `bound`.
+    fn numbers(len: usize, bound: u32) -> Vec<u32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state % bound
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_small_and_presorted_inputs() {
+        for input in [
+            vec![],
+            vec![1],
+            vec![1, 2, 3, 4],
+            vec![4, 3, 2, 1],
+            vec![2, 2, 2],
+        ] {
+            let mut list: LinkedList<u32> = input.iter().copied().collect();
+            list.sort();
+            let mut expected = input;
+            expected.sort();
+            assert_eq!(items(list), expected);
+        }
+    }
+
+    #[test]
+    fn sort_matches_vec_sort() {
+        for len in [5, 16, 17, 100, 1000] {
+            let input = numbers(len, 50);
+            let mut list: LinkedList<_> = input.iter().copied().collect();
+            list.sort();
+            let mut expected = input;
+            expected.sort();
+            assert_eq!(items(list), expected);
+        }
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let input: Vec<(u32, usize)> = numbers(300, 8).into_iter().zip(0..).collect();
+        let mut list: LinkedList<_> = input.iter().copied().collect();
+        list.sort_by_key(|&(key, _)| key);
+        let mut expected = input;
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(items(list), expected);
+    }
+
+    #[test]
+    fn merge_prefers_self_on_ties() {
+        let mut left: LinkedList<_> = [(1, 'a'), (3, 'a'), (5, 'a')].into_iter().collect();
+        let mut right: LinkedList<_> = [(1, 'b'), (2, 'b'), (5, 'b'), (6, 'b')]
+            .into_iter()
+            .collect();
+        left.merge_by(&mut right, |a, b| a.0.cmp(&b.0));
+        check(&right);
+        assert!(right.is_empty());
+        let merged: Vec<_> = items(left)
+            .into_iter()
+            .map(|(n, side)| format!("{n}{side}"))
+            .collect();
+        assert_eq!(merged, ["1a", "1b", "2b", "3a", "5a", "5b", "6b"]);
+    }
+
+    #[test]
+    fn merge_with_empty_sides() {
+        let mut left: LinkedList<i32> = LinkedList::new();
+        let mut right: LinkedList<_> = [1, 2].into_iter().collect();
+        left.merge(&mut right);
+        assert_eq!(items(left.clone()), [1, 2]);
+        left.merge(&mut right);
+        assert_eq!(items(left), [1, 2]);
+    }
+
+    #[test]
+    fn dedup_sorted_counts_removals() {
+        for (input, expected) in [
+            (vec![], vec![]),
+            (vec![1], vec![1]),
+            (vec![1, 1, 1], vec![1]),
+            (vec![1, 2, 3], vec![1, 2, 3]),
+            (vec![1, 1, 2, 3, 3], vec![1, 2, 3]),
+        ] {
+            let mut list: LinkedList<_> = input.iter().copied().collect();
+            assert_eq!(list.dedup_sorted(), input.len() - expected.len());
+            assert_eq!(items(list), expected);
+        }
+    }
+}
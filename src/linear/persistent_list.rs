@@ -0,0 +1,305 @@
+//! Persistent (immutable, structurally shared) singly linked list.
+//!
+//! `Linear/Queue.md` recommends immutable, persistent structures in the
+//! Clojure style, while the Go `LinkedList` is purely mutable. A
+//! [`PersistentList`] is never modified in place: [`cons`](PersistentList::cons)
+//! returns a new list that shares every existing node with the old one, and
+//! [`tail`](PersistentList::tail) returns a list that shares all but the first
+//! node. Cloning is O(1), so keeping old versions around is free.
+//!
+//! Nodes are reference-counted with `Arc`, so lists (and the versions derived
+//! from them) can be sent to and shared between threads.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::sync::Arc;
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    data: T,
+    next: Link<T>,
+    /// Length of the list starting at this node.
+    len: usize,
+}
+
+/// An immutable cons list with shared suffixes.
+///
+/// Unlike [`LinkedList::tail`](super::LinkedList::tail), which returns the
+/// last element, `tail` here follows the Lisp convention and returns the list
+/// without its first element.
+///
+/// | Operation             | Cost |
+/// |-----------------------|------|
+/// | `cons`                | O(1) |
+/// | `head`, `tail`        | O(1) |
+/// | `len`, `clone`        | O(1) |
+pub struct PersistentList<T> {
+    head: Link<T>,
+}
+
+impl<T> PersistentList<T> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        PersistentList { head: None }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a new list with `data` in front of this one.
+    ///
+    /// `self` is left untouched and shares all of its nodes with the result.
+    pub fn cons(&self, data: T) -> Self {
+        PersistentList {
+            head: Some(Arc::new(Node {
+                data,
+                next: self.head.clone(),
+                len: self.len() + 1,
+            })),
+        }
+    }
+
+    /// Returns the first element.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_deref().map(|node| &node.data)
+    }
+
+    /// Returns the list without its first element, or `None` if empty.
+    pub fn tail(&self) -> Option<Self> {
+        self.head.as_deref().map(|node| PersistentList {
+            head: node.next.clone(),
+        })
+    }
+
+    /// Splits the list into its first element and the rest.
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.head.as_deref().map(|node| {
+            let rest = PersistentList {
+                head: node.next.clone(),
+            };
+            (&node.data, rest)
+        })
+    }
+
+    /// Returns `true` if both lists are the very same nodes, not merely
+    /// equal elements.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns a new list with the elements in reverse order. O(n).
+    pub fn reverse(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.iter()
+            .fold(PersistentList::new(), |acc, item| acc.cons(item.clone()))
+    }
+
+    /// Returns a front-to-back iterator over references.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    /// Shares the whole list; O(1).
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        PersistentList::new()
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        // Free uniquely owned nodes one by one instead of letting `Arc` drops
+        // recurse down the chain. `Arc::into_inner` hands the node to exactly
+        // one owner even when other threads drop their copies concurrently;
+        // the walk stops at the first node that someone else still shares.
+        let mut link = self.head.take();
+        while let Some(node) = link {
+            link = Arc::into_inner(node).and_then(|mut node| node.next.take());
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T: Hash> Hash for PersistentList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PersistentList<T> {
+    /// Builds a list with the elements in iteration order.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(PersistentList::new(), |acc, item| acc.cons(item))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Borrowing iterator returned by [`PersistentList::iter`].
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { next: self.next }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// A `Send` element that counts its drops.
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn cons_and_tail_share_nodes() {
+        let list: PersistentList<_> = (1..=3).collect();
+        let longer = list.cons(0);
+        assert_eq!(longer.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(longer.tail().unwrap().ptr_eq(&list));
+        let (head, rest) = longer.uncons().unwrap();
+        assert_eq!((head, rest.len()), (&0, 3));
+        assert!(rest.ptr_eq(&list));
+        assert!(!list.reverse().ptr_eq(&list));
+        assert_eq!(
+            list.reverse().iter().copied().collect::<Vec<_>>(),
+            [3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn empty_list() {
+        let list: PersistentList<i32> = PersistentList::new();
+        assert_eq!((list.len(), list.head()), (0, None));
+        assert!(list.tail().is_none());
+        assert!(list.uncons().is_none());
+        assert!(list.ptr_eq(&PersistentList::new()));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_a_million_elements_without_recursing() {
+        let list: PersistentList<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        drop(list);
+    }
+
+    #[test]
+    fn drop_stops_at_a_shared_suffix() {
+        let counter = DropCounter::new();
+        let shared: PersistentList<_> = (0..5).map(|i| counter.item(i)).collect();
+        let branch = shared.cons(counter.item(10)).cons(counter.item(11));
+        drop(branch);
+        assert_eq!(counter.dropped(), 2);
+        assert_eq!(shared.len(), 5);
+        assert_eq!(shared.head().map(|item| item.value), Some(0));
+        let rest = shared.tail().unwrap();
+        drop(shared);
+        assert_eq!(counter.dropped(), 3);
+        drop(rest);
+        assert_eq!(counter.dropped(), 7);
+    }
+
+    #[test]
+    fn concurrent_drops_free_every_node_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let len = if cfg!(miri) { 50 } else { 100_000 };
+        let shared: PersistentList<_> = (0..len).map(|_| Tracked(Arc::clone(&drops))).collect();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let branch = shared.cons(Tracked(Arc::clone(&drops)));
+                thread::spawn(move || drop(branch))
+            })
+            .collect();
+        drop(shared);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), len + 4);
+    }
+}
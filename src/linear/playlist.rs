@@ -0,0 +1,1066 @@
+//! Music playlist engine.
+//!
+//! `Linear/LinkedList.md` ends with a Go `Playlist` built on a doubly linked
+//! list of `Song`s that can only add songs and step forward or back, failing
+//! at either end. [`Playlist`] keeps the same shape on top of an
+//! [`IndexList`], and adds repeat modes, shuffle with a previous-song history,
+//! and reordering. Songs are addressed by [`SongId`] handles, so moving or
+//! removing songs never invalidates the song that is currently playing.
+//!
+//! Playlists can be read from and written to extended M3U/M3U8 and PLS files.
+//! Directives and keys the parsers do not understand are kept and written
+//! back, so a round-trip through this crate does not lose player-specific
+//! data.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use super::index_list::{Handle, IndexList};
+use crate::rng::SplitMix64;
+
+mod m3u;
+mod pls;
+
+/// Stable reference to a song in a [`Playlist`].
+///
+/// An id stays valid while its song is in the playlist, wherever the song is
+/// moved to; once the song is removed the id is rejected with
+/// [`PlaylistError::UnknownSong`].
+pub type SongId = Handle;
+
+/// A song: the Go `Song` without its link fields, plus what playlist files
+/// record about it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Song {
+    pub name: String,
+    pub artist: String,
+    /// Path or URL of the media file. Required when exporting.
+    pub location: Option<String>,
+    /// Running time, if known.
+    pub duration: Option<Duration>,
+    /// `#EXTINF` attributes between the duration and the title, such as
+    /// `tvg-id="..."`, kept verbatim.
+    pub attributes: Option<String>,
+    /// The title exactly as the playlist file wrote it. It is written back
+    /// as long as it still reads as `Artist - Name` for the current artist
+    /// and name, so a title whose artist or name contains ` - ` survives a
+    /// round-trip.
+    pub title: Option<String>,
+    /// Lines attached to this entry that the parsers did not understand:
+    /// M3U directives (starting with `#`) and PLS keys (as `Key=value`,
+    /// without the entry number). Each format writes back only its own.
+    pub directives: Vec<String>,
+    /// How many of the last `directives` an M3U file placed between
+    /// `#EXTINF` and the location; they are written back there.
+    pub directives_after_info: usize,
+}
+
+impl Song {
+    /// Creates a song, rejecting a blank name or artist like `AddSong`.
+    pub fn new(name: impl Into<String>, artist: impl Into<String>) -> Result<Self, SongError> {
+        let song = Song {
+            name: name.into(),
+            artist: artist.into(),
+            ..Song::default()
+        };
+        song.validate()?;
+        Ok(song)
+    }
+
+    /// Checks the rules [`Playlist::add`] enforces on every song.
+    pub fn validate(&self) -> Result<(), SongError> {
+        if self.name.trim().is_empty() {
+            return Err(SongError::EmptyName);
+        }
+        if self.artist.trim().is_empty() {
+            return Err(SongError::EmptyArtist);
+        }
+        Ok(())
+    }
+
+    /// Sets the location of the media file.
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Sets the running time.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// The title as playlist files write it: the title read from the file
+    /// if it still matches, otherwise `Artist - Name`, or just the name when
+    /// the artist is unknown.
+    fn title(&self) -> String {
+        match &self.title {
+            Some(title) if self.matches_title(title) => title.clone(),
+            _ if self.artist.is_empty() => self.name.clone(),
+            _ => format!("{} - {}", self.artist, self.name),
+        }
+    }
+
+    /// Returns `true` if `title` splits into this artist and name at one of
+    /// its ` - ` separators.
+    fn matches_title(&self, title: &str) -> bool {
+        if self.artist.is_empty() {
+            return title.trim() == self.name;
+        }
+        title.match_indices(" - ").any(|(at, separator)| {
+            title[..at].trim() == self.artist && title[at + separator.len()..].trim() == self.name
+        })
+    }
+
+    /// Builds a song from a playlist file title, splitting it into artist and
+    /// name at the first ` - ` and keeping the title as written.
+    fn from_title(title: &str) -> Self {
+        let (artist, name) = title.split_once(" - ").unwrap_or(("", title));
+        Song {
+            name: name.trim().to_owned(),
+            artist: artist.trim().to_owned(),
+            title: Some(title.to_owned()),
+            ..Song::default()
+        }
+    }
+
+    /// Returns `true` if the file title adds nothing to the location: the
+    /// file had none, and the name is still the one taken from the location.
+    fn title_is_implied(&self, location: &str) -> bool {
+        self.title.is_none()
+            && self.artist.is_empty()
+            && self.name == Song::from_location(location).name
+    }
+
+    /// Builds a song from a bare location, named after the file.
+    fn from_location(location: &str) -> Self {
+        let file = location.rsplit(['/', '\\']).next().unwrap_or(location);
+        let name = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+        Song {
+            name: name.to_owned(),
+            ..Song::default()
+        }
+    }
+}
+
+impl fmt::Display for Song {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} by {}", self.name, self.artist)
+    }
+}
+
+/// What happens when playback runs past the last song.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RepeatMode {
+    /// Stop at either end of the playlist, like the Go version.
+    #[default]
+    Off,
+    /// Replay the current song when it finishes; skipping still moves on.
+    One,
+    /// Wrap around to the other end of the playlist.
+    All,
+}
+
+/// How [`Playlist::add`] treats a song whose artist and name are already in
+/// the playlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DuplicatePolicy {
+    /// Add it again, like the Go version.
+    #[default]
+    Allow,
+    /// Refuse it with [`SongError::Duplicate`].
+    Reject,
+    /// Keep the existing song but move it to where the new one would have
+    /// gone: the end for `add`, after the current song for `insert_next`.
+    MoveToEnd,
+}
+
+/// The rule a rejected song broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SongError {
+    /// The name is empty or only whitespace.
+    EmptyName,
+    /// The artist is empty or only whitespace.
+    EmptyArtist,
+    /// The playlist already has this song and rejects duplicates.
+    Duplicate(SongId),
+}
+
+impl fmt::Display for SongError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SongError::EmptyName => "song name is empty",
+            SongError::EmptyArtist => "song artist is empty",
+            SongError::Duplicate(_) => "song is already in the playlist",
+        })
+    }
+}
+
+impl Error for SongError {}
+
+/// Errors returned by [`Playlist`] operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlaylistError {
+    /// A song was refused; the [`SongError`] says why.
+    InvalidSong(SongError),
+    /// The playlist has no songs.
+    Empty,
+    /// Playback has not been started, or the playlist ran out of songs.
+    NotPlaying,
+    /// There is no next song and repeat is off.
+    EndOfPlaylist,
+    /// There is no previous song.
+    BeginningOfPlaylist,
+    /// The id does not refer to a song of this playlist.
+    UnknownSong,
+    /// A song without a location cannot be exported.
+    MissingLocation,
+}
+
+impl fmt::Display for PlaylistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PlaylistError::InvalidSong(error) => return write!(f, "invalid song data: {error}"),
+            PlaylistError::Empty => "playlist is empty",
+            PlaylistError::NotPlaying => "nothing is playing",
+            PlaylistError::EndOfPlaylist => "reached end of playlist",
+            PlaylistError::BeginningOfPlaylist => "reached beginning of playlist",
+            PlaylistError::UnknownSong => "song is not in the playlist",
+            PlaylistError::MissingLocation => "song has no location",
+        })
+    }
+}
+
+impl Error for PlaylistError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlaylistError::InvalidSong(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SongError> for PlaylistError {
+    fn from(error: SongError) -> Self {
+        PlaylistError::InvalidSong(error)
+    }
+}
+
+/// A malformed line in an M3U or PLS file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+/// What is wrong with the line a [`ParseError`] points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// A duration or length is not a number of seconds.
+    InvalidDuration,
+    /// An `#EXTINF` directive has no comma before the title.
+    MissingTitle,
+    /// An `#EXTINF` directive is not followed by a location.
+    DanglingInfo,
+    /// A PLS file does not start with `[playlist]`.
+    MissingHeader,
+    /// A PLS line is neither a section header nor `key=value`.
+    MalformedLine,
+    /// A PLS entry number is zero or too large.
+    InvalidIndex,
+    /// A PLS key is given twice for the same entry.
+    DuplicateKey,
+    /// A PLS entry has a title or length but no `File` key.
+    MissingFile,
+    /// `NumberOfEntries` does not match the entries in the file.
+    EntryCountMismatch,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseErrorKind::InvalidDuration => "invalid duration",
+            ParseErrorKind::MissingTitle => "#EXTINF without a title",
+            ParseErrorKind::DanglingInfo => "#EXTINF not followed by a location",
+            ParseErrorKind::MissingHeader => "missing [playlist] header",
+            ParseErrorKind::MalformedLine => "expected key=value",
+            ParseErrorKind::InvalidIndex => "invalid entry number",
+            ParseErrorKind::DuplicateKey => "duplicate key",
+            ParseErrorKind::MissingFile => "entry has no File key",
+            ParseErrorKind::EntryCountMismatch => "NumberOfEntries does not match the entries",
+        })
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a duration in (possibly fractional) seconds; negative values mean
+/// "unknown", as in both file formats.
+fn parse_duration(text: &str) -> Option<Option<Duration>> {
+    let seconds: f64 = text.trim().parse().ok()?;
+    if seconds < 0.0 {
+        return Some(None);
+    }
+    Duration::try_from_secs_f64(seconds).ok().map(Some)
+}
+
+/// Formats a duration the way [`parse_duration`] reads it, using `-1` for
+/// unknown.
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        None => String::from("-1"),
+        Some(duration) if duration.subsec_nanos() == 0 => duration.as_secs().to_string(),
+        Some(duration) => duration.as_secs_f64().to_string(),
+    }
+}
+
+/// Shuffle order, drawn lazily from a bag of songs not yet played.
+///
+/// `history` is the order songs were played in and `position` the index of
+/// the current song in it, so going back and then forward again replays the
+/// same songs instead of drawing new ones. Every song appears in it at most
+/// once, at its latest play, so the history never outgrows the playlist even
+/// when rounds repeat forever.
+#[derive(Debug, Clone, Default)]
+struct Shuffle {
+    unplayed: Vec<SongId>,
+    history: Vec<SongId>,
+    position: usize,
+}
+
+impl Shuffle {
+    /// Drops every trace of `id`, keeping `position` on the same entry or,
+    /// if that entry was `id`, on the one that followed it.
+    fn forget(&mut self, id: SongId) {
+        self.unplayed.retain(|&other| other != id);
+        let before = self.history[..self.position.min(self.history.len())]
+            .iter()
+            .filter(|&&other| other == id)
+            .count();
+        self.history.retain(|&other| other != id);
+        self.position -= before;
+    }
+
+    /// Makes `id` the current entry of the history, marking it as played.
+    fn play(&mut self, id: SongId) {
+        self.unplayed.retain(|&other| other != id);
+        let at = if self.history.is_empty() {
+            0
+        } else {
+            self.position + 1
+        };
+        self.position = self.place(at, id);
+    }
+
+    /// Inserts `id` into the history at `at` after removing its older entry,
+    /// and returns where it ended up. `position` keeps pointing at the same
+    /// entry unless that entry was `id`.
+    fn place(&mut self, at: usize, id: SongId) -> usize {
+        let mut index = 0;
+        let (mut before_at, mut before_position) = (0, 0);
+        self.history.retain(|&other| {
+            let keep = other != id;
+            if !keep {
+                before_at += usize::from(index < at);
+                before_position += usize::from(index < self.position);
+            }
+            index += 1;
+            keep
+        });
+        let at = at - before_at;
+        self.position -= before_position;
+        self.history.insert(at, id);
+        if at <= self.position {
+            self.position += 1;
+        }
+        at
+    }
+}
+
+/// A named, ordered list of songs with a playback position.
+///
+/// | Operation                     | Cost                     |
+/// |-------------------------------|--------------------------|
+/// | `add_song`, `add`             | O(1) amortized           |
+/// | `next_song`, `previous_song`  | O(1)                     |
+/// | `move_after`, `move_before`   | O(1)                     |
+/// | `insert_next`, `remove_song`  | O(1), O(n) when shuffled |
+/// | `play`                        | O(1), O(n) when shuffled |
+/// | `find`, `remove_by_key`       | O(1) expected            |
+/// | `set_shuffle(true)`           | O(n)                     |
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    name: String,
+    songs: IndexList<Song>,
+    now_playing: Option<SongId>,
+    repeat: RepeatMode,
+    shuffle: Option<Shuffle>,
+    rng: SplitMix64,
+    duplicates: DuplicatePolicy,
+    /// Ids of every song, by artist and then by name.
+    index: HashMap<String, HashMap<String, Vec<SongId>>>,
+    /// Playlist-level lines the parsers did not understand, written back
+    /// after the songs.
+    directives: Vec<String>,
+    /// Whether the name was given rather than defaulted, so that exports
+    /// only record names that exist.
+    named: bool,
+}
+
+impl Playlist {
+    /// Creates an empty playlist. An empty name becomes `"New Playlist"`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Playlist::with_rng(name.into(), SplitMix64::from_entropy())
+    }
+
+    /// Creates an empty playlist whose shuffle order is determined by `seed`.
+    pub fn with_seed(name: impl Into<String>, seed: u64) -> Self {
+        Playlist::with_rng(name.into(), SplitMix64::new(seed))
+    }
+
+    fn with_rng(mut name: String, rng: SplitMix64) -> Self {
+        let named = !name.is_empty();
+        if !named {
+            name = String::from("New Playlist");
+        }
+        Playlist {
+            name,
+            songs: IndexList::new(),
+            now_playing: None,
+            repeat: RepeatMode::Off,
+            shuffle: None,
+            rng,
+            duplicates: DuplicatePolicy::Allow,
+            index: HashMap::new(),
+            directives: Vec::new(),
+            named,
+        }
+    }
+
+    /// Name of the playlist.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of songs.
+    pub fn len(&self) -> usize {
+        self.songs.len()
+    }
+
+    /// Returns `true` if the playlist has no songs.
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty()
+    }
+
+    /// How songs that are already in the playlist are added.
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicates
+    }
+
+    /// Changes the duplicate policy. Duplicates already in the playlist are
+    /// left alone.
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicates = policy;
+    }
+
+    /// Playlist-level directives kept from an imported file.
+    pub fn directives(&self) -> &[String] {
+        &self.directives
+    }
+
+    /// Current repeat mode.
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    /// Changes the repeat mode.
+    pub fn set_repeat(&mut self, mode: RepeatMode) {
+        self.repeat = mode;
+    }
+
+    /// Returns `true` if shuffle is on.
+    pub fn is_shuffled(&self) -> bool {
+        self.shuffle.is_some()
+    }
+
+    /// Turns shuffle on or off.
+    ///
+    /// Turning it on starts a fresh round: every song except the current one
+    /// is played once, in random order, before any song repeats. Turning it
+    /// off continues in playlist order from the current song.
+    pub fn set_shuffle(&mut self, on: bool) {
+        if !on {
+            self.shuffle = None;
+        } else if self.shuffle.is_none() {
+            let mut shuffle = Shuffle::default();
+            self.refill(&mut shuffle);
+            shuffle.history.extend(self.now_playing);
+            self.shuffle = Some(shuffle);
+        }
+    }
+
+    /// Appends a song to the end of the playlist.
+    pub fn add_song(
+        &mut self,
+        name: impl Into<String>,
+        artist: impl Into<String>,
+    ) -> Result<SongId, PlaylistError> {
+        self.add(Song::new(name, artist)?)
+    }
+
+    /// Appends a song built with [`Song::new`], keeping its location and
+    /// duration.
+    ///
+    /// The song must pass [`Song::validate`], and the duplicate policy
+    /// decides what happens if its artist and name are already present.
+    pub fn add(&mut self, song: Song) -> Result<SongId, PlaylistError> {
+        if let Some(existing) = self.check(&song)? {
+            let tail = self.songs.tail_handle().expect("duplicate is in the list");
+            self.move_after(existing, tail)?;
+            return Ok(existing);
+        }
+        Ok(self.push(song))
+    }
+
+    /// Appends without validation; imported songs may lack an artist.
+    fn push(&mut self, song: Song) -> SongId {
+        let names = self.index.entry(song.artist.clone()).or_default();
+        let ids = names.entry(song.name.clone()).or_default();
+        let id = self.songs.insert_at_end(song);
+        ids.push(id);
+        if let Some(shuffle) = &mut self.shuffle {
+            shuffle.unplayed.push(id);
+        }
+        id
+    }
+
+    /// Validates `song` against the duplicate policy. Returns the existing
+    /// copy if it should be moved instead of adding `song`.
+    fn check(&self, song: &Song) -> Result<Option<SongId>, SongError> {
+        song.validate()?;
+        match (self.duplicates, self.find(&song.artist, &song.name)) {
+            (DuplicatePolicy::Allow, _) | (_, None) => Ok(None),
+            (DuplicatePolicy::Reject, Some(existing)) => Err(SongError::Duplicate(existing)),
+            (DuplicatePolicy::MoveToEnd, Some(existing)) => Ok(Some(existing)),
+        }
+    }
+
+    /// Inserts a song right after the current one, so it plays next in both
+    /// playlist and shuffle order. With nothing playing it goes to the front.
+    ///
+    /// Duplicates are handled as in [`add`](Playlist::add); under
+    /// [`DuplicatePolicy::MoveToEnd`] the existing copy is queued next.
+    pub fn insert_next(
+        &mut self,
+        name: impl Into<String>,
+        artist: impl Into<String>,
+    ) -> Result<SongId, PlaylistError> {
+        let song = Song::new(name, artist)?;
+        let id = match self.check(&song)? {
+            Some(existing) if Some(existing) == self.now_playing => return Ok(existing),
+            Some(existing) => existing,
+            None => self.push(song),
+        };
+        let Some(current) = self.now_playing else {
+            let head = self.songs.head_handle().expect("playlist has a song");
+            self.songs
+                .move_before(id, head)
+                .expect("both ids are valid");
+            return Ok(id);
+        };
+        self.songs
+            .move_after(id, current)
+            .expect("both ids are valid");
+        if let Some(shuffle) = &mut self.shuffle {
+            shuffle.unplayed.retain(|&other| other != id);
+            shuffle.place(shuffle.position + 1, id);
+        }
+        Ok(id)
+    }
+
+    /// Id of a song with this artist and name, if there is one.
+    pub fn find(&self, artist: &str, name: &str) -> Option<SongId> {
+        self.index.get(artist)?.get(name)?.first().copied()
+    }
+
+    /// Removes a song with this artist and name and returns it.
+    pub fn remove_by_key(&mut self, artist: &str, name: &str) -> Result<Song, PlaylistError> {
+        let id = self.find(artist, name).ok_or(PlaylistError::UnknownSong)?;
+        self.remove_song(id)
+    }
+
+    fn remove_from_index(&mut self, id: SongId) {
+        let Ok(song) = self.songs.get(id) else {
+            return;
+        };
+        let names = self.index.get_mut(&song.artist).expect("songs are indexed");
+        let ids = names.get_mut(&song.name).expect("songs are indexed");
+        ids.retain(|&other| other != id);
+        if ids.is_empty() {
+            names.remove(&song.name);
+            if names.is_empty() {
+                self.index.remove(&song.artist);
+            }
+        }
+    }
+
+    /// Removes a song and returns it.
+    ///
+    /// If it was playing, the song after it in playlist order (or before it,
+    /// if it was the last one) becomes the current song without being
+    /// announced as a skip; removing the only song stops playback.
+    pub fn remove_song(&mut self, id: SongId) -> Result<Song, PlaylistError> {
+        let next = self
+            .songs
+            .next_handle(id)
+            .map_err(|_| PlaylistError::UnknownSong)?;
+        let prev = self
+            .songs
+            .prev_handle(id)
+            .map_err(|_| PlaylistError::UnknownSong)?;
+        self.remove_from_index(id);
+        let song = self
+            .songs
+            .remove(id)
+            .map_err(|_| PlaylistError::UnknownSong)?;
+        if let Some(shuffle) = &mut self.shuffle {
+            shuffle.forget(id);
+        }
+        if self.now_playing == Some(id) {
+            self.now_playing = next.or(prev);
+            if let (Some(shuffle), Some(current)) = (&mut self.shuffle, self.now_playing) {
+                shuffle.unplayed.retain(|&other| other != current);
+                shuffle.position = shuffle.place(shuffle.position, current);
+            }
+        }
+        Ok(song)
+    }
+
+    /// Moves the song `id` right after `target`. The current song keeps
+    /// playing and the shuffle order is unaffected.
+    pub fn move_after(&mut self, id: SongId, target: SongId) -> Result<(), PlaylistError> {
+        self.songs
+            .move_after(id, target)
+            .map_err(|_| PlaylistError::UnknownSong)
+    }
+
+    /// Moves the song `id` right before `target`.
+    pub fn move_before(&mut self, id: SongId, target: SongId) -> Result<(), PlaylistError> {
+        self.songs
+            .move_before(id, target)
+            .map_err(|_| PlaylistError::UnknownSong)
+    }
+
+    /// Returns the song `id` refers to.
+    pub fn get(&self, id: SongId) -> Result<&Song, PlaylistError> {
+        self.songs.get(id).map_err(|_| PlaylistError::UnknownSong)
+    }
+
+    /// The song that is currently playing.
+    pub fn now_playing(&self) -> Option<&Song> {
+        self.now_playing.and_then(|id| self.songs.get(id).ok())
+    }
+
+    /// Id of the song that is currently playing.
+    pub fn now_playing_id(&self) -> Option<SongId> {
+        self.now_playing
+    }
+
+    /// Iterates over the songs in playlist order.
+    pub fn songs(&self) -> impl DoubleEndedIterator<Item = &Song> + ExactSizeIterator {
+        self.songs.iter()
+    }
+
+    /// Iterates over the song ids in playlist order.
+    pub fn song_ids(&self) -> impl DoubleEndedIterator<Item = SongId> + ExactSizeIterator + '_ {
+        self.songs.handles()
+    }
+
+    /// Starts playback from the first song, or from a random one when
+    /// shuffled (which also starts a fresh shuffle round).
+    pub fn start_playing(&mut self) -> Result<&Song, PlaylistError> {
+        let first = self.songs.head_handle().ok_or(PlaylistError::Empty)?;
+        self.now_playing = Some(first);
+        if let Some(mut shuffle) = self.shuffle.take() {
+            self.now_playing = None;
+            shuffle.history.clear();
+            self.refill(&mut shuffle);
+            let id = self.draw(&mut shuffle);
+            shuffle.play(id);
+            self.now_playing = Some(id);
+            self.shuffle = Some(shuffle);
+        }
+        self.current()
+    }
+
+    /// Jumps to the song `id`.
+    pub fn play(&mut self, id: SongId) -> Result<&Song, PlaylistError> {
+        self.get(id)?;
+        if let Some(shuffle) = &mut self.shuffle {
+            shuffle.history.truncate(shuffle.position + 1);
+            shuffle.play(id);
+        }
+        self.now_playing = Some(id);
+        self.current()
+    }
+
+    /// Skips to the next song.
+    ///
+    /// In playlist order the last song is followed by the first one unless
+    /// repeat is [`Off`](RepeatMode::Off). Shuffled, a new song is drawn once
+    /// the history is exhausted; when every song has played, a new round
+    /// starts unless repeat is off. A new round never opens with the song
+    /// that just ended.
+    pub fn next_song(&mut self) -> Result<&Song, PlaylistError> {
+        let current = self.now_playing.ok_or(PlaylistError::NotPlaying)?;
+        let next = match self.shuffle.take() {
+            Some(mut shuffle) => {
+                let next = self.next_shuffled(&mut shuffle);
+                self.shuffle = Some(shuffle);
+                next?
+            }
+            None => match self.songs.next_handle(current) {
+                Ok(Some(next)) => next,
+                _ if self.repeat == RepeatMode::Off => return Err(PlaylistError::EndOfPlaylist),
+                _ => self.songs.head_handle().ok_or(PlaylistError::Empty)?,
+            },
+        };
+        self.now_playing = Some(next);
+        self.current()
+    }
+
+    /// Goes back to the previous song.
+    ///
+    /// In playlist order this wraps to the last song unless repeat is off.
+    /// Shuffled, it walks back through the songs played so far.
+    pub fn previous_song(&mut self) -> Result<&Song, PlaylistError> {
+        let current = self.now_playing.ok_or(PlaylistError::NotPlaying)?;
+        let prev = match &mut self.shuffle {
+            Some(shuffle) => {
+                if shuffle.position == 0 {
+                    return Err(PlaylistError::BeginningOfPlaylist);
+                }
+                shuffle.position -= 1;
+                shuffle.history[shuffle.position]
+            }
+            None => match self.songs.prev_handle(current) {
+                Ok(Some(prev)) => prev,
+                _ if self.repeat == RepeatMode::Off => {
+                    return Err(PlaylistError::BeginningOfPlaylist)
+                }
+                _ => self.songs.tail_handle().ok_or(PlaylistError::Empty)?,
+            },
+        };
+        self.now_playing = Some(prev);
+        self.current()
+    }
+
+    /// Advances playback when the current song ends: with
+    /// [`RepeatMode::One`] the same song plays again, otherwise this is
+    /// [`next_song`](Playlist::next_song).
+    pub fn song_finished(&mut self) -> Result<&Song, PlaylistError> {
+        if self.repeat == RepeatMode::One {
+            return self.current();
+        }
+        self.next_song()
+    }
+
+    fn current(&self) -> Result<&Song, PlaylistError> {
+        self.now_playing().ok_or(PlaylistError::NotPlaying)
+    }
+
+    fn next_shuffled(&mut self, shuffle: &mut Shuffle) -> Result<SongId, PlaylistError> {
+        if shuffle.position + 1 < shuffle.history.len() {
+            shuffle.position += 1;
+            return Ok(shuffle.history[shuffle.position]);
+        }
+        if shuffle.unplayed.is_empty() {
+            if self.repeat == RepeatMode::Off {
+                return Err(PlaylistError::EndOfPlaylist);
+            }
+            self.refill(shuffle);
+        }
+        let next = self.draw(shuffle);
+        shuffle.play(next);
+        Ok(next)
+    }
+
+    /// Puts every song except the current one back into the bag.
+    fn refill(&self, shuffle: &mut Shuffle) {
+        shuffle.unplayed.clear();
+        shuffle.unplayed.extend(
+            self.songs
+                .handles()
+                .filter(|&id| Some(id) != self.now_playing),
+        );
+    }
+
+    /// Takes a random song out of the bag, or the current song again if the
+    /// bag is empty because the playlist has a single song.
+    fn draw(&mut self, shuffle: &mut Shuffle) -> SongId {
+        if shuffle.unplayed.is_empty() {
+            return self.now_playing.expect("playlist has a song");
+        }
+        let index = self.rng.below(shuffle.unplayed.len());
+        shuffle.unplayed.swap_remove(index)
+    }
+}
+
+impl fmt::Display for Playlist {
+    /// Lists the songs in playlist order, marking the current one, like the
+    /// Go `ShowAllSongs`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.name)?;
+        let entries = self.songs.handles().zip(&self.songs);
+        for (position, (id, song)) in entries.enumerate() {
+            let marker = if Some(id) == self.now_playing {
+                '>'
+            } else {
+                ' '
+            };
+            writeln!(f, "{marker} {}. {song}", position + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn playlist(songs: usize) -> Playlist {
+        let mut playlist = Playlist::with_seed("Test", 7);
+        for i in 0..songs {
+            playlist.add_song(format!("Song {i}"), "Artist").unwrap();
+        }
+        playlist
+    }
+
+    fn name(song: Result<&Song, PlaylistError>) -> String {
+        song.unwrap().name.clone()
+    }
+
+    /// Checks that the shuffle history holds every song at most once and
+    /// that `position` is inside it; returns the history length.
+    fn check_history(playlist: &Playlist) -> usize {
+        let shuffle = playlist.shuffle.as_ref().expect("shuffle is on");
+        let distinct: HashSet<_> = shuffle.history.iter().collect();
+        assert_eq!(
+            distinct.len(),
+            shuffle.history.len(),
+            "{:?}",
+            shuffle.history
+        );
+        assert!(shuffle.history.len() <= playlist.len());
+        assert!(shuffle
+            .history
+            .iter()
+            .all(|&id| playlist.songs.contains_handle(id)));
+        if let Some(current) = playlist.now_playing {
+            assert_eq!(shuffle.history.get(shuffle.position), Some(&current));
+        }
+        shuffle.history.len()
+    }
+
+    #[test]
+    fn playlist_order_stops_at_both_ends_without_repeat() {
+        let mut playlist = playlist(3);
+        assert_eq!(playlist.next_song().err(), Some(PlaylistError::NotPlaying));
+        assert_eq!(name(playlist.start_playing()), "Song 0");
+        assert_eq!(
+            playlist.previous_song().err(),
+            Some(PlaylistError::BeginningOfPlaylist)
+        );
+        assert_eq!(name(playlist.next_song()), "Song 1");
+        assert_eq!(name(playlist.song_finished()), "Song 2");
+        assert_eq!(
+            playlist.next_song().err(),
+            Some(PlaylistError::EndOfPlaylist)
+        );
+        assert_eq!(playlist.now_playing().unwrap().name, "Song 2");
+        assert_eq!(name(playlist.previous_song()), "Song 1");
+    }
+
+    #[test]
+    fn repeat_all_wraps_in_playlist_order() {
+        let mut playlist = playlist(2);
+        playlist.set_repeat(RepeatMode::All);
+        playlist.start_playing().unwrap();
+        assert_eq!(name(playlist.previous_song()), "Song 1");
+        assert_eq!(name(playlist.next_song()), "Song 0");
+        assert_eq!(name(playlist.next_song()), "Song 1");
+        assert_eq!(name(playlist.song_finished()), "Song 0");
+    }
+
+    #[test]
+    fn repeat_one_replays_only_when_the_song_finishes() {
+        let mut playlist = playlist(2);
+        playlist.set_repeat(RepeatMode::One);
+        playlist.start_playing().unwrap();
+        assert_eq!(name(playlist.song_finished()), "Song 0");
+        assert_eq!(name(playlist.next_song()), "Song 1");
+        assert_eq!(name(playlist.next_song()), "Song 0", "skipping wraps");
+    }
+
+    #[test]
+    fn empty_playlist() {
+        let mut playlist = playlist(0);
+        assert_eq!(playlist.start_playing().err(), Some(PlaylistError::Empty));
+        playlist.set_shuffle(true);
+        assert_eq!(playlist.start_playing().err(), Some(PlaylistError::Empty));
+        assert_eq!(
+            playlist.previous_song().err(),
+            Some(PlaylistError::NotPlaying)
+        );
+    }
+
+    #[test]
+    fn shuffle_plays_every_song_once_per_round() {
+        let mut playlist = playlist(8);
+        playlist.set_shuffle(true);
+        let mut played = vec![name(playlist.start_playing())];
+        while let Ok(song) = playlist.next_song() {
+            played.push(song.name.clone());
+            check_history(&playlist);
+        }
+        let distinct: HashSet<_> = played.iter().collect();
+        assert_eq!((played.len(), distinct.len()), (8, 8));
+        assert_eq!(
+            playlist.next_song().err(),
+            Some(PlaylistError::EndOfPlaylist)
+        );
+    }
+
+    #[test]
+    fn shuffle_is_reproducible_from_the_seed() {
+        let order = || {
+            let mut playlist = playlist(6);
+            playlist.set_shuffle(true);
+            let mut order = vec![name(playlist.start_playing())];
+            order.extend((1..6).map(|_| name(playlist.next_song())));
+            order
+        };
+        assert_eq!(order(), order());
+    }
+
+    #[test]
+    fn previous_song_walks_back_through_the_shuffle_history() {
+        let mut playlist = playlist(5);
+        playlist.set_shuffle(true);
+        let mut played = vec![name(playlist.start_playing())];
+        played.extend((0..3).map(|_| name(playlist.next_song())));
+        for expected in played[..3].iter().rev() {
+            assert_eq!(&name(playlist.previous_song()), expected);
+        }
+        assert_eq!(
+            playlist.previous_song().err(),
+            Some(PlaylistError::BeginningOfPlaylist)
+        );
+        for expected in &played[1..] {
+            assert_eq!(&name(playlist.next_song()), expected, "replays the history");
+        }
+        check_history(&playlist);
+    }
+
+    #[test]
+    fn repeat_all_shuffle_keeps_the_history_bounded() {
+        let mut playlist = playlist(4);
+        playlist.set_shuffle(true);
+        playlist.set_repeat(RepeatMode::All);
+        let mut previous = name(playlist.start_playing());
+        for _ in 0..100 {
+            let next = name(playlist.next_song());
+            assert_ne!(next, previous, "a new round never repeats the last song");
+            previous = next;
+            check_history(&playlist);
+        }
+        assert_eq!(check_history(&playlist), 4);
+        for _ in 0..3 {
+            playlist.previous_song().unwrap();
+        }
+        assert_eq!(
+            playlist.previous_song().err(),
+            Some(PlaylistError::BeginningOfPlaylist)
+        );
+    }
+
+    #[test]
+    fn removing_the_current_song_does_not_duplicate_history() {
+        let mut playlist = playlist(4);
+        playlist.set_shuffle(true);
+        playlist.set_repeat(RepeatMode::All);
+        playlist.start_playing().unwrap();
+        for _ in 0..5 {
+            playlist.next_song().unwrap();
+        }
+        while playlist.len() > 1 {
+            let current = playlist.now_playing_id().unwrap();
+            playlist.remove_song(current).unwrap();
+            check_history(&playlist);
+        }
+        let last = playlist.now_playing_id().unwrap();
+        playlist.remove_song(last).unwrap();
+        assert_eq!(playlist.now_playing_id(), None);
+        assert_eq!(check_history(&playlist), 0);
+    }
+
+    #[test]
+    fn insert_next_moves_a_played_duplicate_without_copying_it() {
+        let mut playlist = playlist(4);
+        playlist.set_duplicate_policy(DuplicatePolicy::MoveToEnd);
+        playlist.set_shuffle(true);
+        let first = name(playlist.start_playing());
+        playlist.next_song().unwrap();
+        let id = playlist.insert_next(first.clone(), "Artist").unwrap();
+        assert_eq!(playlist.len(), 4);
+        check_history(&playlist);
+        assert_eq!(name(playlist.next_song()), first);
+        assert_eq!(playlist.now_playing_id(), Some(id));
+        check_history(&playlist);
+    }
+
+    #[test]
+    fn jumping_to_a_played_song_moves_it_to_the_front_of_history() {
+        let mut playlist = playlist(3);
+        playlist.set_shuffle(true);
+        playlist.start_playing().unwrap();
+        let first = playlist.now_playing_id().unwrap();
+        playlist.next_song().unwrap();
+        playlist.play(first).unwrap();
+        assert_eq!(check_history(&playlist), 2);
+        assert_eq!(playlist.now_playing_id(), Some(first));
+        playlist.previous_song().unwrap();
+        assert_eq!(
+            playlist.previous_song().err(),
+            Some(PlaylistError::BeginningOfPlaylist)
+        );
+    }
+
+    #[test]
+    fn set_shuffle_off_continues_in_playlist_order() {
+        let mut playlist = playlist(5);
+        playlist.set_shuffle(true);
+        playlist.start_playing().unwrap();
+        playlist.set_shuffle(false);
+        let current = playlist.now_playing_id().unwrap();
+        let next = playlist.songs.next_handle(current).unwrap();
+        match next {
+            Some(next) => {
+                playlist.next_song().unwrap();
+                assert_eq!(playlist.now_playing_id(), Some(next));
+            }
+            None => assert!(playlist.next_song().is_err()),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Extended M3U and M3U8 playlists.
+//!
+//! A file is a list of locations, each optionally preceded by
+//! `#EXTINF:<seconds>[ <attributes>],<Artist> - <Title>`. `#EXTM3U` opens the
+//! file and `#PLAYLIST:` names it. M3U8 is the same format in UTF-8; since
+//! input is a `&str`, both are handled alike.
+
+use std::fmt::Write as _;
+
+use super::{
+    format_duration, parse_duration, ParseError, ParseErrorKind, Playlist, PlaylistError, Song,
+};
+
+/// Parsed `#EXTINF` line waiting for its location.
+struct Info {
+    line: usize,
+    song: Song,
+    /// Number of directives seen before the `#EXTINF` line.
+    directives_before: usize,
+}
+
+impl Playlist {
+    /// Reads an extended (or plain) M3U/M3U8 playlist.
+    ///
+    /// Unknown `#` directives are attached to the entry that follows them, or
+    /// to the playlist when no entry follows, and written back in the same
+    /// place by [`to_m3u`](Playlist::to_m3u). `#PLAYLIST:` names the
+    /// playlist and is kept the same way. Songs without `#EXTINF` are named
+    /// after their file.
+    pub fn from_m3u(text: &str) -> Result<Playlist, ParseError> {
+        let mut playlist = Playlist::new("");
+        let mut info: Option<Info> = None;
+        let mut directives = Vec::new();
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line == "#EXTM3U" {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("#EXTINF:") {
+                if let Some(pending) = info {
+                    return Err(dangling(pending));
+                }
+                info = Some(Info {
+                    line: number,
+                    song: parse_extinf(rest, number)?,
+                    directives_before: directives.len(),
+                });
+            } else if line.starts_with('#') {
+                if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+                    if !name.trim().is_empty() {
+                        playlist.name = name.trim().to_owned();
+                        playlist.named = true;
+                    }
+                }
+                directives.push(line.to_owned());
+            } else {
+                let mut song = match info.take() {
+                    Some(info) => Song {
+                        directives_after_info: directives.len() - info.directives_before,
+                        ..info.song
+                    },
+                    None => Song::from_location(line),
+                };
+                song.location = Some(line.to_owned());
+                song.directives = std::mem::take(&mut directives);
+                playlist.push(song);
+            }
+        }
+        if let Some(pending) = info {
+            return Err(dangling(pending));
+        }
+        playlist.directives = directives;
+        Ok(playlist)
+    }
+
+    /// Writes the playlist as M3U.
+    ///
+    /// A song is written as a bare location when its `#EXTINF` would say
+    /// nothing the location does not, and `#EXTM3U` opens the file unless no
+    /// `#` line is left, so plain playlists stay plain. The name is written
+    /// as `#PLAYLIST:` where the imported file had it, or at the top if the
+    /// playlist was named some other way.
+    ///
+    /// Every song needs a location; otherwise
+    /// [`PlaylistError::MissingLocation`] is returned.
+    pub fn to_m3u(&self) -> Result<String, PlaylistError> {
+        let mut out = String::new();
+        let names_kept = self
+            .songs()
+            .flat_map(|song| &song.directives)
+            .chain(&self.directives)
+            .any(|directive| directive.starts_with("#PLAYLIST:"));
+        if self.named && !names_kept {
+            let _ = writeln!(out, "#PLAYLIST:{}", self.name);
+        }
+        for song in self.songs() {
+            let location = song
+                .location
+                .as_deref()
+                .ok_or(PlaylistError::MissingLocation)?;
+            let split = song
+                .directives
+                .len()
+                .saturating_sub(song.directives_after_info);
+            let (before, after) = song.directives.split_at(split);
+            write_directives(&mut out, before);
+            let implied = song.title_is_implied(location)
+                && song.duration.is_none()
+                && song.attributes.is_none();
+            if !implied {
+                out.push_str("#EXTINF:");
+                out.push_str(&format_duration(song.duration));
+                if let Some(attributes) = &song.attributes {
+                    out.push(' ');
+                    out.push_str(attributes);
+                }
+                let _ = writeln!(out, ",{}", song.title());
+            }
+            write_directives(&mut out, after);
+            out.push_str(location);
+            out.push('\n');
+        }
+        write_directives(&mut out, &self.directives);
+        if out.lines().any(|line| line.starts_with('#')) {
+            out.insert_str(0, "#EXTM3U\n");
+        }
+        Ok(out)
+    }
+}
+
+/// Writes the M3U directives among `directives`, one per line.
+fn write_directives(out: &mut String, directives: &[String]) {
+    for directive in directives.iter().filter(|d| d.starts_with('#')) {
+        out.push_str(directive);
+        out.push('\n');
+    }
+}
+
+/// Parses what follows `#EXTINF:`.
+fn parse_extinf(rest: &str, line: usize) -> Result<Song, ParseError> {
+    let error = |kind| ParseError { line, kind };
+    // Attribute values are quoted and may themselves contain commas.
+    let mut quoted = false;
+    let comma = rest
+        .char_indices()
+        .find(|&(_, c)| {
+            quoted ^= c == '"';
+            c == ',' && !quoted
+        })
+        .ok_or(error(ParseErrorKind::MissingTitle))?
+        .0;
+    let (head, title) = (rest[..comma].trim(), &rest[comma + 1..]);
+    let (seconds, attributes) = match head.split_once(char::is_whitespace) {
+        Some((seconds, attributes)) => (seconds, Some(attributes.trim().to_owned())),
+        None => (head, None),
+    };
+    let duration = parse_duration(seconds).ok_or(error(ParseErrorKind::InvalidDuration))?;
+    Ok(Song {
+        duration,
+        attributes,
+        ..Song::from_title(title)
+    })
+}
+
+fn dangling(info: Info) -> ParseError {
+    ParseError {
+        line: info.line,
+        kind: ParseErrorKind::DanglingInfo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn songs(playlist: &Playlist) -> Vec<Song> {
+        playlist.songs().cloned().collect()
+    }
+
+    fn error(text: &str) -> (usize, ParseErrorKind) {
+        let error = Playlist::from_m3u(text).unwrap_err();
+        (error.line, error.kind)
+    }
+
+    fn round_trip(text: &str) -> Playlist {
+        let playlist = Playlist::from_m3u(text).unwrap();
+        assert_eq!(playlist.to_m3u().unwrap(), text);
+        playlist
+    }
+
+    #[test]
+    fn extended_playlist_round_trips_verbatim() {
+        let text = "\
+#EXTM3U
+#PLAYLIST:Road trip
+#EXTGRP:Rock
+#EXTINF:201 tvg-id=\"a,b\",AC - DC - Thunderstruck
+#EXTVLCOPT:start-time=5
+thunder.mp3
+plain.mp3
+#EXTINF:-1,Just a title
+http://example.com/radio
+#EXTINF:2.5, Spaced  -  Out
+spaced.mp3
+#EXT-X-ENDLIST
+";
+        let playlist = round_trip(text);
+        assert_eq!(playlist.name(), "Road trip");
+        assert_eq!(playlist.directives(), ["#EXT-X-ENDLIST"]);
+        let songs = songs(&playlist);
+        assert_eq!(songs.len(), 4);
+        assert_eq!(songs[0].duration, Some(Duration::from_secs(201)));
+        assert_eq!(songs[0].attributes.as_deref(), Some("tvg-id=\"a,b\""));
+        assert_eq!(
+            songs[0].directives,
+            [
+                "#PLAYLIST:Road trip",
+                "#EXTGRP:Rock",
+                "#EXTVLCOPT:start-time=5"
+            ]
+        );
+        assert_eq!(songs[0].directives_after_info, 1);
+        assert_eq!(
+            (songs[1].name.as_str(), songs[1].title.as_deref()),
+            ("plain", None)
+        );
+        assert_eq!(
+            (songs[2].artist.as_str(), songs[2].name.as_str()),
+            ("", "Just a title")
+        );
+        assert_eq!(
+            (songs[3].artist.as_str(), songs[3].name.as_str()),
+            ("Spaced", "Out")
+        );
+    }
+
+    #[test]
+    fn plain_playlist_stays_plain() {
+        let playlist = round_trip("one.mp3\ndir/two.ogg\n");
+        assert_eq!(playlist.name(), "New Playlist");
+        let names: Vec<_> = playlist.songs().map(|song| song.name.as_str()).collect();
+        assert_eq!(names, ["one", "two"]);
+    }
+
+    #[test]
+    fn playlist_line_is_only_written_when_named() {
+        let text = "#EXTM3U\n#EXTINF:10,A - B\nb.mp3\n";
+        round_trip(text);
+        let mut playlist = Playlist::with_seed("Mix", 0);
+        playlist
+            .add(
+                Song::new("Name", "Artist")
+                    .unwrap()
+                    .with_location("song.mp3"),
+            )
+            .unwrap();
+        assert_eq!(
+            playlist.to_m3u().unwrap(),
+            "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:-1,Artist - Name\nsong.mp3\n"
+        );
+    }
+
+    #[test]
+    fn removing_the_song_that_carried_the_name_keeps_it() {
+        let mut playlist = Playlist::from_m3u("#PLAYLIST:Kept\nfirst.mp3\nsecond.mp3\n").unwrap();
+        let first = playlist.song_ids().next().unwrap();
+        playlist.remove_song(first).unwrap();
+        assert_eq!(
+            playlist.to_m3u().unwrap(),
+            "#EXTM3U\n#PLAYLIST:Kept\nsecond.mp3\n"
+        );
+    }
+
+    #[test]
+    fn edited_songs_get_a_fresh_title() {
+        let mut playlist =
+            Playlist::from_m3u("#EXTINF:5,Old Artist - Old Name\nsong.mp3\n").unwrap();
+        let id = playlist.song_ids().next().unwrap();
+        let song = playlist.songs.get_mut(id).unwrap();
+        song.name = String::from("New Name");
+        assert_eq!(
+            playlist.to_m3u().unwrap(),
+            "#EXTM3U\n#EXTINF:5,Old Artist - New Name\nsong.mp3\n"
+        );
+    }
+
+    #[test]
+    fn normalises_whitespace_and_byte_order_mark() {
+        let playlist =
+            Playlist::from_m3u("\u{feff}#EXTM3U\r\n\r\n  #EXTINF:1,A - B  \r\n  b.mp3\r\n")
+                .unwrap();
+        assert_eq!(
+            playlist.to_m3u().unwrap(),
+            "#EXTM3U\n#EXTINF:1,A - B\nb.mp3\n"
+        );
+    }
+
+    #[test]
+    fn songs_need_a_location_to_export() {
+        let mut playlist = Playlist::with_seed("", 0);
+        playlist.add_song("Name", "Artist").unwrap();
+        assert_eq!(playlist.to_m3u(), Err(PlaylistError::MissingLocation));
+    }
+
+    #[test]
+    fn errors_report_the_offending_line() {
+        use ParseErrorKind::*;
+        assert_eq!(error("#EXTM3U\n#EXTINF:10\na.mp3\n"), (2, MissingTitle));
+        assert_eq!(
+            error("#EXTM3U\n\n#EXTINF:ten,A - B\na.mp3\n"),
+            (3, InvalidDuration)
+        );
+        assert_eq!(error("#EXTINF:1 x=\"a,b\"\na.mp3\n"), (1, MissingTitle));
+        // A second `#EXTINF` or the end of the file leaves the first one
+        // without a location; the error points at the unfinished one.
+        assert_eq!(
+            error("a.mp3\n#EXTINF:1,A\n#EXTGRP:x\n#EXTINF:2,B\nb.mp3\n"),
+            (2, DanglingInfo)
+        );
+        assert_eq!(error("a.mp3\n\n#EXTINF:1,A\n\n"), (3, DanglingInfo));
+    }
+
+    #[test]
+    fn error_lines_count_crlf_and_byte_order_mark() {
+        assert_eq!(
+            error("\u{feff}#EXTM3U\r\n\r\n#EXTINF:x,A\r\na.mp3\r\n"),
+            (3, ParseErrorKind::InvalidDuration)
+        );
+        let error = Playlist::from_m3u("a.mp3\n#EXTINF:1,A\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: #EXTINF not followed by a location"
+        );
+    }
+}
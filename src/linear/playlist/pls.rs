@@ -0,0 +1,269 @@
+//! PLS playlists.
+//!
+//! A PLS file is an INI-style `[playlist]` section of numbered keys
+//! (`File1`, `Title1`, `Length1`, ...) followed by `NumberOfEntries` and
+//! `Version`. Entries may appear in any order and are sorted by number.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use super::{
+    format_duration, parse_duration, ParseError, ParseErrorKind, Playlist, PlaylistError, Song,
+};
+
+/// Keys collected for one entry number.
+#[derive(Default)]
+struct Entry {
+    /// Line of the first key seen for this entry.
+    line: usize,
+    file: Option<String>,
+    title: Option<String>,
+    length: Option<Option<Duration>>,
+    directives: Vec<String>,
+}
+
+impl Playlist {
+    /// Reads a PLS playlist.
+    ///
+    /// Unknown numbered keys are kept on their entry and unknown unnumbered
+    /// keys on the playlist; [`to_pls`](Playlist::to_pls) writes both back.
+    /// Comment lines starting with `;` or `#` are skipped.
+    pub fn from_pls(text: &str) -> Result<Playlist, ParseError> {
+        let mut playlist = Playlist::new("");
+        let mut entries: BTreeMap<u32, Entry> = BTreeMap::new();
+        let mut count = None;
+        let mut header = false;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+            let error = |kind| ParseError { line: number, kind };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with([';', '#']) {
+                continue;
+            }
+            if !header {
+                if !line.eq_ignore_ascii_case("[playlist]") {
+                    return Err(error(ParseErrorKind::MissingHeader));
+                }
+                header = true;
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(error(ParseErrorKind::MalformedLine))?;
+            let (key, value) = (key.trim(), value.trim());
+            let digits = key.len() - key.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            let (name, entry_number) = key.split_at(key.len() - digits);
+            if name.is_empty() {
+                return Err(error(ParseErrorKind::MalformedLine));
+            }
+            if entry_number.is_empty() {
+                if name.eq_ignore_ascii_case("NumberOfEntries") {
+                    let value: usize = value
+                        .parse()
+                        .map_err(|_| error(ParseErrorKind::MalformedLine))?;
+                    count = Some((number, value));
+                } else if !name.eq_ignore_ascii_case("Version") {
+                    playlist.directives.push(format!("{name}={value}"));
+                }
+                continue;
+            }
+            let entry_number: u32 = match entry_number.parse() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(error(ParseErrorKind::InvalidIndex)),
+            };
+            let entry = entries.entry(entry_number).or_insert_with(|| Entry {
+                line: number,
+                ..Entry::default()
+            });
+            let duplicate = if name.eq_ignore_ascii_case("File") {
+                entry.file.replace(value.to_owned()).is_some()
+            } else if name.eq_ignore_ascii_case("Title") {
+                entry.title.replace(value.to_owned()).is_some()
+            } else if name.eq_ignore_ascii_case("Length") {
+                let length = parse_duration(value).ok_or(error(ParseErrorKind::InvalidDuration))?;
+                entry.length.replace(length).is_some()
+            } else {
+                entry.directives.push(format!("{name}={value}"));
+                false
+            };
+            if duplicate {
+                return Err(error(ParseErrorKind::DuplicateKey));
+            }
+        }
+        if let Some((line, count)) = count {
+            if count != entries.len() {
+                return Err(ParseError {
+                    line,
+                    kind: ParseErrorKind::EntryCountMismatch,
+                });
+            }
+        }
+        for entry in entries.into_values() {
+            let location = entry.file.ok_or(ParseError {
+                line: entry.line,
+                kind: ParseErrorKind::MissingFile,
+            })?;
+            let mut song = match &entry.title {
+                Some(title) => Song::from_title(title),
+                None => Song::from_location(&location),
+            };
+            song.location = Some(location);
+            song.duration = entry.length.flatten();
+            song.directives = entry.directives;
+            playlist.push(song);
+        }
+        Ok(playlist)
+    }
+
+    /// Writes the playlist as PLS version 2.
+    ///
+    /// `Title` is left out for entries that had none and are still named
+    /// after their file. Every song needs a location; otherwise
+    /// [`PlaylistError::MissingLocation`] is returned. PLS has no field for
+    /// the playlist name, so it is not written.
+    pub fn to_pls(&self) -> Result<String, PlaylistError> {
+        let mut out = String::from("[playlist]\n");
+        for (index, song) in self.songs().enumerate() {
+            let n = index + 1;
+            let location = song
+                .location
+                .as_deref()
+                .ok_or(PlaylistError::MissingLocation)?;
+            let _ = writeln!(out, "File{n}={location}");
+            if !song.title_is_implied(location) {
+                let _ = writeln!(out, "Title{n}={}", song.title());
+            }
+            let _ = writeln!(out, "Length{n}={}", format_duration(song.duration));
+            for directive in song.directives.iter().filter(|d| !d.starts_with('#')) {
+                if let Some((key, value)) = directive.split_once('=') {
+                    let _ = writeln!(out, "{key}{n}={value}");
+                }
+            }
+        }
+        for directive in self.directives.iter().filter(|d| !d.starts_with('#')) {
+            out.push_str(directive);
+            out.push('\n');
+        }
+        let _ = writeln!(out, "NumberOfEntries={}", self.len());
+        out.push_str("Version=2\n");
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn songs(playlist: &Playlist) -> Vec<Song> {
+        playlist.songs().cloned().collect()
+    }
+
+    fn error(text: &str) -> (usize, ParseErrorKind) {
+        let error = Playlist::from_pls(text).unwrap_err();
+        (error.line, error.kind)
+    }
+
+    #[test]
+    fn playlist_round_trips_verbatim() {
+        let text = "\
+[playlist]
+File1=a.mp3
+Title1=AC - DC - Thunderstruck
+Length1=120
+File2=http://example.com/radio
+Length2=-1
+Genre2=News
+X-Custom=1
+NumberOfEntries=2
+Version=2
+";
+        let playlist = Playlist::from_pls(text).unwrap();
+        assert_eq!(playlist.to_pls().unwrap(), text);
+        let songs = songs(&playlist);
+        assert_eq!(songs[0].title.as_deref(), Some("AC - DC - Thunderstruck"));
+        assert_eq!((songs[1].name.as_str(), songs[1].duration), ("radio", None));
+        assert_eq!(songs[1].directives, ["Genre=News"]);
+        assert_eq!(playlist.directives(), ["X-Custom=1"]);
+    }
+
+    #[test]
+    fn reordered_keys_read_back_the_same() {
+        let text = "\
+; exported by some player
+[Playlist]
+Version=2
+numberofentries=2
+Title2=Two
+File2=two.mp3
+File1=one.mp3
+Length1=3.5
+";
+        let playlist = Playlist::from_pls(text).unwrap();
+        let written = playlist.to_pls().unwrap();
+        let reread = Playlist::from_pls(&written).unwrap();
+        assert_eq!(songs(&reread), songs(&playlist));
+        assert_eq!(reread.to_pls().unwrap(), written);
+        let names: Vec<_> = playlist.songs().map(|song| song.name.as_str()).collect();
+        assert_eq!(names, ["one", "Two"]);
+    }
+
+    #[test]
+    fn converts_to_m3u_and_back() {
+        let text = "[playlist]\nFile1=a.mp3\nTitle1=Artist - Name\nLength1=60\nNumberOfEntries=1\nVersion=2\n";
+        let playlist = Playlist::from_pls(text).unwrap();
+        let m3u = playlist.to_m3u().unwrap();
+        assert_eq!(m3u, "#EXTM3U\n#EXTINF:60,Artist - Name\na.mp3\n");
+        assert_eq!(Playlist::from_m3u(&m3u).unwrap().to_pls().unwrap(), text);
+    }
+
+    #[test]
+    fn errors_report_the_offending_line() {
+        use ParseErrorKind::*;
+        assert_eq!(error("; comment\n\nFile1=a.mp3\n"), (3, MissingHeader));
+        assert!(Playlist::from_pls("; only a comment\n").unwrap().is_empty());
+        assert_eq!(
+            error("[playlist]\nFile1=a.mp3\nnonsense\n"),
+            (3, MalformedLine)
+        );
+        assert_eq!(error("[playlist]\n=a.mp3\n"), (2, MalformedLine));
+        assert_eq!(error("[playlist]\n1=a.mp3\n"), (2, MalformedLine));
+        assert_eq!(
+            error("[playlist]\nNumberOfEntries=two\n"),
+            (2, MalformedLine)
+        );
+        assert_eq!(error("[playlist]\nFile0=a.mp3\n"), (2, InvalidIndex));
+        assert_eq!(
+            error("[playlist]\nFile99999999999=a.mp3\n"),
+            (2, InvalidIndex)
+        );
+        assert_eq!(
+            error("[playlist]\nFile1=a.mp3\nLength1=long\n"),
+            (3, InvalidDuration)
+        );
+        assert_eq!(
+            error("[playlist]\nFile1=a.mp3\n\nfile1=b.mp3\n"),
+            (4, DuplicateKey)
+        );
+        assert_eq!(
+            error("[playlist]\nFile1=a.mp3\nNumberOfEntries=2\nVersion=2\n"),
+            (3, EntryCountMismatch)
+        );
+        // A missing `File` is reported at the entry's first key.
+        assert_eq!(
+            error("[playlist]\nFile1=a.mp3\nLength2=5\nTitle2=B\nNumberOfEntries=2\n"),
+            (3, MissingFile)
+        );
+    }
+
+    #[test]
+    fn error_lines_count_crlf_and_byte_order_mark() {
+        assert_eq!(
+            error("\u{feff}[playlist]\r\n\r\nFile1=a.mp3\r\nTitle1\r\n"),
+            (4, ParseErrorKind::MalformedLine)
+        );
+        let error = Playlist::from_pls("[playlist]\nFile2=b.mp3\nFile2=c.mp3\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: duplicate key");
+    }
+}
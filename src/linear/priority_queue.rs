@@ -0,0 +1,227 @@
+//! Priority queue: see "Priority Queue" in `Linear/Queue.md`.
+//!
+//! The Clojure version keeps `[priority item]` pairs in a sorted set and
+//! dequeues the first. Only the smallest pair is ever needed, so
+//! [`PriorityQueue`] keeps them in a binary min-heap instead: a `Vec` read
+//! as a complete binary tree in which every parent is at most its children.
+//! Unlike the sorted set, it holds equal pairs side by side.
+
+use std::fmt;
+
+/// A queue that dequeues the item with the smallest priority first.
+///
+/// Items with equal priorities come out in no particular order.
+///
+/// ```
+/// use data_structure::linear::PriorityQueue;
+///
+/// let mut queue = PriorityQueue::new();
+/// queue.push(3, "Low priority");
+/// queue.push(1, "High priority");
+/// queue.push(2, "Medium priority");
+/// assert_eq!(queue.peek(), Some((&1, &"High priority")));
+/// assert_eq!(queue.pop(), Some((1, "High priority")));
+/// assert_eq!(queue.len(), 2);
+/// ```
+///
+/// | Operation      | Cost           |
+/// |----------------|----------------|
+/// | `push`         | O(log n)       |
+/// | `pop`          | O(log n)       |
+/// | `peek`, `len`  | O(1)           |
+#[derive(Clone)]
+pub struct PriorityQueue<P, T> {
+    /// The children of `i` are `2i + 1` and `2i + 2`.
+    heap: Vec<(P, T)>,
+}
+
+impl<P: Ord, T> PriorityQueue<P, T> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        PriorityQueue { heap: Vec::new() }
+    }
+
+    /// Creates an empty queue with room for `capacity` items.
+    pub fn with_capacity(capacity: usize) -> Self {
+        PriorityQueue {
+            heap: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of items.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if the queue holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Adds `item` with `priority`.
+    pub fn push(&mut self, priority: P, item: T) {
+        self.heap.push((priority, item));
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    /// Removes the item with the smallest priority.
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        let last = self.heap.len().checked_sub(1)?;
+        self.heap.swap(0, last);
+        let top = self.heap.pop();
+        self.sift_down(0);
+        top
+    }
+
+    /// Returns the item with the smallest priority.
+    pub fn peek(&self) -> Option<(&P, &T)> {
+        self.heap.first().map(|(priority, item)| (priority, item))
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.heap[parent].0 <= self.heap[index].0 {
+                break;
+            }
+            self.heap.swap(parent, index);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let mut smallest = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < self.heap.len() && self.heap[child].0 < self.heap[smallest].0 {
+                    smallest = child;
+                }
+            }
+            if smallest == index {
+                break;
+            }
+            self.heap.swap(index, smallest);
+            index = smallest;
+        }
+    }
+}
+
+impl<P: Ord, T> Default for PriorityQueue<P, T> {
+    fn default() -> Self {
+        PriorityQueue::new()
+    }
+}
+
+impl<P: Ord, T> Extend<(P, T)> for PriorityQueue<P, T> {
+    fn extend<I: IntoIterator<Item = (P, T)>>(&mut self, iter: I) {
+        for (priority, item) in iter {
+            self.push(priority, item);
+        }
+    }
+}
+
+impl<P: Ord, T> FromIterator<(P, T)> for PriorityQueue<P, T> {
+    fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+        let mut queue = PriorityQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<P: fmt::Debug, T: fmt::Debug> fmt::Debug for PriorityQueue<P, T> {
+    /// Lists the items in heap order, which starts with the smallest
+    /// priority but is not sorted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.heap).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SplitMix64;
+    use crate::testing::DropCounter;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn pops_in_priority_order_like_a_sorted_model() {
+        let mut rng = SplitMix64::new(7);
+        let mut queue = PriorityQueue::new();
+        let mut model: Vec<(usize, usize)> = Vec::new();
+        for step in 0..2000 {
+            if rng.below(3) == 0 {
+                model.sort_unstable();
+                let popped = queue.pop();
+                assert_eq!(
+                    popped.map(|(priority, _)| priority),
+                    model.first().map(|&(priority, _)| priority)
+                );
+                if let Some((priority, item)) = popped {
+                    let index = model.binary_search(&(priority, item)).unwrap();
+                    model.remove(index);
+                }
+            } else {
+                // Few distinct priorities, so many are equal.
+                let priority = rng.below(50);
+                queue.push(priority, step);
+                model.push((priority, step));
+            }
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(
+                queue.peek().map(|(&priority, _)| priority),
+                model.iter().map(|&(priority, _)| priority).min()
+            );
+        }
+    }
+
+    #[test]
+    fn collects_and_drains_in_order() {
+        let mut queue: PriorityQueue<_, _> = [5, 3, 8, 1, 9, 2]
+            .into_iter()
+            .map(|n| (n, n * 10))
+            .collect();
+        queue.extend([(4, 40), (0, 0)]);
+        let drained: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(
+            drained,
+            [
+                (0, 0),
+                (1, 10),
+                (2, 20),
+                (3, 30),
+                (4, 40),
+                (5, 50),
+                (8, 80),
+                (9, 90)
+            ]
+        );
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.peek(), None);
+    }
+
+    #[test]
+    fn drops_every_item_once() {
+        let counter = DropCounter::new();
+        let mut queue = PriorityQueue::with_capacity(4);
+        for priority in 0..10 {
+            queue.push(priority, counter.item(priority));
+        }
+        let (_, item) = queue.pop().unwrap();
+        assert_eq!(item.value, 0);
+        drop(item);
+        assert_eq!(counter.dropped(), 1);
+        queue.pop();
+        assert_eq!(counter.dropped(), 2);
+        queue.clear();
+        assert_eq!(counter.dropped(), 10);
+        queue.push(0, counter.item(10));
+        drop(queue);
+        assert_eq!(counter.dropped(), 11);
+    }
+}
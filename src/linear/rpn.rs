@@ -0,0 +1,740 @@
+//! Reverse Polish Notation calculator: see `Linear/Stack.md`.
+//!
+//! [`RpnCalculator`] is the notes' `RPNCalculator`: operands are pushed on a
+//! stack and each operator pops its arguments and pushes the result. Where
+//! the Ruby class hardcodes `+ - * /` on floats, raises a bare string on
+//! unknown operators and computes with `nil` when the stack runs dry, this
+//! one looks operators up in a registry that callers can extend, computes
+//! in integers or floats, and reports every failure as an [`RpnError`]
+//! naming the offending token.
+//!
+//! Infix formulas are converted to postfix by [`to_rpn`] with Dijkstra's
+//! shunting-yard algorithm. Expressions evaluated over and over can be
+//! compiled once to bytecode for the stack machine in [`vm`].
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::stack::{ArrayStack, Stack};
+
+pub mod infix;
+pub mod vm;
+
+pub use infix::{to_rpn, InfixError, InfixErrorKind};
+
+/// A value on the calculator's stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// The value as a float; large integers lose precision.
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+
+    /// Returns `true` if the value is zero.
+    pub fn is_zero(self) -> bool {
+        self.to_f64() == 0.0
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::Int(value)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float(value)
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{value}"),
+            Number::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// How a calculator computes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Mode {
+    /// Every value is an `i64`. Division truncates, overflow is an error,
+    /// and a float is only accepted if it is a whole number.
+    Integer,
+    /// Every value is an `f64`.
+    #[default]
+    Float,
+}
+
+impl Mode {
+    /// Converts `value` to this mode's representation.
+    fn coerce(self, value: Number) -> Result<Number, RpnErrorKind> {
+        match (self, value) {
+            (Mode::Float, value) => Ok(Number::Float(value.to_f64())),
+            (Mode::Integer, Number::Int(value)) => Ok(Number::Int(value)),
+            (Mode::Integer, Number::Float(value)) => {
+                if !value.is_finite() || value.fract() != 0.0 {
+                    Err(RpnErrorKind::NotAnInteger)
+                } else if !(-(2f64.powi(63))..2f64.powi(63)).contains(&value) {
+                    Err(RpnErrorKind::Overflow)
+                } else {
+                    Ok(Number::Int(value as i64))
+                }
+            }
+        }
+    }
+}
+
+/// One token of a postfix expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Number(Number),
+    /// An operator, stack word, variable or assignment.
+    Word(String),
+    /// A function applied to `arity` arguments, as written in infix.
+    Call {
+        name: String,
+        arity: usize,
+    },
+}
+
+impl fmt::Display for Token {
+    /// Writes the token as postfix text; a `Call` loses its argument count.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{value}"),
+            Token::Word(word) => f.write_str(word),
+            Token::Call { name, .. } => f.write_str(name),
+        }
+    }
+}
+
+/// What went wrong while evaluating a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpnErrorKind {
+    /// The token needed more values than were on the stack.
+    Underflow,
+    /// Division or remainder by zero.
+    DivisionByZero,
+    /// An integer result out of `i64` range.
+    Overflow,
+    /// A fractional or non-finite value in integer mode.
+    NotAnInteger,
+    /// An argument outside the operator's domain, like `sqrt` of a
+    /// negative number.
+    InvalidArgument,
+    /// A word that is neither a number, an operator, a stack word nor a
+    /// variable.
+    UnknownToken(String),
+    /// A function called with the wrong number of arguments.
+    Arity { expected: usize, found: usize },
+    /// The expression left no value, or more than one.
+    Unbalanced(usize),
+}
+
+/// An evaluation failure at token `index` (counted from 0). Errors found
+/// after the last token, like [`RpnErrorKind::Unbalanced`], have the index
+/// one past it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpnError {
+    pub index: usize,
+    pub kind: RpnErrorKind,
+}
+
+impl fmt::Display for RpnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpnErrorKind::Underflow => f.write_str("not enough operands"),
+            RpnErrorKind::DivisionByZero => f.write_str("division by zero"),
+            RpnErrorKind::Overflow => f.write_str("integer overflow"),
+            RpnErrorKind::NotAnInteger => f.write_str("not an integer"),
+            RpnErrorKind::InvalidArgument => f.write_str("invalid argument"),
+            RpnErrorKind::UnknownToken(token) => write!(f, "unknown token `{token}`"),
+            RpnErrorKind::Arity { expected, found } => {
+                write!(f, "takes {expected} arguments but was given {found}")
+            }
+            RpnErrorKind::Unbalanced(count) => {
+                write!(f, "expression left {count} values instead of one")
+            }
+        }
+    }
+}
+
+impl fmt::Display for RpnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "token {}: {}", self.index, self.kind)
+    }
+}
+
+impl Error for RpnError {}
+
+/// Why an infix expression could not be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// The expression did not parse.
+    Parse(InfixError),
+    /// Its postfix form failed; the index counts tokens of [`to_rpn`]'s
+    /// output.
+    Evaluate(RpnError),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::Parse(error) => write!(f, "invalid expression: {error}"),
+            ExpressionError::Evaluate(error) => write!(f, "evaluation failed: {error}"),
+        }
+    }
+}
+
+impl Error for ExpressionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExpressionError::Parse(error) => Some(error),
+            ExpressionError::Evaluate(error) => Some(error),
+        }
+    }
+}
+
+impl From<InfixError> for ExpressionError {
+    fn from(error: InfixError) -> Self {
+        ExpressionError::Parse(error)
+    }
+}
+
+impl From<RpnError> for ExpressionError {
+    fn from(error: RpnError) -> Self {
+        ExpressionError::Evaluate(error)
+    }
+}
+
+/// An operator's implementation. It receives its arguments bottom first,
+/// all in the calculator's mode, and its result is converted back to it.
+pub type OperatorFn = fn(&[Number]) -> Result<Number, RpnErrorKind>;
+
+#[derive(Debug, Clone, Copy)]
+struct Operator {
+    arity: usize,
+    function: OperatorFn,
+}
+
+/// Words the evaluator handles itself; they cannot be registered.
+const STACK_WORDS: [&str; 3] = ["dup", "swap", "drop"];
+
+/// An RPN calculator with an operator registry and variables.
+///
+/// Tokens are evaluated left to right:
+///
+/// | Token            | Effect                                              |
+/// |------------------|-----------------------------------------------------|
+/// | number           | pushes it                                           |
+/// | `dup`            | pushes a copy of the top value                      |
+/// | `swap`           | exchanges the top two values                        |
+/// | `drop`           | discards the top value                              |
+/// | `=name`          | pops the top value into variable `name`             |
+/// | operator         | pops its arguments and pushes its result            |
+/// | variable         | pushes its value                                    |
+///
+/// Operators shadow variables of the same name. The registry starts with
+/// `+ - * / % ^`, `neg`, `abs`, `sqrt`, `min` and `max`.
+///
+/// ```
+/// use data_structure::linear::rpn::{Mode, Number, RpnCalculator, RpnErrorKind};
+///
+/// let mut calculator = RpnCalculator::new();
+/// assert_eq!(calculator.evaluate("3 4 + 2 *"), Ok(Number::Float(14.0)));
+///
+/// let mut calculator = RpnCalculator::with_mode(Mode::Integer);
+/// calculator.set_variable("x", 7);
+/// calculator.register("double", 1, |args| match args[0] {
+///     Number::Int(n) => n.checked_mul(2).map(Number::Int).ok_or(RpnErrorKind::Overflow),
+///     other => Ok(Number::Float(other.to_f64() * 2.0)),
+/// });
+/// assert_eq!(calculator.evaluate("x dup * double 3 /"), Ok(Number::Int(32)));
+/// assert_eq!(calculator.evaluate_infix("-x ^ 2 + max(1, 3)"), Ok(Number::Int(-46)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RpnCalculator {
+    stack: ArrayStack<Number>,
+    mode: Mode,
+    operators: HashMap<String, Operator>,
+    variables: HashMap<String, Number>,
+}
+
+impl RpnCalculator {
+    /// Creates a floating-point calculator with the built-in operators.
+    pub fn new() -> Self {
+        RpnCalculator::with_mode(Mode::Float)
+    }
+
+    /// Creates a calculator in `mode` with the built-in operators.
+    pub fn with_mode(mode: Mode) -> Self {
+        let mut calculator = RpnCalculator {
+            stack: ArrayStack::new(),
+            mode,
+            operators: HashMap::new(),
+            variables: HashMap::new(),
+        };
+        let builtins: [(&str, usize, OperatorFn); 11] = [
+            ("+", 2, add),
+            ("-", 2, subtract),
+            ("*", 2, multiply),
+            ("/", 2, divide),
+            ("%", 2, remainder),
+            ("^", 2, power),
+            ("neg", 1, negate),
+            ("abs", 1, |args| match args[0] {
+                Number::Int(a) => a
+                    .checked_abs()
+                    .map(Number::Int)
+                    .ok_or(RpnErrorKind::Overflow),
+                Number::Float(a) => Ok(Number::Float(a.abs())),
+            }),
+            ("sqrt", 1, |args| match args[0] {
+                value if value.to_f64() < 0.0 => Err(RpnErrorKind::InvalidArgument),
+                Number::Int(a) => Ok(Number::Int(a.isqrt())),
+                Number::Float(a) => Ok(Number::Float(a.sqrt())),
+            }),
+            ("min", 2, |args| {
+                binary(args, |a, b| Some(a.min(b)), f64::min)
+            }),
+            ("max", 2, |args| {
+                binary(args, |a, b| Some(a.max(b)), f64::max)
+            }),
+        ];
+        for (word, arity, function) in builtins {
+            calculator.register(word, arity, function);
+        }
+        calculator
+    }
+
+    /// Current mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Changes the mode. Variables keep their values and are converted
+    /// when used.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Adds or replaces the operator `word`, which pops `arity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `word` is empty, contains whitespace, starts with `=` or
+    /// is one of the stack words `dup`, `swap` and `drop`.
+    pub fn register(&mut self, word: impl Into<String>, arity: usize, function: OperatorFn) {
+        let word = word.into();
+        assert!(
+            !word.is_empty()
+                && !word.contains(char::is_whitespace)
+                && !word.starts_with('=')
+                && !STACK_WORDS.contains(&word.as_str()),
+            "`{word}` cannot name an operator"
+        );
+        self.operators.insert(word, Operator { arity, function });
+    }
+
+    /// Removes the operator `word`, returning `true` if it existed.
+    pub fn unregister(&mut self, word: &str) -> bool {
+        self.operators.remove(word).is_some()
+    }
+
+    /// Sets variable `name`, returning its previous value.
+    pub fn set_variable(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<Number>,
+    ) -> Option<Number> {
+        self.variables.insert(name.into(), value.into())
+    }
+
+    /// Value of variable `name`.
+    pub fn variable(&self, name: &str) -> Option<Number> {
+        self.variables.get(name).copied()
+    }
+
+    /// Removes variable `name`, returning its value.
+    pub fn remove_variable(&mut self, name: &str) -> Option<Number> {
+        self.variables.remove(name)
+    }
+
+    /// Evaluates a postfix expression of whitespace-separated tokens.
+    pub fn evaluate(&mut self, expression: &str) -> Result<Number, RpnError> {
+        let tokens: Vec<Token> = expression
+            .split_whitespace()
+            .map(|word| match parse_number(word) {
+                Some(value) => Token::Number(value),
+                None => Token::Word(word.to_owned()),
+            })
+            .collect();
+        self.evaluate_tokens(&tokens)
+    }
+
+    /// Converts an infix expression with [`to_rpn`] and evaluates it.
+    pub fn evaluate_infix(&mut self, expression: &str) -> Result<Number, ExpressionError> {
+        let tokens = to_rpn(expression)?;
+        Ok(self.evaluate_tokens(&tokens)?)
+    }
+
+    /// Evaluates a postfix token sequence.
+    pub fn evaluate_tokens(&mut self, tokens: &[Token]) -> Result<Number, RpnError> {
+        self.stack.clear();
+        for (index, token) in tokens.iter().enumerate() {
+            self.step(token).map_err(|kind| RpnError { index, kind })?;
+        }
+        match self.stack.len() {
+            1 => Ok(self.stack.pop().expect("one value is left")),
+            count => Err(RpnError {
+                index: tokens.len(),
+                kind: RpnErrorKind::Unbalanced(count),
+            }),
+        }
+    }
+
+    fn step(&mut self, token: &Token) -> Result<(), RpnErrorKind> {
+        let (word, arity) = match token {
+            Token::Number(value) => return self.push(*value),
+            Token::Word(word) => (word.as_str(), None),
+            Token::Call { name, arity } => (name.as_str(), Some(*arity)),
+        };
+        if let Some(operator) = self.operators.get(word).copied() {
+            if let Some(found) = arity.filter(|&found| found != operator.arity) {
+                return Err(RpnErrorKind::Arity {
+                    expected: operator.arity,
+                    found,
+                });
+            }
+            if self.stack.len() < operator.arity {
+                return Err(RpnErrorKind::Underflow);
+            }
+            // `iter` runs top first; operators want the bottom argument first.
+            let mut args: Vec<Number> = self.stack.iter().take(operator.arity).copied().collect();
+            args.reverse();
+            let result = (operator.function)(&args)?;
+            for _ in 0..operator.arity {
+                self.pop()?;
+            }
+            return self.push(result);
+        }
+        if arity.is_some() {
+            return Err(RpnErrorKind::UnknownToken(word.to_owned()));
+        }
+        match word {
+            "dup" => {
+                let top = *self.stack.peek().map_err(|_| RpnErrorKind::Underflow)?;
+                self.stack.push(top);
+            }
+            "swap" => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(b);
+                self.stack.push(a);
+            }
+            "drop" => {
+                self.pop()?;
+            }
+            _ => {
+                if let Some(name) = word.strip_prefix('=').filter(|name| !name.is_empty()) {
+                    let value = self.pop()?;
+                    self.variables.insert(name.to_owned(), value);
+                } else if let Some(value) = self.variables.get(word).copied() {
+                    self.push(value)?;
+                } else {
+                    return Err(RpnErrorKind::UnknownToken(word.to_owned()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, value: Number) -> Result<(), RpnErrorKind> {
+        self.stack.push(self.mode.coerce(value)?);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Number, RpnErrorKind> {
+        self.stack.pop().map_err(|_| RpnErrorKind::Underflow)
+    }
+}
+
+impl Default for RpnCalculator {
+    fn default() -> Self {
+        RpnCalculator::new()
+    }
+}
+
+/// Applies a binary operator, in integers if both arguments are integers.
+fn binary(
+    args: &[Number],
+    int: fn(i64, i64) -> Option<i64>,
+    float: fn(f64, f64) -> f64,
+) -> Result<Number, RpnErrorKind> {
+    match (args[0], args[1]) {
+        (Number::Int(a), Number::Int(b)) => {
+            int(a, b).map(Number::Int).ok_or(RpnErrorKind::Overflow)
+        }
+        (a, b) => Ok(Number::Float(float(a.to_f64(), b.to_f64()))),
+    }
+}
+
+fn add(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    binary(args, i64::checked_add, |a, b| a + b)
+}
+
+fn subtract(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    binary(args, i64::checked_sub, |a, b| a - b)
+}
+
+fn multiply(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    binary(args, i64::checked_mul, |a, b| a * b)
+}
+
+fn divide(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    nonzero(args[1])?;
+    binary(args, i64::checked_div, |a, b| a / b)
+}
+
+fn remainder(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    nonzero(args[1])?;
+    binary(args, i64::checked_rem, |a, b| a % b)
+}
+
+fn negate(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    match args[0] {
+        Number::Int(a) => a
+            .checked_neg()
+            .map(Number::Int)
+            .ok_or(RpnErrorKind::Overflow),
+        Number::Float(a) => Ok(Number::Float(-a)),
+    }
+}
+
+fn nonzero(divisor: Number) -> Result<(), RpnErrorKind> {
+    if divisor.is_zero() {
+        return Err(RpnErrorKind::DivisionByZero);
+    }
+    Ok(())
+}
+
+fn power(args: &[Number]) -> Result<Number, RpnErrorKind> {
+    match (args[0], args[1]) {
+        (Number::Int(_), Number::Int(exponent)) if exponent < 0 => {
+            Err(RpnErrorKind::InvalidArgument)
+        }
+        (Number::Int(base), Number::Int(exponent)) => u32::try_from(exponent)
+            .ok()
+            .and_then(|exponent| base.checked_pow(exponent))
+            .map(Number::Int)
+            .ok_or(RpnErrorKind::Overflow),
+        (a, b) => Ok(Number::Float(a.to_f64().powf(b.to_f64()))),
+    }
+}
+
+/// Parses a decimal literal like `-1.5` or `42`, as an integer if it has no
+/// fraction and fits. Unlike `str::parse`, rejects `inf` and `NaN`, which
+/// would otherwise shadow words of the same name.
+fn parse_number(word: &str) -> Option<Number> {
+    let digits = word.strip_prefix('-').unwrap_or(word);
+    if !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    match word.parse() {
+        Ok(value) => Some(Number::Int(value)),
+        Err(_) => word.parse().ok().map(Number::Float),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [Mode; 2] = [Mode::Integer, Mode::Float];
+
+    fn error(mode: Mode, expression: &str) -> (usize, RpnErrorKind) {
+        let error = RpnCalculator::with_mode(mode)
+            .evaluate(expression)
+            .unwrap_err();
+        (error.index, error.kind)
+    }
+
+    fn infix_error(mode: Mode, expression: &str) -> (usize, RpnErrorKind) {
+        match RpnCalculator::with_mode(mode).evaluate_infix(expression) {
+            Err(ExpressionError::Evaluate(error)) => (error.index, error.kind),
+            other => panic!("{expression}: {other:?}"),
+        }
+    }
+
+    fn call(name: &str, arity: usize) -> Token {
+        Token::Call {
+            name: name.to_owned(),
+            arity,
+        }
+    }
+
+    #[test]
+    fn evaluates_in_each_mode() {
+        let mut calculator = RpnCalculator::with_mode(Mode::Integer);
+        assert_eq!(calculator.evaluate("7 2 /"), Ok(Number::Int(3)));
+        assert_eq!(calculator.evaluate("-7 2 %"), Ok(Number::Int(-1)));
+        assert_eq!(calculator.evaluate("2 10 ^ 4.0 -"), Ok(Number::Int(1020)));
+        assert_eq!(calculator.evaluate("17 sqrt"), Ok(Number::Int(4)));
+        calculator.set_mode(Mode::Float);
+        assert_eq!(calculator.evaluate("7 2 /"), Ok(Number::Float(3.5)));
+        assert_eq!(
+            calculator.evaluate("16 0.5 ^ dup *"),
+            Ok(Number::Float(16.0))
+        );
+        assert_eq!(
+            calculator.evaluate("1 2 swap - 3 drop"),
+            Ok(Number::Float(1.0))
+        );
+    }
+
+    #[test]
+    fn underflow_names_the_token() {
+        for mode in MODES {
+            assert_eq!(error(mode, "+"), (0, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "1 +"), (1, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "1 2 + *"), (3, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "dup"), (0, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "1 swap"), (1, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "1 drop drop"), (2, RpnErrorKind::Underflow));
+            assert_eq!(error(mode, "=x"), (0, RpnErrorKind::Underflow));
+        }
+    }
+
+    #[test]
+    fn division_by_zero_names_the_token() {
+        for mode in MODES {
+            assert_eq!(error(mode, "1 0 /"), (2, RpnErrorKind::DivisionByZero));
+            assert_eq!(error(mode, "5 2 2 - %"), (4, RpnErrorKind::DivisionByZero));
+            assert_eq!(error(mode, "1 0.0 /"), (2, RpnErrorKind::DivisionByZero));
+            assert_eq!(
+                infix_error(mode, "1 + 4 / (2 - 2)"),
+                (5, RpnErrorKind::DivisionByZero)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_tokens() {
+        let unknown = |word: &str| RpnErrorKind::UnknownToken(word.to_owned());
+        for mode in MODES {
+            assert_eq!(error(mode, "1 foo +"), (1, unknown("foo")));
+            assert_eq!(error(mode, "inf"), (0, unknown("inf")));
+            assert_eq!(error(mode, "1 ="), (1, unknown("=")));
+            let mut calculator = RpnCalculator::with_mode(mode);
+            let tokens = [Token::Number(Number::Int(1)), call("nope", 1)];
+            assert_eq!(
+                calculator.evaluate_tokens(&tokens),
+                Err(RpnError {
+                    index: 1,
+                    kind: unknown("nope")
+                })
+            );
+            // Stack words are not functions.
+            assert_eq!(infix_error(mode, "dup(1)"), (1, unknown("dup")));
+        }
+    }
+
+    #[test]
+    fn calls_with_the_wrong_arity() {
+        for mode in MODES {
+            assert_eq!(
+                infix_error(mode, "max(1)"),
+                (
+                    1,
+                    RpnErrorKind::Arity {
+                        expected: 2,
+                        found: 1
+                    }
+                )
+            );
+            assert_eq!(
+                infix_error(mode, "2 * neg(1, 2)"),
+                (
+                    3,
+                    RpnErrorKind::Arity {
+                        expected: 1,
+                        found: 2
+                    }
+                )
+            );
+            assert_eq!(
+                infix_error(mode, "abs()"),
+                (
+                    0,
+                    RpnErrorKind::Arity {
+                        expected: 1,
+                        found: 0
+                    }
+                )
+            );
+            // Postfix words carry no arity, so only the stack is checked.
+            assert_eq!(error(mode, "1 max"), (1, RpnErrorKind::Underflow));
+        }
+    }
+
+    #[test]
+    fn unbalanced_results_point_past_the_end() {
+        for mode in MODES {
+            assert_eq!(error(mode, ""), (0, RpnErrorKind::Unbalanced(0)));
+            assert_eq!(error(mode, "1 2"), (2, RpnErrorKind::Unbalanced(2)));
+            assert_eq!(error(mode, "1 2 3 +"), (4, RpnErrorKind::Unbalanced(2)));
+            assert_eq!(error(mode, "1 =x"), (2, RpnErrorKind::Unbalanced(0)));
+        }
+    }
+
+    #[test]
+    fn integer_mode_errors() {
+        let mode = Mode::Integer;
+        assert_eq!(error(mode, "1 1.5 +"), (1, RpnErrorKind::NotAnInteger));
+        assert_eq!(error(mode, "2 0.5 /"), (1, RpnErrorKind::NotAnInteger));
+        assert_eq!(
+            error(mode, "9223372036854775807 1 +"),
+            (2, RpnErrorKind::Overflow)
+        );
+        assert_eq!(error(mode, "1e19"), (0, RpnErrorKind::Overflow));
+        assert_eq!(error(mode, "2 -1 ^"), (2, RpnErrorKind::InvalidArgument));
+        assert_eq!(error(mode, "-4 sqrt"), (1, RpnErrorKind::InvalidArgument));
+        assert_eq!(
+            error(Mode::Float, "-4 sqrt"),
+            (1, RpnErrorKind::InvalidArgument)
+        );
+        assert_eq!(
+            RpnCalculator::new().evaluate("2 -1 ^"),
+            Ok(Number::Float(0.5))
+        );
+    }
+
+    #[test]
+    fn errors_leave_the_calculator_usable() {
+        let mut calculator = RpnCalculator::with_mode(Mode::Integer);
+        calculator.set_variable("x", 4);
+        assert!(calculator.evaluate("1 2 x 0 /").is_err());
+        assert_eq!(calculator.evaluate("x 1 +"), Ok(Number::Int(5)));
+        assert_eq!(calculator.evaluate("x 3 * =y y"), Ok(Number::Int(12)));
+        assert_eq!(calculator.variable("y"), Some(Number::Int(12)));
+        let error = calculator.evaluate("1 +").unwrap_err();
+        assert_eq!(error.to_string(), "token 1: not enough operands");
+        let error = calculator.evaluate_infix("max(1)").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "evaluation failed: token 1: takes 2 arguments but was given 1"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! Infix to postfix conversion with the shunting-yard algorithm.
+//!
+//! Operands go straight to the output; operators wait on a stack until an
+//! operator that binds less tightly, a closing parenthesis or the end of the
+//! input pushes them out.
+//!
+//! | Operator          | Precedence | Associativity |
+//! |-------------------|------------|---------------|
+//! | `+ -`             | 1          | left          |
+//! | `* / %`           | 2          | left          |
+//! | unary `-` (`neg`) | 3          | right         |
+//! | `^`               | 4          | right         |
+//!
+//! So `-2 ^ 2` is `-(2 ^ 2)` and `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`. A name
+//! followed by `(` is a function call whose arguments are separated by
+//! commas; any other name is passed through as a word.
+
+use std::error::Error;
+use std::fmt;
+
+use super::{parse_number, Token};
+use crate::linear::stack::{ArrayStack, Stack};
+
+/// Why an infix expression did not parse, and at which character (counted
+/// from 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InfixError {
+    pub position: usize,
+    pub kind: InfixErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InfixErrorKind {
+    /// A character that starts no token.
+    UnexpectedCharacter,
+    /// An operand where an operator was expected, as in `2 3`.
+    UnexpectedOperand,
+    /// An operator, `)`, `,` or the end where an operand was expected.
+    MissingOperand,
+    /// A `)` without a matching `(`.
+    UnmatchedClose,
+    /// A `(` that is never closed.
+    UnclosedParen,
+    /// A `,` outside a function call's parentheses.
+    MisplacedComma,
+}
+
+impl fmt::Display for InfixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            InfixErrorKind::UnexpectedCharacter => "unexpected character",
+            InfixErrorKind::UnexpectedOperand => "expected an operator",
+            InfixErrorKind::MissingOperand => "expected an operand",
+            InfixErrorKind::UnmatchedClose => "`)` without matching `(`",
+            InfixErrorKind::UnclosedParen => "`(` is never closed",
+            InfixErrorKind::MisplacedComma => "`,` outside a function call",
+        };
+        write!(f, "column {}: {message}", self.position)
+    }
+}
+
+impl Error for InfixError {}
+
+/// An entry waiting on the operator stack.
+enum Pending {
+    Operator {
+        word: &'static str,
+        precedence: u8,
+    },
+    Paren {
+        position: usize,
+        /// Set for a call: the function name and arguments seen so far.
+        call: Option<(String, usize)>,
+    },
+}
+
+/// Converts an infix expression to postfix tokens.
+///
+/// ```
+/// use data_structure::linear::rpn::{to_rpn, Token};
+///
+/// let text: Vec<String> = to_rpn("3 + 4 * -max(1, 2)")
+///     .unwrap()
+///     .iter()
+///     .map(Token::to_string)
+///     .collect();
+/// assert_eq!(text, ["3", "4", "1", "2", "max", "neg", "*", "+"]);
+/// ```
+pub fn to_rpn(expression: &str) -> Result<Vec<Token>, InfixError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut output = Vec::new();
+    let mut pending: ArrayStack<Pending> = ArrayStack::new();
+    let mut expect_operand = true;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let error = |kind| InfixError { position: i, kind };
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' || c.is_alphabetic() || c == '_' {
+            if !expect_operand {
+                return Err(error(InfixErrorKind::UnexpectedOperand));
+            }
+            let start = i;
+            let number = !c.is_alphabetic() && c != '_';
+            while i < chars.len()
+                && (chars[i] == '.' && number
+                    || chars[i].is_ascii_digit()
+                    || !number && (chars[i].is_alphanumeric() || chars[i] == '_'))
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            if number {
+                let value = parse_number(&text).ok_or(InfixError {
+                    position: start,
+                    kind: InfixErrorKind::UnexpectedCharacter,
+                })?;
+                output.push(Token::Number(value));
+                expect_operand = false;
+            } else if chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'(') {
+                i = start
+                    + chars[start..]
+                        .iter()
+                        .position(|&c| c == '(')
+                        .expect("found");
+                pending.push(Pending::Paren {
+                    position: i,
+                    call: Some((text, 0)),
+                });
+                i += 1;
+            } else {
+                output.push(Token::Word(text));
+                expect_operand = false;
+            }
+            continue;
+        }
+        match c {
+            '(' => {
+                if !expect_operand {
+                    return Err(error(InfixErrorKind::UnexpectedOperand));
+                }
+                pending.push(Pending::Paren {
+                    position: i,
+                    call: None,
+                });
+            }
+            '+' | '-' if expect_operand => {
+                // Unary plus changes nothing.
+                if c == '-' {
+                    pending.push(Pending::Operator {
+                        word: "neg",
+                        precedence: 3,
+                    });
+                }
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                if expect_operand {
+                    return Err(error(InfixErrorKind::MissingOperand));
+                }
+                let (word, precedence, right) = match c {
+                    '+' => ("+", 1, false),
+                    '-' => ("-", 1, false),
+                    '*' => ("*", 2, false),
+                    '/' => ("/", 2, false),
+                    '%' => ("%", 2, false),
+                    _ => ("^", 4, true),
+                };
+                while let Ok(Pending::Operator {
+                    precedence: top, ..
+                }) = pending.peek()
+                {
+                    if *top < precedence || *top == precedence && right {
+                        break;
+                    }
+                    pop_operator(&mut pending, &mut output);
+                }
+                pending.push(Pending::Operator { word, precedence });
+                expect_operand = true;
+            }
+            ',' => {
+                if expect_operand {
+                    return Err(error(InfixErrorKind::MissingOperand));
+                }
+                pop_operators(&mut pending, &mut output);
+                match pending.peek_mut() {
+                    Ok(Pending::Paren {
+                        call: Some((_, arguments)),
+                        ..
+                    }) => *arguments += 1,
+                    _ => return Err(error(InfixErrorKind::MisplacedComma)),
+                }
+                expect_operand = true;
+            }
+            ')' => {
+                let empty_call = expect_operand
+                    && matches!(
+                        pending.peek(),
+                        Ok(Pending::Paren {
+                            call: Some((_, 0)),
+                            ..
+                        })
+                    );
+                if expect_operand && !empty_call {
+                    return Err(error(InfixErrorKind::MissingOperand));
+                }
+                pop_operators(&mut pending, &mut output);
+                match pending.pop() {
+                    Ok(Pending::Paren {
+                        call: Some((name, arguments)),
+                        ..
+                    }) => output.push(Token::Call {
+                        name,
+                        arity: if empty_call { 0 } else { arguments + 1 },
+                    }),
+                    Ok(_) => {}
+                    Err(_) => return Err(error(InfixErrorKind::UnmatchedClose)),
+                }
+                expect_operand = false;
+            }
+            _ => return Err(error(InfixErrorKind::UnexpectedCharacter)),
+        }
+        i += 1;
+    }
+    if expect_operand {
+        return Err(InfixError {
+            position: chars.len(),
+            kind: InfixErrorKind::MissingOperand,
+        });
+    }
+    pop_operators(&mut pending, &mut output);
+    if let Ok(Pending::Paren { position, .. }) = pending.peek() {
+        return Err(InfixError {
+            position: *position,
+            kind: InfixErrorKind::UnclosedParen,
+        });
+    }
+    Ok(output)
+}
+
+/// Moves operators to the output down to the nearest parenthesis.
+fn pop_operators(pending: &mut ArrayStack<Pending>, output: &mut Vec<Token>) {
+    while let Ok(Pending::Operator { .. }) = pending.peek() {
+        pop_operator(pending, output);
+    }
+}
+
+fn pop_operator(pending: &mut ArrayStack<Pending>, output: &mut Vec<Token>) {
+    if let Ok(Pending::Operator { word, .. }) = pending.pop() {
+        output.push(Token::Word(word.to_owned()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear::rpn::{Number, RpnCalculator};
+
+    fn postfix(expression: &str) -> String {
+        let tokens = to_rpn(expression).unwrap();
+        let words: Vec<String> = tokens.iter().map(Token::to_string).collect();
+        words.join(" ")
+    }
+
+    fn calls(expression: &str) -> Vec<(String, usize)> {
+        to_rpn(expression)
+            .unwrap()
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Call { name, arity } => Some((name, arity)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn error(expression: &str) -> (usize, InfixErrorKind) {
+        let error = to_rpn(expression).unwrap_err();
+        (error.position, error.kind)
+    }
+
+    fn evaluate(expression: &str) -> f64 {
+        match RpnCalculator::new().evaluate_infix(expression) {
+            Ok(Number::Float(value)) => value,
+            other => panic!("{expression}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(postfix("1 + 2 * 3"), "1 2 3 * +");
+        assert_eq!(postfix("(1 + 2) * 3"), "1 2 + 3 *");
+        assert_eq!(postfix("8 - 4 - 2"), "8 4 - 2 -");
+        assert_eq!(postfix("8 / 4 % 3 * 2"), "8 4 / 3 % 2 *");
+        assert_eq!(postfix("2 ^ 3 ^ 2"), "2 3 2 ^ ^");
+        assert_eq!(postfix("+x - -y"), "x y neg -");
+    }
+
+    #[test]
+    fn unary_minus_binds_looser_than_power() {
+        assert_eq!(postfix("-2^2"), "2 2 ^ neg");
+        assert_eq!(postfix("2^-3"), "2 3 neg ^");
+        assert_eq!(postfix("2*-3+1"), "2 3 neg * 1 +");
+        assert_eq!(postfix("--2"), "2 neg neg");
+        assert_eq!(postfix("2^-3^2"), "2 3 2 ^ neg ^");
+        assert_eq!(evaluate("-2^2"), -4.0);
+        assert_eq!(evaluate("(-2)^2"), 4.0);
+        assert_eq!(evaluate("2^-3"), 0.125);
+        assert_eq!(evaluate("2*-3+1"), -5.0);
+        assert_eq!(evaluate("2^3^2"), 512.0);
+    }
+
+    #[test]
+    fn function_calls() {
+        assert_eq!(postfix("max(1, 2)"), "1 2 max");
+        assert_eq!(calls("f()"), [("f".to_owned(), 0)]);
+        assert_eq!(calls("f ( 1 )"), [("f".to_owned(), 1)]);
+        assert_eq!(postfix("g(f(), h(1, 2 + 3), -4)"), "f 1 2 3 + h 4 neg g");
+        assert_eq!(
+            calls("g(f(), h(1, 2 + 3), -4)"),
+            [
+                ("f".to_owned(), 0),
+                ("h".to_owned(), 2),
+                ("g".to_owned(), 3)
+            ]
+        );
+        assert_eq!(
+            postfix("2 * max((1), min(3, 4)) ^ 2"),
+            "2 1 3 4 min max 2 ^ *"
+        );
+        assert_eq!(evaluate("max(1, min(5, 3)) * abs(-2)"), 6.0);
+        assert_eq!(postfix("pi * r_2"), "pi r_2 *");
+    }
+
+    #[test]
+    fn misplaced_operands_and_operators() {
+        use InfixErrorKind::*;
+        assert_eq!(error(""), (0, MissingOperand));
+        assert_eq!(error("   "), (3, MissingOperand));
+        assert_eq!(error("2 3"), (2, UnexpectedOperand));
+        assert_eq!(error("2 (3)"), (2, UnexpectedOperand));
+        assert_eq!(error("max(1, 2)(3)"), (9, UnexpectedOperand));
+        assert_eq!(error("x y"), (2, UnexpectedOperand));
+        assert_eq!(error("2 +"), (3, MissingOperand));
+        assert_eq!(error("2 * / 3"), (4, MissingOperand));
+        assert_eq!(error("* 2"), (0, MissingOperand));
+        assert_eq!(error("2 $ 3"), (2, UnexpectedCharacter));
+        assert_eq!(error("1.2.3"), (0, UnexpectedCharacter));
+    }
+
+    #[test]
+    fn malformed_calls() {
+        use InfixErrorKind::*;
+        assert_eq!(error("f(1,)"), (4, MissingOperand));
+        assert_eq!(error("f(,1)"), (2, MissingOperand));
+        assert_eq!(error("f(1,,2)"), (4, MissingOperand));
+        assert_eq!(error("f(1"), (1, UnclosedParen));
+        assert_eq!(error("1, 2"), (1, MisplacedComma));
+        assert_eq!(error("(1, 2)"), (2, MisplacedComma));
+        assert_eq!(error("f((1, 2))"), (4, MisplacedComma));
+    }
+
+    #[test]
+    fn unbalanced_parentheses() {
+        use InfixErrorKind::*;
+        assert_eq!(error("(1 + 2"), (0, UnclosedParen));
+        assert_eq!(error("((1 + 2) * 3"), (0, UnclosedParen));
+        assert_eq!(error("(1 + (2"), (5, UnclosedParen));
+        assert_eq!(error("1 + 2)"), (5, UnmatchedClose));
+        assert_eq!(error("(1))"), (3, UnmatchedClose));
+        assert_eq!(error("()"), (1, MissingOperand));
+        assert_eq!(error(")"), (0, MissingOperand));
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        use InfixErrorKind::*;
+        assert_eq!(error("ü+ 2 $"), (5, UnexpectedCharacter));
+        assert_eq!(error("größe 2"), (6, UnexpectedOperand));
+        let error = to_rpn("1 +").unwrap_err();
+        assert_eq!(error.to_string(), "column 3: expected an operand");
+    }
+}
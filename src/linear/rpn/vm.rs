@@ -0,0 +1,976 @@
+//! Bytecode virtual machine for compiled expressions.
+//!
+//! [`RpnCalculator::evaluate`] re-reads its input every time: it splits the
+//! text, parses numbers and looks every word up by name. When the same
+//! expression runs many times with different inputs, that work can be done
+//! once. [`RpnCalculator::compile`] lowers an expression to a [`Program`]:
+//! instructions that refer to constants, variables and operators by index,
+//! checked up front for stack underflow and arity. A [`Vm`] then runs the
+//! program against a slice of variable values.
+//!
+//! Programs can also be assembled by hand with jumps, conditionals and
+//! calls, which expressions have no syntax for. Calls keep their frames on a
+//! [`BoundedStack`], so runaway recursion stops at a fixed depth.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use super::{
+    add, divide, multiply, negate, parse_number, power, remainder, subtract, to_rpn,
+    ExpressionError, Mode, Number, OperatorFn, RpnCalculator, RpnError, RpnErrorKind, Token,
+    STACK_WORDS,
+};
+use crate::linear::stack::{BoundedStack, OverflowPolicy};
+
+/// One VM instruction. Operands are indexes into the program's tables or
+/// code addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// Pushes constant `n`.
+    Const(usize),
+    /// Pushes variable `n`.
+    Load(usize),
+    /// Pops a value into variable `n`.
+    Store(usize),
+    /// Pushes argument `n` of the current call, counted from the first.
+    Arg(usize),
+    Dup,
+    Swap,
+    Drop,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Neg,
+    /// Applies operator `n` of the program's operator table.
+    Operator(usize),
+    /// Pops `b` and `a` and pushes 1 if `a == b`, else 0. Like `Lt` and
+    /// `Gt`, pushes 0 if either is NaN.
+    Eq,
+    /// Pops `b` and `a` and pushes 1 if `a < b`, else 0.
+    Lt,
+    /// Pops `b` and `a` and pushes 1 if `a > b`, else 0.
+    Gt,
+    /// Pops a value and pushes 1 if it is zero, else 0.
+    Not,
+    /// Continues at the address.
+    Jump(usize),
+    /// Pops a value and continues at the address if it is zero.
+    JumpIfZero(usize),
+    /// Calls the code at `target` with the top `arity` values as arguments.
+    Call {
+        target: usize,
+        arity: usize,
+    },
+    /// Pops the result, discards the call's arguments and everything above
+    /// them, pushes the result and continues after the `Call`.
+    Return,
+    /// Stops; the single value left on the stack is the result. Running off
+    /// the end of the code does the same.
+    Halt,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Const(n) => write!(f, "const {n}"),
+            Instruction::Load(n) => write!(f, "load {n}"),
+            Instruction::Store(n) => write!(f, "store {n}"),
+            Instruction::Arg(n) => write!(f, "arg {n}"),
+            Instruction::Dup => f.write_str("dup"),
+            Instruction::Swap => f.write_str("swap"),
+            Instruction::Drop => f.write_str("drop"),
+            Instruction::Add => f.write_str("add"),
+            Instruction::Sub => f.write_str("sub"),
+            Instruction::Mul => f.write_str("mul"),
+            Instruction::Div => f.write_str("div"),
+            Instruction::Rem => f.write_str("rem"),
+            Instruction::Pow => f.write_str("pow"),
+            Instruction::Neg => f.write_str("neg"),
+            Instruction::Operator(n) => write!(f, "op {n}"),
+            Instruction::Eq => f.write_str("eq"),
+            Instruction::Lt => f.write_str("lt"),
+            Instruction::Gt => f.write_str("gt"),
+            Instruction::Not => f.write_str("not"),
+            Instruction::Jump(target) => write!(f, "jump {target}"),
+            Instruction::JumpIfZero(target) => write!(f, "jz {target}"),
+            Instruction::Call { target, arity } => write!(f, "call {target} {arity}"),
+            Instruction::Return => f.write_str("ret"),
+            Instruction::Halt => f.write_str("halt"),
+        }
+    }
+}
+
+/// Instructions with the constants, variable names and operators they
+/// refer to.
+///
+/// Its `Display` is a disassembly listing, one instruction per line with
+/// its address and what its operand refers to.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    code: Vec<Instruction>,
+    constants: Vec<Number>,
+    variables: Vec<String>,
+    operators: Vec<(String, usize, OperatorFn)>,
+}
+
+impl Program {
+    /// Creates an empty program.
+    pub fn new() -> Self {
+        Program::default()
+    }
+
+    /// Appends `instruction` and returns its address.
+    pub fn emit(&mut self, instruction: Instruction) -> usize {
+        self.code.push(instruction);
+        self.code.len() - 1
+    }
+
+    /// Replaces the instruction at `address`, to fill in a forward jump.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `address` is out of range.
+    pub fn patch(&mut self, address: usize, instruction: Instruction) {
+        self.code[address] = instruction;
+    }
+
+    /// Address the next emitted instruction will get.
+    pub fn next_address(&self) -> usize {
+        self.code.len()
+    }
+
+    /// Index of `value` in the constant pool, adding it if new.
+    pub fn constant(&mut self, value: Number) -> usize {
+        match self.constants.iter().position(|&c| c == value) {
+            Some(index) => index,
+            None => {
+                self.constants.push(value);
+                self.constants.len() - 1
+            }
+        }
+    }
+
+    /// Slot of variable `name`, adding it if new.
+    pub fn variable(&mut self, name: &str) -> usize {
+        match self.slot(name) {
+            Some(index) => index,
+            None => {
+                self.variables.push(name.to_owned());
+                self.variables.len() - 1
+            }
+        }
+    }
+
+    /// Index of operator `name` in the operator table, adding it if new.
+    pub fn operator(&mut self, name: &str, arity: usize, function: OperatorFn) -> usize {
+        match self.operators.iter().position(|(known, ..)| known == name) {
+            Some(index) => index,
+            None => {
+                self.operators.push((name.to_owned(), arity, function));
+                self.operators.len() - 1
+            }
+        }
+    }
+
+    /// Slot of variable `name`, if the program uses it.
+    pub fn slot(&self, name: &str) -> Option<usize> {
+        self.variables.iter().position(|known| known == name)
+    }
+
+    /// The instructions.
+    pub fn code(&self) -> &[Instruction] {
+        &self.code
+    }
+
+    /// The constant pool.
+    pub fn constants(&self) -> &[Number] {
+        &self.constants
+    }
+
+    /// Variable names, by slot.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (address, instruction) in self.code.iter().enumerate() {
+            let text = instruction.to_string();
+            let note = match *instruction {
+                Instruction::Const(n) => self.constants.get(n).map(Number::to_string),
+                Instruction::Load(n) | Instruction::Store(n) => self.variables.get(n).cloned(),
+                Instruction::Operator(n) => self.operators.get(n).map(|(name, ..)| name.clone()),
+                _ => None,
+            };
+            match note {
+                Some(note) => writeln!(f, "{address:04}  {text:<12} ; {note}")?,
+                None => writeln!(f, "{address:04}  {text}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What stopped a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmErrorKind {
+    /// An operation failed, as it would have in [`RpnCalculator`].
+    Operation(RpnErrorKind),
+    /// A `Call` would exceed the call-depth limit.
+    CallDepthExceeded,
+    /// A `Return` outside any call.
+    ReturnWithoutCall,
+    /// An operand that refers to no constant, variable, operator, argument
+    /// or address.
+    InvalidOperand,
+}
+
+/// A run-time failure at instruction address `pc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VmError {
+    pub pc: usize,
+    pub kind: VmErrorKind,
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {:04}: ", self.pc)?;
+        match &self.kind {
+            VmErrorKind::Operation(kind) => write!(f, "{kind}"),
+            VmErrorKind::CallDepthExceeded => f.write_str("call depth exceeded"),
+            VmErrorKind::ReturnWithoutCall => f.write_str("return outside a call"),
+            VmErrorKind::InvalidOperand => f.write_str("invalid operand"),
+        }
+    }
+}
+
+impl Error for VmError {}
+
+impl From<RpnErrorKind> for VmErrorKind {
+    fn from(kind: RpnErrorKind) -> Self {
+        VmErrorKind::Operation(kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    return_to: usize,
+    /// Stack height below the call's arguments.
+    base: usize,
+    /// Number of arguments the call was made with.
+    arity: usize,
+}
+
+/// A stack machine that runs [`Program`]s.
+///
+/// Like [`RpnCalculator`], it computes in the [`Mode`] it was created with.
+/// The operand stack is a plain vector, since calls read their arguments
+/// by position; call frames live on a [`BoundedStack`].
+///
+/// ```
+/// use data_structure::linear::rpn::vm::{Instruction, Program, Vm};
+/// use data_structure::linear::rpn::{Mode, Number, RpnCalculator};
+///
+/// let calculator = RpnCalculator::with_mode(Mode::Integer);
+/// let program = calculator.compile_infix("x * x + max(y, 1)").unwrap();
+/// let mut vm = Vm::new(Mode::Integer, 64);
+/// let mut variables = vec![Number::Int(0); program.variables().len()];
+/// for x in 0..3 {
+///     variables[program.slot("x").unwrap()] = Number::Int(x);
+///     variables[program.slot("y").unwrap()] = Number::Int(5);
+///     assert_eq!(vm.run(&program, &mut variables), Ok(Number::Int(x * x + 5)));
+/// }
+///
+/// // Factorial by recursion: fact(n) = n < 2 ? 1 : n * fact(n - 1).
+/// let mut program = Program::new();
+/// let (n, one) = (program.constant(Number::Int(5)), program.constant(Number::Int(1)));
+/// program.emit(Instruction::Const(n));
+/// program.emit(Instruction::Call { target: 3, arity: 1 });
+/// program.emit(Instruction::Halt);
+/// program.emit(Instruction::Arg(0));
+/// program.emit(Instruction::Const(one));
+/// program.emit(Instruction::Gt);
+/// program.emit(Instruction::JumpIfZero(14));
+/// program.emit(Instruction::Arg(0));
+/// program.emit(Instruction::Arg(0));
+/// program.emit(Instruction::Const(one));
+/// program.emit(Instruction::Sub);
+/// program.emit(Instruction::Call { target: 3, arity: 1 });
+/// program.emit(Instruction::Mul);
+/// program.emit(Instruction::Return);
+/// program.emit(Instruction::Const(one));
+/// program.emit(Instruction::Return);
+/// assert_eq!(vm.run(&program, &mut []), Ok(Number::Int(120)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Vm {
+    stack: Vec<Number>,
+    frames: BoundedStack<Frame>,
+    mode: Mode,
+}
+
+impl Vm {
+    /// Creates a VM computing in `mode` that allows calls nested
+    /// `max_depth` deep.
+    pub fn new(mode: Mode, max_depth: usize) -> Self {
+        Vm {
+            stack: Vec::new(),
+            frames: BoundedStack::new(max_depth, OverflowPolicy::Error),
+            mode,
+        }
+    }
+
+    /// Current mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Runs `program` with `variables` indexed by the program's slots, and
+    /// returns the single value left on the stack. `Store` instructions
+    /// write back into `variables`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variables` is shorter than [`Program::variables`].
+    pub fn run(&mut self, program: &Program, variables: &mut [Number]) -> Result<Number, VmError> {
+        assert!(
+            variables.len() >= program.variables.len(),
+            "program uses {} variables but {} were given",
+            program.variables.len(),
+            variables.len()
+        );
+        self.stack.clear();
+        self.frames.clear();
+        let mut pc = 0;
+        loop {
+            let instruction = program.code.get(pc).copied().unwrap_or(Instruction::Halt);
+            match self.step(program, variables, instruction, pc) {
+                Ok(Some(next)) => pc = next,
+                Ok(None) => break,
+                Err(kind) => return Err(VmError { pc, kind }),
+            }
+        }
+        match self.stack.len() {
+            1 => Ok(self.stack[0]),
+            count => Err(VmError {
+                pc,
+                kind: VmErrorKind::Operation(RpnErrorKind::Unbalanced(count)),
+            }),
+        }
+    }
+
+    /// Executes one instruction and returns the next address, or `None` to
+    /// stop.
+    fn step(
+        &mut self,
+        program: &Program,
+        variables: &mut [Number],
+        instruction: Instruction,
+        pc: usize,
+    ) -> Result<Option<usize>, VmErrorKind> {
+        let jump = |target: usize| {
+            if target > program.code.len() {
+                return Err(VmErrorKind::InvalidOperand);
+            }
+            Ok(Some(target))
+        };
+        match instruction {
+            Instruction::Const(n) => {
+                let value = *program
+                    .constants
+                    .get(n)
+                    .ok_or(VmErrorKind::InvalidOperand)?;
+                self.push(value)?;
+            }
+            Instruction::Load(n) => {
+                let value = *variables.get(n).ok_or(VmErrorKind::InvalidOperand)?;
+                self.push(value)?;
+            }
+            Instruction::Store(n) => {
+                let value = self.pop()?;
+                *variables.get_mut(n).ok_or(VmErrorKind::InvalidOperand)? = value;
+            }
+            Instruction::Arg(n) => {
+                let frame = self
+                    .frames
+                    .peek()
+                    .map_err(|_| VmErrorKind::InvalidOperand)?;
+                if n >= frame.arity {
+                    return Err(VmErrorKind::InvalidOperand);
+                }
+                // The callee may have dropped its own arguments.
+                let value = *self
+                    .stack
+                    .get(frame.base + n)
+                    .ok_or(RpnErrorKind::Underflow)?;
+                self.stack.push(value);
+            }
+            Instruction::Dup => {
+                let top = *self.stack.last().ok_or(RpnErrorKind::Underflow)?;
+                self.stack.push(top);
+            }
+            Instruction::Swap => {
+                let len = self.stack.len();
+                if len < 2 {
+                    return Err(RpnErrorKind::Underflow.into());
+                }
+                self.stack.swap(len - 1, len - 2);
+            }
+            Instruction::Drop => {
+                self.pop()?;
+            }
+            Instruction::Add => self.apply(2, add)?,
+            Instruction::Sub => self.apply(2, subtract)?,
+            Instruction::Mul => self.apply(2, multiply)?,
+            Instruction::Div => self.apply(2, divide)?,
+            Instruction::Rem => self.apply(2, remainder)?,
+            Instruction::Pow => self.apply(2, power)?,
+            Instruction::Neg => self.apply(1, negate)?,
+            Instruction::Operator(n) => {
+                let &(_, arity, function) = program
+                    .operators
+                    .get(n)
+                    .ok_or(VmErrorKind::InvalidOperand)?;
+                self.apply(arity, function)?;
+            }
+            Instruction::Eq => self.apply(2, |args| Ok(compare(args, Ordering::Equal)))?,
+            Instruction::Lt => self.apply(2, |args| Ok(compare(args, Ordering::Less)))?,
+            Instruction::Gt => self.apply(2, |args| Ok(compare(args, Ordering::Greater)))?,
+            Instruction::Not => self.apply(1, |args| Ok(truth(args[0].is_zero())))?,
+            Instruction::Jump(target) => return jump(target),
+            Instruction::JumpIfZero(target) => {
+                if self.pop()?.is_zero() {
+                    return jump(target);
+                }
+            }
+            Instruction::Call { target, arity } => {
+                let base = self
+                    .stack
+                    .len()
+                    .checked_sub(arity)
+                    .ok_or(RpnErrorKind::Underflow)?;
+                let frame = Frame {
+                    return_to: pc + 1,
+                    base,
+                    arity,
+                };
+                self.frames
+                    .push(frame)
+                    .map_err(|_| VmErrorKind::CallDepthExceeded)?;
+                return jump(target);
+            }
+            Instruction::Return => {
+                let result = self.pop()?;
+                let frame = self
+                    .frames
+                    .pop()
+                    .map_err(|_| VmErrorKind::ReturnWithoutCall)?;
+                self.stack.truncate(frame.base);
+                self.stack.push(result);
+                return Ok(Some(frame.return_to));
+            }
+            Instruction::Halt => return Ok(None),
+        }
+        Ok(Some(pc + 1))
+    }
+
+    /// Pops `arity` arguments, applies `function` and pushes the result.
+    fn apply(&mut self, arity: usize, function: OperatorFn) -> Result<(), VmErrorKind> {
+        let start = self
+            .stack
+            .len()
+            .checked_sub(arity)
+            .ok_or(RpnErrorKind::Underflow)?;
+        let result = function(&self.stack[start..])?;
+        self.stack.truncate(start);
+        self.push(result)
+    }
+
+    fn push(&mut self, value: Number) -> Result<(), VmErrorKind> {
+        self.stack.push(self.mode.coerce(value)?);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Number, VmErrorKind> {
+        Ok(self.stack.pop().ok_or(RpnErrorKind::Underflow)?)
+    }
+}
+
+impl Default for Vm {
+    /// A floating-point VM allowing calls 256 deep.
+    fn default() -> Self {
+        Vm::new(Mode::Float, 256)
+    }
+}
+
+/// Pushes 1 if the two arguments compare as `ordering`, else 0. NaN is
+/// unordered, so every comparison involving it pushes 0, even with itself.
+fn compare(args: &[Number], ordering: Ordering) -> Number {
+    let found = match (args[0], args[1]) {
+        (Number::Int(a), Number::Int(b)) => Some(a.cmp(&b)),
+        (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+    };
+    truth(found == Some(ordering))
+}
+
+fn truth(condition: bool) -> Number {
+    Number::Int(condition.into())
+}
+
+impl RpnCalculator {
+    /// Compiles a postfix expression of whitespace-separated tokens; see
+    /// [`compile_tokens`](Self::compile_tokens).
+    pub fn compile(&self, expression: &str) -> Result<Program, RpnError> {
+        let tokens: Vec<Token> = expression
+            .split_whitespace()
+            .map(|word| match parse_number(word) {
+                Some(value) => Token::Number(value),
+                None => Token::Word(word.to_owned()),
+            })
+            .collect();
+        self.compile_tokens(&tokens)
+    }
+
+    /// Converts an infix expression with [`to_rpn`] and compiles it.
+    pub fn compile_infix(&self, expression: &str) -> Result<Program, ExpressionError> {
+        let tokens = to_rpn(expression)?;
+        Ok(self.compile_tokens(&tokens)?)
+    }
+
+    /// Compiles postfix tokens into a program that computes what
+    /// [`evaluate_tokens`](Self::evaluate_tokens) would.
+    ///
+    /// Operators are resolved against the registry now, so later changes
+    /// to it do not affect the program. Every other word becomes a variable
+    /// slot, whose value is supplied when the program runs. Stack underflow,
+    /// arity mismatches and unbalanced expressions are reported here rather
+    /// than at run time; division by zero and overflow can only show up
+    /// when the program runs.
+    pub fn compile_tokens(&self, tokens: &[Token]) -> Result<Program, RpnError> {
+        let mut program = Program::new();
+        let mut depth = 0;
+        for (index, token) in tokens.iter().enumerate() {
+            let error = |kind| RpnError { index, kind };
+            let (instruction, pops, pushes) = match token {
+                Token::Number(value) => (Instruction::Const(program.constant(*value)), 0, 1),
+                Token::Word(word) | Token::Call { name: word, .. } => {
+                    let arity = match token {
+                        Token::Call { arity, .. } => Some(*arity),
+                        _ => None,
+                    };
+                    if let Some(operator) = self.operators.get(word.as_str()) {
+                        if let Some(found) = arity.filter(|&found| found != operator.arity) {
+                            return Err(error(RpnErrorKind::Arity {
+                                expected: operator.arity,
+                                found,
+                            }));
+                        }
+                        (
+                            lower(&mut program, word, operator.arity, operator.function),
+                            operator.arity,
+                            1,
+                        )
+                    } else if arity.is_some() {
+                        return Err(error(RpnErrorKind::UnknownToken(word.clone())));
+                    } else if STACK_WORDS.contains(&word.as_str()) {
+                        match word.as_str() {
+                            "dup" => (Instruction::Dup, 1, 2),
+                            "swap" => (Instruction::Swap, 2, 2),
+                            _ => (Instruction::Drop, 1, 0),
+                        }
+                    } else if let Some(name) = word.strip_prefix('=').filter(|n| !n.is_empty()) {
+                        (Instruction::Store(program.variable(name)), 1, 0)
+                    } else {
+                        (Instruction::Load(program.variable(word)), 0, 1)
+                    }
+                }
+            };
+            if depth < pops {
+                return Err(error(RpnErrorKind::Underflow));
+            }
+            depth = depth - pops + pushes;
+            program.emit(instruction);
+        }
+        if depth != 1 {
+            return Err(RpnError {
+                index: tokens.len(),
+                kind: RpnErrorKind::Unbalanced(depth),
+            });
+        }
+        program.emit(Instruction::Halt);
+        Ok(program)
+    }
+}
+
+/// Picks the instruction for operator `word`: a dedicated opcode while the
+/// word still has its built-in meaning, a table entry otherwise. Should the
+/// function addresses fail to compare equal, the table entry computes the
+/// same, only slower.
+fn lower(program: &mut Program, word: &str, arity: usize, function: OperatorFn) -> Instruction {
+    let builtins: [(&str, OperatorFn, Instruction); 7] = [
+        ("+", add, Instruction::Add),
+        ("-", subtract, Instruction::Sub),
+        ("*", multiply, Instruction::Mul),
+        ("/", divide, Instruction::Div),
+        ("%", remainder, Instruction::Rem),
+        ("^", power, Instruction::Pow),
+        ("neg", negate, Instruction::Neg),
+    ];
+    for (name, builtin, instruction) in builtins {
+        if name == word && std::ptr::fn_addr_eq(builtin, function) {
+            return instruction;
+        }
+    }
+    Instruction::Operator(program.operator(word, arity, function))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [Mode; 2] = [Mode::Integer, Mode::Float];
+
+    /// Assembles `code` over the constants `values`, `Const(n)` referring to
+    /// `values[n]`.
+    fn program(values: &[Number], code: &[Instruction]) -> Program {
+        let mut program = Program::new();
+        program.constants = values.to_vec();
+        for &instruction in code {
+            program.emit(instruction);
+        }
+        program
+    }
+
+    fn run(vm: &mut Vm, program: &Program) -> Result<Number, (usize, VmErrorKind)> {
+        vm.run(program, &mut [])
+            .map_err(|error| (error.pc, error.kind))
+    }
+
+    /// `f(k) = k == 0 ? 0 : f(k - 1)`, called with `k`; needs `k + 1` frames.
+    fn countdown(k: i64) -> Program {
+        use Instruction::*;
+        let values = [Number::Int(k), Number::Int(1), Number::Int(0)];
+        program(
+            &values,
+            &[
+                Const(0),
+                Call {
+                    target: 3,
+                    arity: 1,
+                },
+                Halt,
+                Arg(0),
+                JumpIfZero(10),
+                Arg(0),
+                Const(1),
+                Sub,
+                Call {
+                    target: 3,
+                    arity: 1,
+                },
+                Return,
+                Const(2),
+                Return,
+            ],
+        )
+    }
+
+    #[test]
+    fn comparisons_with_nan_are_false() {
+        use Instruction::*;
+        let values = [Number::Float(f64::NAN), Number::Float(1.0)];
+        let mut vm = Vm::new(Mode::Float, 4);
+        for (a, b) in [(0, 1), (1, 0), (0, 0)] {
+            for op in [Eq, Lt, Gt] {
+                let program = program(&values, &[Const(a), Const(b), op]);
+                assert_eq!(run(&mut vm, &program), Ok(Number::Float(0.0)), "{op}");
+            }
+        }
+        let ordered = |op| program(&values, &[Const(1), Const(1), op]);
+        assert_eq!(run(&mut vm, &ordered(Eq)), Ok(Number::Float(1.0)));
+        assert_eq!(run(&mut vm, &ordered(Lt)), Ok(Number::Float(0.0)));
+        let mut vm = Vm::new(Mode::Integer, 4);
+        let values = [Number::Int(-3), Number::Int(2)];
+        let less = program(&values, &[Const(0), Const(1), Lt]);
+        assert_eq!(run(&mut vm, &less), Ok(Number::Int(1)));
+        let greater = program(&values, &[Const(0), Const(1), Gt, Not]);
+        assert_eq!(run(&mut vm, &greater), Ok(Number::Int(1)));
+    }
+
+    #[test]
+    fn jump_targets_must_be_in_range() {
+        use Instruction::*;
+        let values = [Number::Int(0), Number::Int(7)];
+        let mut vm = Vm::new(Mode::Integer, 4);
+        // Jumping to the end of the code halts; anything past it is invalid.
+        let to_end = program(&values, &[Const(1), Jump(3), Const(0)]);
+        assert_eq!(run(&mut vm, &to_end), Ok(Number::Int(7)));
+        let past_end = program(&values, &[Const(1), Jump(4), Const(0)]);
+        assert_eq!(
+            run(&mut vm, &past_end),
+            Err((1, VmErrorKind::InvalidOperand))
+        );
+        let taken = program(&values, &[Const(1), Const(0), JumpIfZero(9)]);
+        assert_eq!(run(&mut vm, &taken), Err((2, VmErrorKind::InvalidOperand)));
+        // A branch that is not taken never checks its target.
+        let not_taken = program(&values, &[Const(1), Dup, JumpIfZero(9)]);
+        assert_eq!(run(&mut vm, &not_taken), Ok(Number::Int(7)));
+        let call = program(
+            &values,
+            &[
+                Const(1),
+                Call {
+                    target: 4,
+                    arity: 1,
+                },
+            ],
+        );
+        assert_eq!(run(&mut vm, &call), Err((1, VmErrorKind::InvalidOperand)));
+        let empty = program(&values, &[JumpIfZero(0)]);
+        assert_eq!(
+            run(&mut vm, &empty),
+            Err((0, VmErrorKind::Operation(RpnErrorKind::Underflow)))
+        );
+    }
+
+    #[test]
+    fn call_depth_is_limited_to_max_depth() {
+        let mut vm = Vm::new(Mode::Integer, 3);
+        assert_eq!(run(&mut vm, &countdown(2)), Ok(Number::Int(0)));
+        assert_eq!(
+            run(&mut vm, &countdown(3)),
+            Err((8, VmErrorKind::CallDepthExceeded))
+        );
+        let mut vm = Vm::new(Mode::Integer, 0);
+        assert_eq!(
+            run(&mut vm, &countdown(0)),
+            Err((1, VmErrorKind::CallDepthExceeded))
+        );
+        let mut vm = Vm::default();
+        assert_eq!(run(&mut vm, &countdown(255)), Ok(Number::Float(0.0)));
+        assert_eq!(
+            run(&mut vm, &countdown(256)),
+            Err((8, VmErrorKind::CallDepthExceeded))
+        );
+    }
+
+    #[test]
+    fn return_and_arg_need_a_call() {
+        use Instruction::*;
+        let values = [Number::Int(1)];
+        let mut vm = Vm::new(Mode::Integer, 4);
+        let ret = program(&values, &[Const(0), Return]);
+        assert_eq!(run(&mut vm, &ret), Err((1, VmErrorKind::ReturnWithoutCall)));
+        let arg = program(&values, &[Const(0), Arg(0)]);
+        assert_eq!(run(&mut vm, &arg), Err((1, VmErrorKind::InvalidOperand)));
+        // Inside a call, arguments past the call's arity are invalid too.
+        let past = program(
+            &values,
+            &[
+                Const(0),
+                Call {
+                    target: 3,
+                    arity: 1,
+                },
+                Halt,
+                Arg(1),
+                Return,
+            ],
+        );
+        assert_eq!(run(&mut vm, &past), Err((3, VmErrorKind::InvalidOperand)));
+        // Even once the callee has pushed values above its arguments.
+        let temporary = program(
+            &values,
+            &[
+                Const(0),
+                Call {
+                    target: 3,
+                    arity: 1,
+                },
+                Halt,
+                Const(0),
+                Arg(0),
+                Arg(1),
+                Return,
+            ],
+        );
+        assert_eq!(
+            run(&mut vm, &temporary),
+            Err((5, VmErrorKind::InvalidOperand))
+        );
+        // An argument the callee dropped is gone.
+        let dropped = program(
+            &values,
+            &[
+                Const(0),
+                Call {
+                    target: 3,
+                    arity: 1,
+                },
+                Halt,
+                Drop,
+                Arg(0),
+                Return,
+            ],
+        );
+        assert_eq!(
+            run(&mut vm, &dropped),
+            Err((4, RpnErrorKind::Underflow.into()))
+        );
+        let underflow = program(
+            &values,
+            &[Call {
+                target: 2,
+                arity: 1,
+            }],
+        );
+        assert_eq!(
+            run(&mut vm, &underflow),
+            Err((0, VmErrorKind::Operation(RpnErrorKind::Underflow)))
+        );
+        let error = VmError {
+            pc: 1,
+            kind: VmErrorKind::ReturnWithoutCall,
+        };
+        assert_eq!(error.to_string(), "at 0001: return outside a call");
+    }
+
+    #[test]
+    fn operands_must_refer_to_something() {
+        use Instruction::*;
+        let mut vm = Vm::new(Mode::Integer, 4);
+        let values = [Number::Int(1)];
+        for (code, pc) in [
+            (&[Const(1)][..], 0),
+            (&[Load(0)], 0),
+            (&[Const(0), Store(0)], 1),
+            (&[Const(0), Operator(0)], 1),
+        ] {
+            let program = program(&values, code);
+            assert_eq!(
+                run(&mut vm, &program),
+                Err((pc, VmErrorKind::InvalidOperand)),
+                "{code:?}"
+            );
+        }
+        let unbalanced = program(&[Number::Int(1)], &[Const(0), Const(0)]);
+        assert_eq!(
+            run(&mut vm, &unbalanced),
+            Err((2, VmErrorKind::Operation(RpnErrorKind::Unbalanced(2))))
+        );
+    }
+
+    #[test]
+    fn compile_time_errors() {
+        let calculator = RpnCalculator::with_mode(Mode::Integer);
+        let error = |expression: &str| {
+            let error = calculator.compile(expression).unwrap_err();
+            (error.index, error.kind)
+        };
+        assert_eq!(error("1 +"), (1, RpnErrorKind::Underflow));
+        assert_eq!(error("dup"), (0, RpnErrorKind::Underflow));
+        assert_eq!(error("1 swap"), (1, RpnErrorKind::Underflow));
+        assert_eq!(error("=x"), (0, RpnErrorKind::Underflow));
+        assert_eq!(error("1 2"), (2, RpnErrorKind::Unbalanced(2)));
+        assert_eq!(error(""), (0, RpnErrorKind::Unbalanced(0)));
+        assert_eq!(error("1 =x"), (2, RpnErrorKind::Unbalanced(0)));
+        let infix_error = |expression: &str| match calculator.compile_infix(expression) {
+            Err(ExpressionError::Evaluate(error)) => (error.index, error.kind),
+            other => panic!("{expression}: {other:?}"),
+        };
+        assert_eq!(
+            infix_error("1 + max(2)"),
+            (
+                2,
+                RpnErrorKind::Arity {
+                    expected: 2,
+                    found: 1
+                }
+            )
+        );
+        assert_eq!(
+            infix_error("nope(1)"),
+            (1, RpnErrorKind::UnknownToken("nope".to_owned()))
+        );
+        assert!(matches!(
+            calculator.compile_infix("1 +"),
+            Err(ExpressionError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn compiled_programs_match_evaluate() {
+        let expressions = [
+            "1 2 + 3 *",
+            "7 2 / 7 2 % -",
+            "2 10 ^ neg abs",
+            "17 sqrt 2 min 5 max",
+            "x y - x *",
+            "x dup * y swap - 1 drop",
+            "x 3 * =z z z +",
+            "x double 1 +",
+            "1 0 /",
+            "5 x x - %",
+            "2 -1 ^",
+            "-4 sqrt",
+            "9223372036854775807 x +",
+            "0.5 1 +",
+            "-9223372036854775807 1 - neg",
+        ];
+        for mode in MODES {
+            let mut calculator = RpnCalculator::with_mode(mode);
+            calculator.register("double", 1, |args| multiply(&[args[0], Number::Int(2)]));
+            calculator.set_variable("x", 4);
+            calculator.set_variable("y", Number::Float(1.5));
+            let mut vm = Vm::new(mode, 8);
+            for expression in expressions {
+                let program = calculator.compile(expression).unwrap();
+                let mut variables: Vec<Number> = program
+                    .variables()
+                    .iter()
+                    .map(|name| calculator.variable(name).unwrap_or(Number::Int(0)))
+                    .collect();
+                let compiled = vm.run(&program, &mut variables).map_err(|error| error.kind);
+                let evaluated = calculator
+                    .evaluate(expression)
+                    .map_err(|error| VmErrorKind::Operation(error.kind));
+                assert_eq!(compiled, evaluated, "{mode:?}: {expression}");
+                // Stores write back, already in the mode's representation.
+                if let (Ok(_), Some(slot)) = (&compiled, program.slot("z")) {
+                    assert_eq!(calculator.variable("z"), Some(variables[slot]));
+                }
+            }
+            let program = calculator.compile_infix("-x ^ 2 + max(y, 1)").unwrap();
+            let evaluated = calculator.evaluate_infix("-x ^ 2 + max(y, 1)").ok();
+            let mut variables: Vec<Number> = program
+                .variables()
+                .iter()
+                .map(|name| calculator.variable(name).unwrap())
+                .collect();
+            assert_eq!(vm.run(&program, &mut variables).ok(), evaluated);
+        }
+    }
+
+    #[test]
+    fn programs_keep_the_operators_they_were_compiled_with() {
+        let mut calculator = RpnCalculator::with_mode(Mode::Integer);
+        calculator.register("double", 1, |args| multiply(&[args[0], Number::Int(2)]));
+        let program = calculator.compile("3 double 1 +").unwrap();
+        calculator.unregister("double");
+        calculator.register("+", 2, subtract);
+        assert_eq!(
+            Vm::new(Mode::Integer, 1).run(&program, &mut []),
+            Ok(Number::Int(7))
+        );
+        assert_eq!(
+            program.to_string(),
+            "0000  const 0      ; 3\n0001  op 0         ; double\n\
+             0002  const 1      ; 1\n0003  add\n0004  halt\n"
+        );
+    }
+}
@@ -0,0 +1,734 @@
+//! Skip list: an ordered map made of stacked sorted linked lists.
+//!
+//! Searching a sorted linked list is O(n) (see "Performance Considerations"
+//! in `Linear/LinkedList.md`). A skip list keeps the linked-list node model
+//! but gives every node a random number of forward links: level 0 links
+//! every node, level 1 roughly every second node, level 2 every fourth, and
+//! so on. Searches start on the sparsest level and drop down, which gives
+//! O(log n) expected search, insertion and removal without any rebalancing.
+//!
+//! Nodes live in a `Vec` arena and link by index, like
+//! [`IndexList`](super::IndexList). Levels are drawn from a seedable
+//! generator; [`SkipList::with_seed`] makes the shape, and therefore the
+//! performance profile, reproducible.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+use crate::rng::SplitMix64;
+
+/// Highest tower a node can get; enough for 2^32 elements at p = 1/2.
+const MAX_LEVEL: usize = 32;
+const NIL: u32 = u32::MAX;
+
+#[derive(Clone)]
+struct SkipNode<K, V> {
+    key: K,
+    value: V,
+    /// Level-0 back link, used for reverse iteration.
+    prev: u32,
+    /// `forward[i]` is the next node on level `i`.
+    forward: Vec<u32>,
+}
+
+/// A probabilistic ordered map.
+///
+/// | Operation                     | Expected cost |
+/// |-------------------------------|---------------|
+/// | `get`, `insert`, `remove`     | O(log n)      |
+/// | `range` (first element)       | O(log n)      |
+/// | `first`, `last`, `len`        | O(1)          |
+#[derive(Clone)]
+pub struct SkipList<K, V> {
+    nodes: Vec<Option<SkipNode<K, V>>>,
+    free: Vec<u32>,
+    /// Forward links of the head sentinel, one per level in use.
+    head: Vec<u32>,
+    tail: u32,
+    len: usize,
+    rng: SplitMix64,
+}
+
+impl<K, V> SkipList<K, V> {
+    /// Creates an empty map seeded from process-level randomness.
+    pub fn new() -> Self {
+        Self::with_rng(SplitMix64::from_entropy())
+    }
+
+    /// Creates an empty map whose level choices are fully determined by
+    /// `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(SplitMix64::new(seed))
+    }
+
+    fn with_rng(rng: SplitMix64) -> Self {
+        SkipList {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: Vec::new(),
+            tail: NIL,
+            len: 0,
+            rng,
+        }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of levels currently in use.
+    pub fn height(&self) -> usize {
+        self.head.len()
+    }
+
+    /// Entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entry(self.head.first().copied().unwrap_or(NIL))
+    }
+
+    /// Entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entry(self.tail)
+    }
+
+    /// Removes every entry.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.head.clear();
+        self.tail = NIL;
+        self.len = 0;
+    }
+
+    /// Iterates over the entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            list: self,
+            front: self.head.first().copied().unwrap_or(NIL),
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    /// Iterates over the keys in ascending order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterates over the values in ascending key order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    fn node(&self, index: u32) -> &SkipNode<K, V> {
+        self.nodes[index as usize]
+            .as_ref()
+            .expect("linked index refers to a live node")
+    }
+
+    fn node_mut(&mut self, index: u32) -> &mut SkipNode<K, V> {
+        self.nodes[index as usize]
+            .as_mut()
+            .expect("linked index refers to a live node")
+    }
+
+    fn entry(&self, index: u32) -> Option<(&K, &V)> {
+        if index == NIL {
+            return None;
+        }
+        let node = self.node(index);
+        Some((&node.key, &node.value))
+    }
+
+    /// Forward link of `pred` on `level`, where `NIL` stands for the head.
+    fn next_of(&self, pred: u32, level: usize) -> u32 {
+        match pred {
+            NIL => self.head[level],
+            pred => self.node(pred).forward[level],
+        }
+    }
+
+    fn set_next(&mut self, pred: u32, level: usize, next: u32) {
+        match pred {
+            NIL => self.head[level] = next,
+            pred => self.node_mut(pred).forward[level] = next,
+        }
+    }
+
+    /// Draws a tower height: each extra level with probability 1/2.
+    fn random_level(&mut self) -> usize {
+        let ones = self.rng.next_u64().trailing_ones() as usize;
+        (ones + 1).min(MAX_LEVEL)
+    }
+
+    /// For every level, the last node whose key is `< key` (or `<= key`
+    /// when `inclusive`), with `NIL` meaning the head.
+    fn predecessors<Q>(&self, key: &Q, inclusive: bool) -> [u32; MAX_LEVEL]
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut update = [NIL; MAX_LEVEL];
+        let mut pred = NIL;
+        for level in (0..self.head.len()).rev() {
+            loop {
+                let next = self.next_of(pred, level);
+                if next == NIL {
+                    break;
+                }
+                let next_key = self.node(next).key.borrow();
+                let advance = if inclusive {
+                    next_key <= key
+                } else {
+                    next_key < key
+                };
+                if !advance {
+                    break;
+                }
+                pred = next;
+            }
+            update[level] = pred;
+        }
+        update
+    }
+
+    /// First node whose key satisfies the lower bound.
+    fn lower_bound<Q>(&self, bound: Bound<&Q>) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let pred = match bound {
+            Bound::Unbounded => NIL,
+            Bound::Included(key) => self.predecessors(key, false)[0],
+            Bound::Excluded(key) => self.predecessors(key, true)[0],
+        };
+        if self.head.is_empty() {
+            NIL
+        } else {
+            self.next_of(pred, 0)
+        }
+    }
+
+    /// Last node whose key satisfies the upper bound.
+    fn upper_bound<Q>(&self, bound: Bound<&Q>) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match bound {
+            Bound::Unbounded => self.tail,
+            Bound::Included(key) => self.predecessors(key, true)[0],
+            Bound::Excluded(key) => self.predecessors(key, false)[0],
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.lower_bound(Bound::Included(key));
+        if index != NIL && self.node(index).key.borrow() == key {
+            index
+        } else {
+            NIL
+        }
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// Returns the value stored under `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry(self.find(key)).map(|(_, value)| value)
+    }
+
+    /// Returns the value stored under `key`, mutably.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.find(key) {
+            NIL => None,
+            index => Some(&mut self.node_mut(index).value),
+        }
+    }
+
+    /// Returns `true` if the map has an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key) != NIL
+    }
+
+    /// Inserts `value` under `key`, returning the previous value if the key
+    /// was already present (the stored key is kept in that case).
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let update = self.predecessors(&key, false);
+        if !self.head.is_empty() {
+            let existing = self.next_of(update[0], 0);
+            if existing != NIL && self.node(existing).key == key {
+                return Some(std::mem::replace(&mut self.node_mut(existing).value, value));
+            }
+        }
+
+        let height = self.random_level();
+        while self.head.len() < height {
+            self.head.push(NIL);
+        }
+        let prev = update[0];
+        let node = SkipNode {
+            key,
+            value,
+            prev,
+            forward: (0..height)
+                .map(|level| self.next_of(update[level], level))
+                .collect(),
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = Some(node);
+                index
+            }
+            None => {
+                let index = u32::try_from(self.nodes.len())
+                    .ok()
+                    .filter(|&index| index != NIL)
+                    .expect("SkipList capacity overflow");
+                self.nodes.push(Some(node));
+                index
+            }
+        };
+        for (level, &pred) in update.iter().enumerate().take(height) {
+            self.set_next(pred, level, index);
+        }
+        match self.node(index).forward[0] {
+            NIL => self.tail = index,
+            next => self.node_mut(next).prev = index,
+        }
+        self.len += 1;
+        None
+    }
+
+    /// Removes the entry for `key` and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the entry for `key` and returns it.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if self.head.is_empty() {
+            return None;
+        }
+        let update = self.predecessors(key, false);
+        let index = self.next_of(update[0], 0);
+        if index == NIL || self.node(index).key.borrow() != key {
+            return None;
+        }
+        let node = self.nodes[index as usize]
+            .take()
+            .expect("linked index refers to a live node");
+        for (level, &next) in node.forward.iter().enumerate() {
+            self.set_next(update[level], level, next);
+        }
+        match node.forward[0] {
+            NIL => self.tail = node.prev,
+            next => self.node_mut(next).prev = node.prev,
+        }
+        while self.head.last() == Some(&NIL) {
+            self.head.pop();
+        }
+        self.free.push(index);
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// Iterates over the entries whose keys fall in `range`, in ascending
+    /// order. Locating the first entry is O(log n) expected, each further
+    /// step O(1).
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let front = self.lower_bound(range.start_bound());
+        let back = self.upper_bound(range.end_bound());
+        let done = front == NIL || back == NIL || self.node(front).key > self.node(back).key;
+        Range {
+            list: self,
+            front,
+            back,
+            done,
+        }
+    }
+}
+
+impl<K, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        SkipList::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SkipList<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SkipList<K, V> {}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// Ordered iterator returned by [`SkipList::iter`].
+pub struct Iter<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    front: u32,
+    back: u32,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.front);
+        self.front = node.forward[0];
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.back);
+        self.back = node.prev;
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+/// Ordered iterator returned by [`SkipList::range`].
+pub struct Range<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    front: u32,
+    back: u32,
+    done: bool,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.done {
+            return None;
+        }
+        let node = self.list.node(self.front);
+        self.done = self.front == self.back;
+        self.front = node.forward[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.done {
+            return None;
+        }
+        let node = self.list.node(self.back);
+        self.done = self.front == self.back;
+        self.back = node.prev;
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Range { ..*self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    /// Checks that every level is sorted and contained in the level below,
+    /// that `prev` mirrors level 0, and the bookkeeping of `tail`, `len`,
+    /// `free` and `head`.
+    fn check<K: Ord, V>(list: &SkipList<K, V>) {
+        assert_ne!(list.head.last(), Some(&NIL), "unused top level");
+        let mut below: Option<Vec<u32>> = None;
+        for level in 0..list.height() {
+            let mut chain = Vec::new();
+            let mut index = list.head[level];
+            while index != NIL {
+                chain.push(index);
+                index = list.node(index).forward[level];
+            }
+            assert!(chain
+                .windows(2)
+                .all(|w| list.node(w[0]).key < list.node(w[1]).key));
+            if let Some(below) = &below {
+                assert!(chain.iter().all(|index| below.contains(index)));
+            } else {
+                assert_eq!(chain.len(), list.len);
+                let mut prev = NIL;
+                for &index in &chain {
+                    assert_eq!(list.node(index).prev, prev);
+                    prev = index;
+                }
+                assert_eq!(list.tail, prev);
+            }
+            below = Some(chain);
+        }
+        let live = list.nodes.iter().filter(|node| node.is_some()).count();
+        assert_eq!(live, list.len);
+        assert_eq!(live + list.free.len(), list.nodes.len());
+    }
+
+    fn seeded(keys: impl IntoIterator<Item = i32>) -> SkipList<i32, i32> {
+        let mut list = SkipList::with_seed(1);
+        for key in keys {
+            list.insert(key, key * 10);
+        }
+        check(&list);
+        list
+    }
+
+    fn keys<'a, V>(iter: impl Iterator<Item = (&'a i32, V)>) -> Vec<i32> {
+        iter.map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn empty_map() {
+        let mut list: SkipList<i32, i32> = SkipList::with_seed(0);
+        check(&list);
+        assert_eq!(list.height(), 0);
+        assert_eq!(list.get(&1), None);
+        assert_eq!(list.remove(&1), None);
+        assert_eq!((list.first(), list.last()), (None, None));
+        assert_eq!(list.range(..).next(), None);
+        assert_eq!(list.range(1..5).next_back(), None);
+    }
+
+    #[test]
+    fn insert_keeps_keys_sorted_and_replaces_values() {
+        let mut list = seeded([5, 1, 9, 3, 7]);
+        assert_eq!(list.insert(3, 0), Some(30));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.get(&3), Some(&0));
+        *list.get_mut(&9).unwrap() += 1;
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            [(&1, &10), (&3, &0), (&5, &50), (&7, &70), (&9, &91)]
+        );
+        assert_eq!(
+            list.keys().rev().copied().collect::<Vec<_>>(),
+            [9, 7, 5, 3, 1]
+        );
+        assert_eq!(
+            (list.first(), list.last()),
+            (Some((&1, &10)), Some((&9, &91)))
+        );
+        assert!(!list.contains_key(&4));
+    }
+
+    #[test]
+    fn remove_relinks_every_level() {
+        let mut list = seeded(0..50);
+        for key in (0..50).step_by(3) {
+            assert_eq!(list.remove_entry(&key), Some((key, key * 10)));
+            check(&list);
+        }
+        assert_eq!(list.remove(&0), None);
+        assert_eq!(list.len(), 33);
+        assert_eq!(list.first(), Some((&1, &10)));
+        assert_eq!(list.last(), Some((&49, &490)));
+        for key in 0..50 {
+            list.remove(&key);
+        }
+        check(&list);
+        assert_eq!(list.height(), 0);
+        list.insert(7, 7);
+        check(&list);
+        assert_eq!(list.nodes.len(), 50, "freed nodes are reused");
+    }
+
+    #[test]
+    fn range_bounds() {
+        let list = seeded((0..20).step_by(2));
+        assert_eq!(keys(list.range(4..10)), [4, 6, 8]);
+        assert_eq!(keys(list.range(3..=10)), [4, 6, 8, 10]);
+        assert_eq!(keys(list.range(..3)), [0, 2]);
+        assert_eq!(keys(list.range(15..)), [16, 18]);
+        assert_eq!(
+            keys(list.range((Bound::Excluded(4), Bound::Excluded(10)))),
+            [6, 8]
+        );
+        assert_eq!(keys(list.range(5..6)), []);
+        assert_eq!(keys(list.range(30..)), []);
+        assert_eq!(keys(list.range(..0)), []);
+        assert_eq!(
+            keys(list.range((Bound::Excluded(8), Bound::Excluded(8)))),
+            []
+        );
+        assert_eq!(keys(list.range(2..9).rev()), [8, 6, 4, 2]);
+        let mut range = list.range(..);
+        assert_eq!(range.next().map(|(k, _)| *k), Some(0));
+        assert_eq!(range.next_back().map(|(k, _)| *k), Some(18));
+        assert_eq!(range.count(), 8);
+    }
+
+    #[test]
+    fn matches_btree_map_under_seeded_edits() {
+        let mut rng = SplitMix64::new(99);
+        let mut list = SkipList::with_seed(5);
+        let mut model = BTreeMap::new();
+        for step in 0..2000 {
+            let key = rng.below(200);
+            if rng.below(3) == 0 {
+                assert_eq!(list.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(list.insert(key, step), model.insert(key, step));
+            }
+            if step % 100 == 0 {
+                check(&list);
+            }
+        }
+        check(&list);
+        assert!(list.iter().eq(model.iter()));
+        for _ in 0..50 {
+            let (a, b) = (rng.below(220), rng.below(220));
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(list.range(low..high).eq(model.range(low..high)));
+            assert!(list
+                .range(low..=high)
+                .rev()
+                .eq(model.range(low..=high).rev()));
+        }
+    }
+
+    #[test]
+    fn same_seed_builds_the_same_towers() {
+        let heights = |seed| {
+            let mut list = SkipList::with_seed(seed);
+            for key in 0..100 {
+                list.insert(key, ());
+            }
+            let mut heights: Vec<(i32, usize)> = list
+                .nodes
+                .iter()
+                .flatten()
+                .map(|node| (node.key, node.forward.len()))
+                .collect();
+            heights.sort();
+            heights
+        };
+        assert_eq!(heights(3), heights(3));
+        assert_ne!(heights(3), heights(4));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn levels_halve_in_size() {
+        let n = 1 << 14;
+        let mut list = SkipList::with_seed(2024);
+        for key in 0..n {
+            list.insert(key, ());
+        }
+        check(&list);
+        for level in 1..8 {
+            let count = list
+                .nodes
+                .iter()
+                .flatten()
+                .filter(|node| node.forward.len() > level)
+                .count();
+            let expected = n >> level;
+            assert!(
+                count.abs_diff(expected) < expected / 5,
+                "level {level}: {count} nodes, expected about {expected}"
+            );
+        }
+        assert!(
+            (12..=24).contains(&list.height()),
+            "height {}",
+            list.height()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! LIFO stacks: see `Linear/Stack.md`.
+//!
+//! The Ruby `Stack` in the notes is a class around an array and raises
+//! `'Stack underflow'` when popped empty. Here [`Stack`] is a trait with the
+//! same five operations, reporting [`StackError`] instead of raising, and it
+//! is implemented over both internal structures the notes describe:
+//! [`ArrayStack`] on a growable array and [`ListStack`] on the crate's
+//! [`LinkedList`](super::LinkedList).
+//!
+//! [`AggStack`] extends the notes' `MaxStack` to any associative aggregate,
+//! and [`AggQueue`] builds a sliding-window queue from two of them.
+//! [`BoundedStack`] and [`InlineBoundedStack`] cap the number of items, and
+//! [`TreiberStack`] is a lock-free stack for many threads.
+
+use std::fmt;
+
+mod agg_stack;
+pub mod array_stack;
+mod bounded;
+pub mod list_stack;
+mod monoid;
+mod treiber;
+
+pub use agg_stack::{AggQueue, AggStack};
+pub use array_stack::ArrayStack;
+pub use bounded::{BoundedStack, InlineBoundedStack, OverflowError, OverflowPolicy};
+pub use list_stack::ListStack;
+pub use monoid::{Gcd, Max, Min, Monoid, Sum};
+pub use treiber::TreiberStack;
+
+/// Errors reported by the stack types in this module tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackError {
+    /// `pop` or `peek` on an empty stack.
+    Underflow,
+    /// `push` onto a full bounded stack; see [`OverflowError`].
+    Overflow,
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackError::Underflow => f.write_str("stack underflow"),
+            StackError::Overflow => f.write_str("stack overflow"),
+        }
+    }
+}
+
+impl std::error::Error for StackError {}
+
+/// A last-in, first-out collection.
+///
+/// Implementors also implement `Display` as `Stack: top <- ... <- bottom`,
+/// like the Ruby `to_s`.
+pub trait Stack<T> {
+    /// Puts `item` on top of the stack.
+    fn push(&mut self, item: T);
+
+    /// Removes and returns the top item.
+    fn pop(&mut self) -> Result<T, StackError>;
+
+    /// Returns the top item without removing it.
+    fn peek(&self) -> Result<&T, StackError>;
+
+    /// Number of items on the stack.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the stack holds no items.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Writes `items`, given top first, in the `Display` format of [`Stack`].
+fn fmt_stack<'a, T, I>(f: &mut fmt::Formatter<'_>, items: I) -> fmt::Result
+where
+    T: fmt::Display + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    f.write_str("Stack:")?;
+    for (position, item) in items.into_iter().enumerate() {
+        let separator = if position == 0 { " " } else { " <- " };
+        write!(f, "{separator}{item}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,406 @@
+//! Stacks and queues that know an aggregate of their items.
+//!
+//! The Ruby `MaxStack` keeps a second `@max_elements` array next to its
+//! items. [`AggStack`] generalises it to any [`Monoid`]: beside every item
+//! an auxiliary stack holds the aggregate of that item and everything below
+//! it, so pushes and pops keep both stacks in step and the aggregate is
+//! always on top. [`AggQueue`] joins two such stacks into a FIFO queue for
+//! sliding-window aggregates.
+
+use std::fmt;
+
+use super::array_stack::{self, ArrayStack};
+use super::{fmt_stack, Monoid, Stack, StackError};
+
+/// A stack that maintains the aggregate of its items under the monoid `M`.
+///
+/// | Operation             | Cost           |
+/// |-----------------------|----------------|
+/// | `push`                | O(1) amortized |
+/// | `pop`, `peek`         | O(1)           |
+/// | `aggregate`           | O(1)           |
+pub struct AggStack<T, M: Monoid<T>> {
+    items: ArrayStack<T>,
+    /// `aggregates` holds, at each height, the aggregate from the bottom of
+    /// the stack up to the item at that height.
+    aggregates: ArrayStack<M::Value>,
+}
+
+impl<T, M: Monoid<T>> AggStack<T, M> {
+    /// Creates an empty stack.
+    pub const fn new() -> Self {
+        AggStack {
+            items: ArrayStack::new(),
+            aggregates: ArrayStack::new(),
+        }
+    }
+
+    /// Aggregate of all items from bottom to top, or `None` if empty.
+    pub fn aggregate(&self) -> Option<&M::Value> {
+        self.aggregates.peek().ok()
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.aggregates.clear();
+    }
+
+    /// Returns an iterator from the top of the stack to the bottom.
+    pub fn iter(&self) -> array_stack::Iter<'_, T> {
+        self.items.iter()
+    }
+}
+
+impl<T, M: Monoid<T>> Stack<T> for AggStack<T, M> {
+    fn push(&mut self, item: T) {
+        let lifted = M::lift(&item);
+        let aggregate = match self.aggregates.peek() {
+            Ok(below) => M::combine(below, &lifted),
+            Err(_) => lifted,
+        };
+        self.items.push(item);
+        self.aggregates.push(aggregate);
+    }
+
+    fn pop(&mut self) -> Result<T, StackError> {
+        let item = self.items.pop()?;
+        self.aggregates.pop()?;
+        Ok(item)
+    }
+
+    fn peek(&self) -> Result<&T, StackError> {
+        self.items.peek()
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<T, M: Monoid<T>> Default for AggStack<T, M> {
+    fn default() -> Self {
+        AggStack::new()
+    }
+}
+
+impl<T: Clone, M: Monoid<T>> Clone for AggStack<T, M> {
+    fn clone(&self) -> Self {
+        AggStack {
+            items: self.items.clone(),
+            aggregates: self.aggregates.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, M: Monoid<T>> fmt::Debug for AggStack<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display, M: Monoid<T>> fmt::Display for AggStack<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_stack(f, self.iter())
+    }
+}
+
+impl<T, M: Monoid<T>> Extend<T> for AggStack<T, M> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, M: Monoid<T>> FromIterator<T> for AggStack<T, M> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = AggStack::new();
+        stack.extend(iter);
+        stack
+    }
+}
+
+/// A FIFO queue that maintains the aggregate of its items, oldest to newest,
+/// under the monoid `M`.
+///
+/// New items go on an [`AggStack`]. When the front is needed and the front
+/// stack is empty, every item is moved over in reverse, each one storing the
+/// aggregate of itself and the items that will leave after it. Every item is
+/// moved once, so `dequeue` is O(1) amortized, and the queue's aggregate is
+/// the front aggregate combined with the back one, which keeps
+/// non-commutative monoids in order.
+///
+/// | Operation             | Cost           |
+/// |-----------------------|----------------|
+/// | `enqueue`             | O(1) amortized |
+/// | `dequeue`, `front`    | O(1) amortized |
+/// | `aggregate`           | O(1)           |
+pub struct AggQueue<T, M: Monoid<T>> {
+    /// Oldest items, the oldest on top, each with the aggregate from itself
+    /// down to the bottom of this stack.
+    front: ArrayStack<(T, M::Value)>,
+    back: AggStack<T, M>,
+}
+
+impl<T, M: Monoid<T>> AggQueue<T, M> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        AggQueue {
+            front: ArrayStack::new(),
+            back: AggStack::new(),
+        }
+    }
+
+    /// Number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Returns `true` if the queue holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds an item at the back of the queue.
+    pub fn enqueue(&mut self, item: T) {
+        self.back.push(item);
+    }
+
+    /// Removes and returns the oldest item.
+    pub fn dequeue(&mut self) -> Result<T, StackError> {
+        self.refill();
+        self.front.pop().map(|(item, _)| item)
+    }
+
+    /// Returns the oldest item without removing it.
+    ///
+    /// Takes `&mut self` because it may move items to the front stack.
+    pub fn front(&mut self) -> Result<&T, StackError> {
+        self.refill();
+        self.front.peek().map(|(item, _)| item)
+    }
+
+    /// Aggregate of all items from oldest to newest, or `None` if empty.
+    pub fn aggregate(&self) -> Option<M::Value> {
+        let front = self.front.peek().ok().map(|(_, aggregate)| aggregate);
+        match (front, self.back.aggregate()) {
+            (Some(front), Some(back)) => Some(M::combine(front, back)),
+            (front, back) => front.or(back).cloned(),
+        }
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
+
+    fn refill(&mut self) {
+        if !self.front.is_empty() {
+            return;
+        }
+        while let Ok(item) = self.back.pop() {
+            let lifted = M::lift(&item);
+            let aggregate = match self.front.peek() {
+                Ok((_, after)) => M::combine(&lifted, after),
+                Err(_) => lifted,
+            };
+            self.front.push((item, aggregate));
+        }
+    }
+}
+
+impl<T, M: Monoid<T>> Default for AggQueue<T, M> {
+    fn default() -> Self {
+        AggQueue::new()
+    }
+}
+
+impl<T: Clone, M: Monoid<T>> Clone for AggQueue<T, M> {
+    fn clone(&self) -> Self {
+        AggQueue {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, M: Monoid<T>> fmt::Debug for AggQueue<T, M> {
+    /// Lists the items oldest first.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let front = self.front.iter().map(|(item, _)| item);
+        let back = self.back.iter().rev();
+        f.debug_list().entries(front.chain(back)).finish()
+    }
+}
+
+impl<T, M: Monoid<T>> Extend<T> for AggQueue<T, M> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.enqueue(item);
+        }
+    }
+}
+
+impl<T, M: Monoid<T>> FromIterator<T> for AggQueue<T, M> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = AggQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::linear::stack::{Gcd, Max, Min, Sum};
+    use crate::rng::SplitMix64;
+
+    /// String concatenation: associative but not commutative, so any
+    /// aggregate combined in the wrong order shows up as a wrong string.
+    struct Concat;
+
+    impl Monoid<char> for Concat {
+        type Value = String;
+
+        fn identity() -> Option<String> {
+            Some(String::new())
+        }
+
+        fn lift(item: &char) -> String {
+            item.to_string()
+        }
+
+        fn combine(left: &String, right: &String) -> String {
+            format!("{left}{right}")
+        }
+    }
+
+    fn letter(step: u64) -> char {
+        char::from(b'a' + (step % 26) as u8)
+    }
+
+    #[test]
+    fn stack_aggregate_runs_bottom_to_top() {
+        let mut stack: AggStack<char, Concat> = AggStack::new();
+        assert_eq!(stack.aggregate(), None);
+        let mut model = String::new();
+        let mut rng = SplitMix64::new(15);
+        for step in 0..2000 {
+            if rng.below(3) == 0 {
+                assert_eq!(stack.pop().ok(), model.pop());
+            } else {
+                stack.push(letter(step));
+                model.push(letter(step));
+            }
+            let expected = (!model.is_empty()).then_some(&model);
+            assert_eq!(stack.aggregate(), expected);
+        }
+    }
+
+    #[test]
+    fn queue_aggregate_keeps_order_across_transfers() {
+        let mut queue: AggQueue<char, Concat> = AggQueue::new();
+        let mut model = VecDeque::new();
+        let mut rng = SplitMix64::new(16);
+        for step in 0..5000 {
+            // Bursts of enqueues between dequeues leave items on both
+            // stacks, so aggregates combine the front with the back.
+            if rng.below(5) < 2 {
+                assert_eq!(queue.dequeue().ok(), model.pop_front());
+            } else {
+                queue.enqueue(letter(step));
+                model.push_back(letter(step));
+            }
+            if step % 7 == 0 {
+                assert_eq!(queue.front().ok(), model.front());
+            }
+            let expected: String = model.iter().collect();
+            assert_eq!(queue.aggregate(), (!model.is_empty()).then_some(expected));
+            assert_eq!(queue.len(), model.len());
+        }
+    }
+
+    #[test]
+    fn queue_aggregate_after_a_transfer_with_pending_items() {
+        let mut queue: AggQueue<char, Concat> = "abc".chars().collect();
+        assert_eq!(queue.dequeue(), Ok('a'));
+        queue.extend("de".chars());
+        assert_eq!(queue.aggregate().as_deref(), Some("bcde"));
+        assert_eq!(queue.dequeue(), Ok('b'));
+        assert_eq!(queue.dequeue(), Ok('c'));
+        assert_eq!(queue.aggregate().as_deref(), Some("de"));
+        assert_eq!(queue.dequeue(), Ok('d'));
+        queue.enqueue('f');
+        assert_eq!(queue.aggregate().as_deref(), Some("ef"));
+        assert_eq!(format!("{queue:?}"), "['e', 'f']");
+        queue.clear();
+        assert_eq!(queue.aggregate(), None);
+        assert_eq!(queue.dequeue(), Err(StackError::Underflow));
+    }
+
+    #[test]
+    fn max_stack_matches_the_ruby_example() {
+        let mut stack: AggStack<i32, Max> = [3, 1, 4, 1, 5].into_iter().collect();
+        assert_eq!(stack.aggregate(), Some(&5));
+        stack.pop().unwrap();
+        assert_eq!(stack.aggregate(), Some(&4));
+        stack.pop().unwrap();
+        stack.pop().unwrap();
+        assert_eq!(stack.aggregate(), Some(&3));
+    }
+
+    #[test]
+    fn identities_leave_values_unchanged() {
+        fn check<T, M: Monoid<T>>(items: &[T])
+        where
+            M::Value: PartialEq + fmt::Debug,
+        {
+            let identity = M::identity().unwrap();
+            for item in items {
+                let value = M::lift(item);
+                assert_eq!(M::combine(&identity, &value), value);
+                assert_eq!(M::combine(&value, &identity), value);
+            }
+        }
+        check::<u32, Gcd>(&[0, 1, 12, u32::MAX]);
+        check::<char, Concat>(&['x']);
+        assert_eq!(<Min as Monoid<i32>>::identity(), None);
+        assert_eq!(<Max as Monoid<String>>::identity(), None);
+        assert_eq!(<Sum as Monoid<i64>>::identity(), None);
+    }
+
+    #[test]
+    fn min_and_max_take_any_ord_type() {
+        let mut words: AggStack<String, Max> = ["pear", "apple", "plum"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(words.aggregate().map(String::as_str), Some("plum"));
+        words.pop().unwrap();
+        assert_eq!(words.aggregate().map(String::as_str), Some("pear"));
+
+        let mut pairs: AggQueue<(u8, &str), Min> =
+            [(2, "b"), (1, "z"), (1, "a")].into_iter().collect();
+        assert_eq!(pairs.aggregate(), Some((1, "a")));
+        pairs.dequeue().unwrap();
+        pairs.dequeue().unwrap();
+        assert_eq!(pairs.aggregate(), Some((1, "a")));
+    }
+
+    #[test]
+    fn gcd_and_sum_aggregates() {
+        let mut queue: AggQueue<u64, Gcd> = [12, 18, 30].into_iter().collect();
+        assert_eq!(queue.aggregate(), Some(6));
+        queue.dequeue().unwrap();
+        queue.enqueue(45);
+        assert_eq!(queue.aggregate(), Some(3));
+        let stack: AggStack<i64, Sum> = (1..=100).collect();
+        assert_eq!(stack.aggregate(), Some(&5050));
+    }
+}
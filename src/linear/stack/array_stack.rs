@@ -0,0 +1,138 @@
+//! Stack over a growable array, the Ruby `Stack` layout.
+
+use std::fmt;
+use std::iter::{FusedIterator, Rev};
+use std::slice;
+
+use super::{fmt_stack, Stack, StackError};
+
+/// A stack stored in a `Vec`, top at the end.
+///
+/// | Operation      | Cost           |
+/// |----------------|----------------|
+/// | `push`         | O(1) amortized |
+/// | `pop`, `peek`  | O(1)           |
+/// | `len`          | O(1)           |
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ArrayStack<T> {
+    items: Vec<T>,
+}
+
+impl<T> ArrayStack<T> {
+    /// Creates an empty stack.
+    pub const fn new() -> Self {
+        ArrayStack { items: Vec::new() }
+    }
+
+    /// Creates an empty stack with room for `capacity` items.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArrayStack {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns the top item mutably.
+    pub fn peek_mut(&mut self) -> Result<&mut T, StackError> {
+        self.items.last_mut().ok_or(StackError::Underflow)
+    }
+
+    /// Returns an iterator from the top of the stack to the bottom.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.items.iter().rev(),
+        }
+    }
+}
+
+impl<T> Stack<T> for ArrayStack<T> {
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    fn pop(&mut self) -> Result<T, StackError> {
+        self.items.pop().ok_or(StackError::Underflow)
+    }
+
+    fn peek(&self) -> Result<&T, StackError> {
+        self.items.last().ok_or(StackError::Underflow)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<T> Default for ArrayStack<T> {
+    fn default() -> Self {
+        ArrayStack::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArrayStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for ArrayStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_stack(f, self)
+    }
+}
+
+impl<T> Extend<T> for ArrayStack<T> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+impl<T> FromIterator<T> for ArrayStack<T> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        ArrayStack {
+            items: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArrayStack<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Top-to-bottom iterator returned by [`ArrayStack::iter`].
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    inner: Rev<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
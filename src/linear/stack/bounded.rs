@@ -0,0 +1,558 @@
+//! Fixed-capacity stacks.
+//!
+//! The Ruby stacks grow without limit and only fail on underflow. These stop
+//! at a capacity chosen up front, at run time for [`BoundedStack`] or at
+//! compile time for [`InlineBoundedStack`], and an [`OverflowPolicy`] decides
+//! what a push onto a full stack does. Both keep their items in a ring, so
+//! dropping the oldest item is as cheap as popping the top one.
+//!
+//! Because a push can fail, neither type implements [`Stack`](super::Stack);
+//! their inherent `push` returns a `Result` instead.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::mem::MaybeUninit;
+
+use super::{fmt_stack, StackError};
+
+/// What a push onto a full bounded stack does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverflowPolicy {
+    /// Refuse the push with an [`OverflowError`] holding the item.
+    #[default]
+    Error,
+    /// Evict the bottom (oldest) item to make room.
+    DropOldest,
+    /// Replace the top item with the new one.
+    OverwriteTop,
+}
+
+/// A push refused under [`OverflowPolicy::Error`]; holds the item that did
+/// not fit.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverflowError<T>(pub T);
+
+impl<T> OverflowError<T> {
+    /// Returns the refused item.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for OverflowError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverflowError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for OverflowError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&StackError::Overflow, f)
+    }
+}
+
+impl<T> Error for OverflowError<T> {}
+
+impl<T> From<OverflowError<T>> for StackError {
+    fn from(_: OverflowError<T>) -> StackError {
+        StackError::Overflow
+    }
+}
+
+/// A heap-allocated stack holding at most `capacity` items.
+///
+/// | Operation             | Cost |
+/// |-----------------------|------|
+/// | `push`                | O(1) |
+/// | `pop`, `pop_oldest`   | O(1) |
+/// | `peek`                | O(1) |
+#[derive(Clone)]
+pub struct BoundedStack<T> {
+    /// Bottom of the stack at the front, top at the back.
+    items: VecDeque<T>,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T> BoundedStack<T> {
+    /// Creates an empty stack for up to `capacity` items. Storage for all of
+    /// them is allocated now, so pushes never reallocate.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        BoundedStack {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+        }
+    }
+
+    /// Maximum number of items.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of items on the stack.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the stack holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if a push would overflow.
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    /// Current overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Changes the overflow policy.
+    pub fn set_policy(&mut self, policy: OverflowPolicy) {
+        self.policy = policy;
+    }
+
+    /// Puts `item` on top of the stack.
+    ///
+    /// On a full stack the overflow policy applies: `Ok(Some(_))` returns
+    /// the item that was evicted or overwritten (the new item itself when
+    /// the capacity is zero), and [`OverflowPolicy::Error`] hands `item`
+    /// back in an [`OverflowError`].
+    pub fn push(&mut self, item: T) -> Result<Option<T>, OverflowError<T>> {
+        if !self.is_full() {
+            self.items.push_back(item);
+            return Ok(None);
+        }
+        match self.policy {
+            OverflowPolicy::Error => Err(OverflowError(item)),
+            _ if self.capacity == 0 => Ok(Some(item)),
+            OverflowPolicy::DropOldest => {
+                let oldest = self.items.pop_front();
+                self.items.push_back(item);
+                Ok(oldest)
+            }
+            OverflowPolicy::OverwriteTop => {
+                let top = self.items.back_mut().expect("full stack has a top");
+                Ok(Some(std::mem::replace(top, item)))
+            }
+        }
+    }
+
+    /// Removes and returns the top item.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        self.items.pop_back().ok_or(StackError::Underflow)
+    }
+
+    /// Removes and returns the bottom (oldest) item.
+    pub fn pop_oldest(&mut self) -> Result<T, StackError> {
+        self.items.pop_front().ok_or(StackError::Underflow)
+    }
+
+    /// Returns the top item without removing it.
+    pub fn peek(&self) -> Result<&T, StackError> {
+        self.items.back().ok_or(StackError::Underflow)
+    }
+
+    /// Returns the top item mutably.
+    pub fn peek_mut(&mut self) -> Result<&mut T, StackError> {
+        self.items.back_mut().ok_or(StackError::Underflow)
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns an iterator from the top of the stack to the bottom.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter().rev()
+    }
+}
+
+impl<T: PartialEq> PartialEq for BoundedStack<T> {
+    /// Compares the items only, not the capacity or policy.
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<T: Eq> Eq for BoundedStack<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for BoundedStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for BoundedStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_stack(f, self.iter())
+    }
+}
+
+/// A stack holding at most `N` items inline, without heap allocation.
+///
+/// Behaves exactly like [`BoundedStack`] with capacity `N`.
+pub struct InlineBoundedStack<T, const N: usize> {
+    /// Ring buffer: item `i` from the bottom lives at `(start + i) % N`.
+    items: [MaybeUninit<T>; N],
+    start: usize,
+    len: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T, const N: usize> InlineBoundedStack<T, N> {
+    /// Creates an empty stack.
+    pub const fn new(policy: OverflowPolicy) -> Self {
+        const { assert!(N > 0, "InlineBoundedStack needs room for an item") };
+        InlineBoundedStack {
+            items: [const { MaybeUninit::uninit() }; N],
+            start: 0,
+            len: 0,
+            policy,
+        }
+    }
+
+    /// Maximum number of items, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of items on the stack.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the stack holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if a push would overflow.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Current overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Changes the overflow policy.
+    pub fn set_policy(&mut self, policy: OverflowPolicy) {
+        self.policy = policy;
+    }
+
+    /// Puts `item` on top of the stack; see [`BoundedStack::push`].
+    pub fn push(&mut self, item: T) -> Result<Option<T>, OverflowError<T>> {
+        if !self.is_full() {
+            self.items[self.slot(self.len)].write(item);
+            self.len += 1;
+            return Ok(None);
+        }
+        match self.policy {
+            OverflowPolicy::Error => Err(OverflowError(item)),
+            OverflowPolicy::DropOldest => {
+                // The oldest slot becomes the new top slot.
+                let slot = &mut self.items[self.start];
+                // SAFETY: the stack is full, so every slot is initialized.
+                let oldest = unsafe { slot.assume_init_read() };
+                slot.write(item);
+                self.start = self.slot(1);
+                Ok(Some(oldest))
+            }
+            OverflowPolicy::OverwriteTop => {
+                let top = self.slot(self.len - 1);
+                // SAFETY: the top slot of a non-empty stack is initialized.
+                let top = unsafe { self.items[top].assume_init_mut() };
+                Ok(Some(std::mem::replace(top, item)))
+            }
+        }
+    }
+
+    /// Removes and returns the top item.
+    pub fn pop(&mut self) -> Result<T, StackError> {
+        if self.len == 0 {
+            return Err(StackError::Underflow);
+        }
+        self.len -= 1;
+        // SAFETY: the slot held the top item; shrinking `len` first hands
+        // its ownership to us.
+        Ok(unsafe { self.items[self.slot(self.len)].assume_init_read() })
+    }
+
+    /// Removes and returns the bottom (oldest) item.
+    pub fn pop_oldest(&mut self) -> Result<T, StackError> {
+        if self.len == 0 {
+            return Err(StackError::Underflow);
+        }
+        // SAFETY: the bottom slot of a non-empty stack is initialized, and
+        // moving `start` past it hands its ownership to us.
+        let oldest = unsafe { self.items[self.start].assume_init_read() };
+        self.start = self.slot(1);
+        self.len -= 1;
+        Ok(oldest)
+    }
+
+    /// Returns the top item without removing it.
+    pub fn peek(&self) -> Result<&T, StackError> {
+        self.get(self.len.wrapping_sub(1))
+            .ok_or(StackError::Underflow)
+    }
+
+    /// Returns the top item mutably.
+    pub fn peek_mut(&mut self) -> Result<&mut T, StackError> {
+        if self.len == 0 {
+            return Err(StackError::Underflow);
+        }
+        let top = self.slot(self.len - 1);
+        // SAFETY: the top slot of a non-empty stack is initialized.
+        Ok(unsafe { self.items[top].assume_init_mut() })
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        while self.pop().is_ok() {}
+        self.start = 0;
+    }
+
+    /// Returns an iterator from the top of the stack to the bottom.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        (0..self.len)
+            .rev()
+            .map(|index| self.get(index).expect("index is below len"))
+    }
+
+    /// Item `index` counted from the bottom.
+    fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: the first `len` slots from `start` are initialized.
+        Some(unsafe { self.items[self.slot(index)].assume_init_ref() })
+    }
+
+    /// Ring slot of item `index` counted from the bottom; `index <= N`.
+    fn slot(&self, index: usize) -> usize {
+        let slot = self.start + index;
+        if slot >= N {
+            slot - N
+        } else {
+            slot
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for InlineBoundedStack<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for InlineBoundedStack<T, N> {
+    fn default() -> Self {
+        InlineBoundedStack::new(OverflowPolicy::default())
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for InlineBoundedStack<T, N> {
+    fn clone(&self) -> Self {
+        let mut stack = InlineBoundedStack::new(self.policy);
+        for item in self.iter().rev() {
+            // The copy has the same capacity, so this never overflows.
+            let _ = stack.push(item.clone());
+        }
+        stack
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for InlineBoundedStack<T, N> {
+    /// Compares the items only, not the policy.
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for InlineBoundedStack<T, N> {}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for InlineBoundedStack<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display, const N: usize> fmt::Display for InlineBoundedStack<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_stack(f, self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Runs the same pushes on both stack types, checks that they agree and
+    /// returns what each push gave back, refused items as `Err`.
+    fn both<const N: usize>(
+        policy: OverflowPolicy,
+        pushes: &[u32],
+    ) -> Vec<Result<Option<u32>, u32>> {
+        let mut heap = BoundedStack::new(N, policy);
+        let mut inline = InlineBoundedStack::<u32, N>::new(policy);
+        let mut results = Vec::new();
+        for &item in pushes {
+            let (a, b) = (heap.push(item), inline.push(item));
+            assert_eq!(a, b);
+            results.push(a.map_err(OverflowError::into_inner));
+            assert!(heap.iter().eq(inline.iter()));
+            assert_eq!(heap.len(), inline.len());
+            assert_eq!(heap.is_full(), inline.is_full());
+        }
+        results
+    }
+
+    fn top_first<'a>(iter: impl Iterator<Item = &'a u32>) -> Vec<u32> {
+        iter.copied().collect()
+    }
+
+    #[test]
+    fn error_policy_hands_the_item_back() {
+        let results = both::<2>(OverflowPolicy::Error, &[1, 2, 3, 4]);
+        assert_eq!(results, [Ok(None), Ok(None), Err(3), Err(4)]);
+        let mut stack = BoundedStack::new(1, OverflowPolicy::Error);
+        stack.push(String::from("kept")).unwrap();
+        let error = stack.push(String::from("refused")).unwrap_err();
+        assert_eq!(error.to_string(), "stack overflow");
+        assert_eq!(format!("{error:?}"), "OverflowError { .. }");
+        assert_eq!(error.into_inner(), "refused");
+        assert_eq!(stack.peek().map(String::as_str), Ok("kept"));
+        assert_eq!(StackError::from(OverflowError(())), StackError::Overflow);
+    }
+
+    #[test]
+    fn drop_oldest_policy_evicts_the_bottom() {
+        let results = both::<3>(OverflowPolicy::DropOldest, &[1, 2, 3, 4, 5]);
+        assert_eq!(
+            results,
+            [Ok(None), Ok(None), Ok(None), Ok(Some(1)), Ok(Some(2))]
+        );
+        let mut stack = InlineBoundedStack::<u32, 3>::new(OverflowPolicy::DropOldest);
+        for item in 1..=5 {
+            stack.push(item).unwrap();
+        }
+        assert_eq!(top_first(stack.iter()), [5, 4, 3]);
+        assert_eq!(stack.pop_oldest(), Ok(3));
+        assert_eq!(stack.pop(), Ok(5));
+        assert_eq!(stack.pop(), Ok(4));
+        assert_eq!(stack.pop(), Err(StackError::Underflow));
+        assert_eq!(stack.pop_oldest(), Err(StackError::Underflow));
+        assert_eq!(stack.peek(), Err(StackError::Underflow));
+    }
+
+    #[test]
+    fn overwrite_top_policy_replaces_the_top() {
+        let results = both::<2>(OverflowPolicy::OverwriteTop, &[1, 2, 3, 4]);
+        assert_eq!(results, [Ok(None), Ok(None), Ok(Some(2)), Ok(Some(3))]);
+        let mut stack = InlineBoundedStack::<u32, 2>::new(OverflowPolicy::OverwriteTop);
+        for item in 1..=4 {
+            stack.push(item).unwrap();
+        }
+        assert_eq!(top_first(stack.iter()), [4, 1]);
+        *stack.peek_mut().unwrap() = 9;
+        assert_eq!(stack.to_string(), "Stack: 9 <- 1");
+    }
+
+    #[test]
+    fn zero_capacity_hands_every_item_back() {
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::OverwriteTop] {
+            let mut stack = BoundedStack::new(0, policy);
+            assert_eq!(stack.push(1), Ok(Some(1)));
+            assert!(stack.is_empty() && stack.is_full());
+        }
+        let mut stack = BoundedStack::new(0, OverflowPolicy::Error);
+        assert_eq!(stack.push(1).map_err(OverflowError::into_inner), Err(1));
+    }
+
+    #[test]
+    fn changing_the_policy_applies_to_later_pushes() {
+        let mut stack = InlineBoundedStack::<u32, 2>::default();
+        assert_eq!(stack.policy(), OverflowPolicy::Error);
+        stack.push(1).unwrap();
+        stack.push(2).unwrap();
+        assert!(stack.push(3).is_err());
+        stack.set_policy(OverflowPolicy::DropOldest);
+        assert_eq!(stack.push(3), Ok(Some(1)));
+        assert_eq!(top_first(stack.iter()), [3, 2]);
+    }
+
+    #[test]
+    fn ring_wraps_around_many_times() {
+        let policy = OverflowPolicy::DropOldest;
+        let mut heap = BoundedStack::new(5, policy);
+        let mut inline = InlineBoundedStack::<u32, 5>::new(policy);
+        let mut model = VecDeque::new();
+        for step in 0..500u32 {
+            match step % 7 {
+                3 => {
+                    let top = model.pop_back();
+                    assert_eq!(heap.pop().ok(), top);
+                    assert_eq!(inline.pop().ok(), top);
+                }
+                5 => {
+                    let oldest = model.pop_front();
+                    assert_eq!(heap.pop_oldest().ok(), oldest);
+                    assert_eq!(inline.pop_oldest().ok(), oldest);
+                }
+                _ => {
+                    let evicted = if model.len() == 5 {
+                        model.pop_front()
+                    } else {
+                        None
+                    };
+                    model.push_back(step);
+                    assert_eq!(heap.push(step), Ok(evicted));
+                    assert_eq!(inline.push(step), Ok(evicted));
+                }
+            }
+            assert!(inline.iter().eq(model.iter().rev()));
+            assert!(heap.iter().eq(model.iter().rev()));
+            assert_eq!(inline.clone(), inline);
+        }
+    }
+
+    #[test]
+    fn wrapped_items_are_dropped_once() {
+        let counter = DropCounter::new();
+        let mut stack = InlineBoundedStack::<_, 4>::new(OverflowPolicy::DropOldest);
+        for value in 0..10 {
+            drop(stack.push(counter.item(value)));
+        }
+        // Six items were evicted and dropped by the caller; the survivors sit
+        // across the end of the ring.
+        assert_eq!(counter.dropped(), 6);
+        let values: Vec<_> = stack.iter().map(|item| item.value).collect();
+        assert_eq!(values, [9, 8, 7, 6]);
+        assert_eq!(stack.pop_oldest().map(|item| item.value), Ok(6));
+        assert_eq!(counter.dropped(), 7);
+        stack.set_policy(OverflowPolicy::OverwriteTop);
+        stack.push(counter.item(10)).unwrap();
+        drop(stack.push(counter.item(11)));
+        assert_eq!(counter.dropped(), 8);
+        let refused = {
+            stack.set_policy(OverflowPolicy::Error);
+            stack.push(counter.item(12)).unwrap_err()
+        };
+        assert_eq!(counter.dropped(), 8);
+        drop(refused);
+        assert_eq!(counter.dropped(), 9);
+        drop(stack);
+        assert_eq!(counter.dropped(), 13);
+    }
+}
@@ -0,0 +1,105 @@
+//! Stack over the singly linked list, top at the head.
+
+use std::fmt;
+
+use super::{fmt_stack, Stack, StackError};
+use crate::linear::linked_list::{self, LinkedList};
+
+/// A stack stored in a [`LinkedList`], top at the head.
+///
+/// Every push allocates a node, but no push ever has to move existing items
+/// the way a growing array does.
+///
+/// | Operation      | Cost |
+/// |----------------|------|
+/// | `push`         | O(1) |
+/// | `pop`, `peek`  | O(1) |
+/// | `len`          | O(1) |
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ListStack<T> {
+    items: LinkedList<T>,
+}
+
+impl<T> ListStack<T> {
+    /// Creates an empty stack.
+    pub const fn new() -> Self {
+        ListStack {
+            items: LinkedList::new(),
+        }
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns an iterator from the top of the stack to the bottom.
+    pub fn iter(&self) -> linked_list::Iter<'_, T> {
+        self.items.iter()
+    }
+}
+
+impl<T> Stack<T> for ListStack<T> {
+    fn push(&mut self, item: T) {
+        self.items.insert_at_beginning(item);
+    }
+
+    fn pop(&mut self) -> Result<T, StackError> {
+        self.items
+            .delete_from_beginning()
+            .map_err(|_| StackError::Underflow)
+    }
+
+    fn peek(&self) -> Result<&T, StackError> {
+        self.items.head().ok_or(StackError::Underflow)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<T> Default for ListStack<T> {
+    fn default() -> Self {
+        ListStack::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ListStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for ListStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_stack(f, self)
+    }
+}
+
+impl<T> Extend<T> for ListStack<T> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ListStack<T> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut stack = ListStack::new();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ListStack<T> {
+    type Item = &'a T;
+    type IntoIter = linked_list::Iter<'a, T>;
+
+    fn into_iter(self) -> linked_list::Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,117 @@
+//! Associative operations that [`AggStack`](super::AggStack) and
+//! [`AggQueue`](super::AggQueue) keep up to date.
+
+use std::cmp::Ordering;
+use std::ops::Add;
+
+/// An associative way to combine items into an aggregate.
+///
+/// `combine` must be associative but need not be commutative: aggregates are
+/// always combined oldest (`left`) to newest (`right`). No identity element
+/// is required; an empty stack or queue simply has no aggregate, which plays
+/// the role of the identity. Monoids that have one can report it through
+/// [`identity`](Monoid::identity).
+pub trait Monoid<T> {
+    /// The aggregate type, often `T` itself.
+    type Value: Clone;
+
+    /// The value that combined with any other on either side gives that
+    /// value back, if there is one. The default has none.
+    fn identity() -> Option<Self::Value> {
+        None
+    }
+
+    /// Aggregate of the single item `item`.
+    fn lift(item: &T) -> Self::Value;
+
+    /// Aggregate of `left` followed by `right`.
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+}
+
+/// Smallest item, the counterpart of `MaxStack`'s `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Min;
+
+/// Largest item, as tracked by the Ruby `MaxStack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Max;
+
+/// Sum of all items. Integer overflow behaves as it does for `+`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Sum;
+
+/// Greatest common divisor of all items, for unsigned integers. The identity
+/// is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gcd;
+
+impl<T: Ord + Clone> Monoid<T> for Min {
+    type Value = T;
+
+    fn lift(item: &T) -> T {
+        item.clone()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        match right.cmp(left) {
+            Ordering::Less => right.clone(),
+            _ => left.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> Monoid<T> for Max {
+    type Value = T;
+
+    fn lift(item: &T) -> T {
+        item.clone()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        match right.cmp(left) {
+            Ordering::Greater => right.clone(),
+            _ => left.clone(),
+        }
+    }
+}
+
+impl<T> Monoid<T> for Sum
+where
+    T: Clone + Add<Output = T>,
+{
+    type Value = T;
+
+    fn lift(item: &T) -> T {
+        item.clone()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        left.clone() + right.clone()
+    }
+}
+
+macro_rules! gcd_impl {
+    ($($int:ty),*) => {$(
+        impl Monoid<$int> for Gcd {
+            type Value = $int;
+
+            fn identity() -> Option<$int> {
+                Some(0)
+            }
+
+            fn lift(item: &$int) -> $int {
+                *item
+            }
+
+            fn combine(left: &$int, right: &$int) -> $int {
+                let (mut a, mut b) = (*left, *right);
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+                a
+            }
+        }
+    )*};
+}
+
+gcd_impl!(u8, u16, u32, u64, u128, usize);
@@ -0,0 +1,327 @@
+//! Lock-free stack shared between threads.
+//!
+//! [`TreiberStack`] is the classic Treiber stack: the top is an atomic
+//! pointer, and both `push` and `pop` prepare a change and install it with a
+//! compare-and-swap, retrying if another thread got there first.
+//!
+//! The hard part is freeing popped nodes. A thread that has read the top
+//! pointer may still dereference it after another thread popped it, and if
+//! the node were freed and its address reused by a later push, the first
+//! thread's compare-and-swap would succeed on a different node (the ABA
+//! problem). Nodes are therefore reclaimed with hazard pointers: before
+//! dereferencing the top, a popping thread publishes it in a hazard slot and
+//! checks that it is still the top. Popped nodes are retired to a list and
+//! only freed once no hazard slot points at them.
+//!
+//! Built with `--cfg loom`, the atomics come from the `loom` model checker so
+//! the tests in `tests/treiber_loom.rs` can explore every interleaving.
+
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use super::StackError;
+
+/// Retired nodes are scanned once this many have piled up. Under loom every
+/// retirement scans, so that reclamation is part of every model.
+#[cfg(not(loom))]
+const RECLAIM_BATCH: usize = 64;
+#[cfg(loom)]
+const RECLAIM_BATCH: usize = 1;
+
+struct Node<T> {
+    /// Moved out by the thread whose `pop` unlinks the node.
+    data: ManuallyDrop<T>,
+    /// Written before the node is published, read-only afterwards.
+    next: *mut Node<T>,
+    /// Link in the retired list; separate from `next`, which other threads
+    /// may still be reading when the node is retired.
+    next_retired: *mut Node<T>,
+}
+
+/// A slot in which one thread at a time announces the node it is reading.
+struct Hazard {
+    pointer: AtomicPtr<()>,
+    in_use: AtomicBool,
+    /// Slots are only ever added, at the head, and freed with the stack.
+    next: *mut Hazard,
+}
+
+/// A lock-free LIFO stack for many threads, with hazard-pointer reclamation.
+///
+/// All operations take `&self`; share the stack with an `Arc`. There is no
+/// `peek`, since a reference to the top item could outlive a concurrent
+/// `pop`, so it does not implement [`Stack`](super::Stack).
+///
+/// | Operation     | Cost                                      |
+/// |---------------|-------------------------------------------|
+/// | `push`        | O(1) per attempt, lock-free               |
+/// | `pop`         | O(threads) per call, lock-free            |
+/// | `len`         | O(1), a snapshot under concurrent updates |
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    len: AtomicUsize,
+    hazards: AtomicPtr<Hazard>,
+    retired: AtomicPtr<Node<T>>,
+    retired_count: AtomicUsize,
+}
+
+// SAFETY: items move between threads through the stack but are never shared:
+// exactly one thread moves each item in and one moves it out. The raw
+// pointers are managed by the stack itself.
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+// SAFETY: as above; `&TreiberStack` only hands out owned items.
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        TreiberStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            hazards: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of items. Under concurrent updates this is only a snapshot,
+    /// and may briefly count an item whose push has not finished.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the stack held no items when checked.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Puts `item` on top of the stack.
+    pub fn push(&self, item: T) {
+        let node = Box::into_raw(Box::new(Node {
+            data: ManuallyDrop::new(item),
+            next: ptr::null_mut(),
+            next_retired: ptr::null_mut(),
+        }));
+        // Counted before it is published, so a racing `pop` can never
+        // decrement below zero.
+        self.len.fetch_add(1, Ordering::Relaxed);
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: `node` is not published yet, so this thread owns it.
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::SeqCst, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Removes and returns the top item.
+    pub fn pop(&self) -> Result<T, StackError> {
+        let hazard = self.acquire_hazard();
+        let unlinked = loop {
+            let head = self.head.load(Ordering::Acquire);
+            if head.is_null() {
+                break None;
+            }
+            // Announce `head`, then make sure it was still the top after the
+            // announcement: from then on no scan can free it.
+            hazard.pointer.store(head.cast(), Ordering::SeqCst);
+            if self.head.load(Ordering::SeqCst) != head {
+                continue;
+            }
+            // SAFETY: `head` is protected by the hazard slot, so it has not
+            // been freed, and `next` never changes after publication.
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                break Some(head);
+            }
+        };
+        hazard.pointer.store(ptr::null_mut(), Ordering::Release);
+        hazard.in_use.store(false, Ordering::Release);
+        let node = unlinked.ok_or(StackError::Underflow)?;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        // SAFETY: winning the exchange makes this thread the only one that
+        // takes the item, and the node is not freed before it is retired.
+        // Other threads only read `next`.
+        let item = unsafe { ManuallyDrop::take(&mut (*node).data) };
+        self.retire(node);
+        Ok(item)
+    }
+
+    /// Claims a free hazard slot, adding one if all are taken.
+    fn acquire_hazard(&self) -> &Hazard {
+        let mut current = self.hazards.load(Ordering::Acquire);
+        while !current.is_null() {
+            // SAFETY: slots live as long as the stack.
+            let hazard = unsafe { &*current };
+            if hazard
+                .in_use
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return hazard;
+            }
+            current = hazard.next;
+        }
+        let hazard = Box::into_raw(Box::new(Hazard {
+            pointer: AtomicPtr::new(ptr::null_mut()),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the slot is not published yet.
+            unsafe { (*hazard).next = head };
+            match self.hazards.compare_exchange_weak(
+                head,
+                hazard,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                // SAFETY: slots live as long as the stack.
+                Ok(_) => return unsafe { &*hazard },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Queues an unlinked node for freeing, and frees what it can once
+    /// enough nodes are queued.
+    fn retire(&self, node: *mut Node<T>) {
+        // Counted before it is listed, so a racing `reclaim` can never
+        // subtract it first.
+        let count = self.retired_count.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the node is unlinked and only this thread retires it.
+            unsafe { (*node).next_retired = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                node,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+        if count >= RECLAIM_BATCH {
+            self.reclaim();
+        }
+    }
+
+    /// Frees every retired node that no hazard slot points at and puts the
+    /// rest back.
+    fn reclaim(&self) {
+        let mut node = self.retired.swap(ptr::null_mut(), Ordering::SeqCst);
+        if node.is_null() {
+            return;
+        }
+        let mut protected = Vec::new();
+        let mut hazard = self.hazards.load(Ordering::Acquire);
+        while !hazard.is_null() {
+            // SAFETY: slots live as long as the stack.
+            let slot = unsafe { &*hazard };
+            protected.push(slot.pointer.load(Ordering::SeqCst));
+            hazard = slot.next;
+        }
+        let mut freed = 0;
+        while !node.is_null() {
+            // SAFETY: taken from the retired list, so this thread owns it.
+            let next = unsafe { (*node).next_retired };
+            if protected.contains(&node.cast()) {
+                self.retire_again(node);
+            } else {
+                // SAFETY: unlinked, retired and unprotected: nobody can reach
+                // it any more. Its item was already moved out.
+                drop(unsafe { Box::from_raw(node) });
+                freed += 1;
+            }
+            node = next;
+        }
+        self.retired_count.fetch_sub(freed, Ordering::Relaxed);
+    }
+
+    /// Puts a still-protected node back on the retired list.
+    fn retire_again(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: this thread owns the node until it is re-published.
+            unsafe { (*node).next_retired = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                node,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        TreiberStack::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        // `&mut self`: no other thread can touch the stack any more.
+        let mut node = self.head.swap(ptr::null_mut(), Ordering::Relaxed);
+        while !node.is_null() {
+            // SAFETY: linked nodes still own their items.
+            let mut boxed = unsafe { Box::from_raw(node) };
+            // SAFETY: the item was never taken.
+            unsafe { ManuallyDrop::drop(&mut boxed.data) };
+            node = boxed.next;
+        }
+        let mut node = self.retired.swap(ptr::null_mut(), Ordering::Relaxed);
+        while !node.is_null() {
+            // SAFETY: retired nodes no longer own items.
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next_retired;
+        }
+        let mut hazard = self.hazards.swap(ptr::null_mut(), Ordering::Relaxed);
+        while !hazard.is_null() {
+            // SAFETY: slots are freed only here.
+            let boxed = unsafe { Box::from_raw(hazard) };
+            hazard = boxed.next;
+        }
+    }
+}
+
+impl<T> fmt::Debug for TreiberStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreiberStack")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> FromIterator<T> for TreiberStack<T> {
+    /// Pushes the items in order, so the last one ends up on top.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let stack = TreiberStack::new();
+        for item in iter {
+            stack.push(item);
+        }
+        stack
+    }
+}
@@ -0,0 +1,716 @@
+//! Undo and redo: see `Linear/Stack.md`.
+//!
+//! Undo history is the textbook pair of stacks. Applying a command pushes it
+//! on the undo stack and clears the redo stack; undoing moves the top command
+//! to the redo stack and redoing moves it back. [`UndoStack`] adds what
+//! editors need on top: consecutive commands that merge (typing a word is
+//! one undo step, not one per letter), groups that undo as a unit, a limit
+//! on how much history is kept, and a clean point that tells whether the
+//! document differs from what was last saved.
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use super::stack::{ArrayStack, BoundedStack, OverflowPolicy, Stack};
+
+/// A reversible change to a `Target`.
+pub trait Command: Sized {
+    type Target: ?Sized;
+
+    /// Makes the change.
+    fn apply(&mut self, target: &mut Self::Target);
+
+    /// Reverts a change made by [`apply`](Command::apply).
+    fn undo(&mut self, target: &mut Self::Target);
+
+    /// Folds `next`, which has just been applied after `self`, into `self`
+    /// so that one undo reverts both, or hands it back. The default never
+    /// merges.
+    fn merge(&mut self, next: Self) -> Result<(), Self> {
+        Err(next)
+    }
+
+    /// Approximate memory the command holds, in bytes, counted against the
+    /// history's memory limit. The default is the command's own size;
+    /// commands owning heap data should add it.
+    fn size(&self) -> usize {
+        mem::size_of_val(self)
+    }
+}
+
+/// Why an [`UndoStack`] operation was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UndoError {
+    /// `undo` with no history.
+    NothingToUndo,
+    /// `redo` after the last undo was overwritten, or with none.
+    NothingToRedo,
+    /// `undo` or `redo` while a group is open.
+    GroupOpen,
+    /// `end_group` or `cancel_group` without an open group.
+    NoGroup,
+}
+
+impl fmt::Display for UndoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            UndoError::NothingToUndo => "nothing to undo",
+            UndoError::NothingToRedo => "nothing to redo",
+            UndoError::GroupOpen => "a group is open",
+            UndoError::NoGroup => "no group is open",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for UndoError {}
+
+/// One undo step: a single command or a closed group.
+#[derive(Debug, Clone)]
+struct Entry<C> {
+    /// In the order they were applied.
+    commands: Vec<C>,
+    size: usize,
+    /// Set for a closed group, which later commands never merge into, even
+    /// if it holds a single command.
+    is_group: bool,
+}
+
+impl<C: Command> Entry<C> {
+    fn new(commands: Vec<C>, is_group: bool) -> Self {
+        let size = commands.iter().map(Command::size).sum();
+        Entry {
+            commands,
+            size,
+            is_group,
+        }
+    }
+
+    fn undo(&mut self, target: &mut C::Target) {
+        for command in self.commands.iter_mut().rev() {
+            command.undo(target);
+        }
+    }
+
+    fn redo(&mut self, target: &mut C::Target) {
+        for command in &mut self.commands {
+            command.apply(target);
+        }
+    }
+}
+
+/// Undo/redo history of [`Command`]s.
+///
+/// The history keeps at most `capacity` undo steps and, if a memory limit
+/// is set, at most that many bytes of commands; beyond either, the oldest
+/// steps are forgotten.
+///
+/// ```
+/// use data_structure::linear::undo::{Command, UndoStack};
+///
+/// struct Append(String);
+///
+/// impl Command for Append {
+///     type Target = String;
+///
+///     fn apply(&mut self, text: &mut String) {
+///         text.push_str(&self.0);
+///     }
+///
+///     fn undo(&mut self, text: &mut String) {
+///         text.truncate(text.len() - self.0.len());
+///     }
+///
+///     fn merge(&mut self, next: Self) -> Result<(), Self> {
+///         if self.0.ends_with(' ') || next.0.starts_with(' ') {
+///             return Err(next);
+///         }
+///         self.0.push_str(&next.0);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut text = String::new();
+/// let mut history = UndoStack::new(100);
+/// for word in ["Hello", " ", "wor", "ld"] {
+///     history.apply(Append(word.to_owned()), &mut text);
+/// }
+/// history.undo(&mut text).unwrap();
+/// assert_eq!(text, "Hello ");
+/// history.redo(&mut text).unwrap();
+/// assert_eq!(text, "Hello world");
+/// ```
+///
+/// | Operation             | Cost                               |
+/// |-----------------------|------------------------------------|
+/// | `apply`               | O(1) plus dropping the redo steps  |
+/// | `undo`, `redo`        | O(commands in the step)            |
+/// | `is_clean`            | O(1)                               |
+#[derive(Debug)]
+pub struct UndoStack<C: Command> {
+    undo: BoundedStack<Entry<C>>,
+    redo: ArrayStack<Entry<C>>,
+    /// Commands of the open group, if any.
+    group: Option<Vec<C>>,
+    /// Nesting depth of `begin_group` calls.
+    depth: usize,
+    /// Undo steps in the clean state; `None` once that state is out of
+    /// reach.
+    clean: Option<usize>,
+    /// Bytes held by both stacks.
+    size: usize,
+    memory_limit: Option<usize>,
+}
+
+impl<C: Command> UndoStack<C> {
+    /// Creates an empty history keeping up to `capacity` undo steps. The
+    /// empty state counts as clean.
+    pub fn new(capacity: usize) -> Self {
+        UndoStack {
+            undo: BoundedStack::new(capacity, OverflowPolicy::DropOldest),
+            redo: ArrayStack::new(),
+            group: None,
+            depth: 0,
+            clean: Some(0),
+            size: 0,
+            memory_limit: None,
+        }
+    }
+
+    /// Also forgets the oldest steps while the commands in the history hold
+    /// more than `bytes`, as reported by [`Command::size`]. Redo steps count
+    /// too, but only undo steps are evicted.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self.enforce_memory_limit();
+        self
+    }
+
+    /// Maximum number of undo steps.
+    pub fn capacity(&self) -> usize {
+        self.undo.capacity()
+    }
+
+    /// Bytes held by the history, as reported by [`Command::size`].
+    pub fn memory_usage(&self) -> usize {
+        self.size
+    }
+
+    /// Number of steps that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of steps that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Returns `true` if [`undo`](Self::undo) would succeed.
+    pub fn can_undo(&self) -> bool {
+        self.group.is_none() && !self.undo.is_empty()
+    }
+
+    /// Returns `true` if [`redo`](Self::redo) would succeed.
+    pub fn can_redo(&self) -> bool {
+        self.group.is_none() && !self.redo.is_empty()
+    }
+
+    /// Applies `command` to `target` and records it.
+    ///
+    /// Redo steps are dropped. Inside a group the command joins the group;
+    /// otherwise it is merged into the previous step if that is a single
+    /// command, not a group, that accepts it and not the clean point.
+    pub fn apply(&mut self, mut command: C, target: &mut C::Target) {
+        command.apply(target);
+        self.clear_redo();
+        if let Some(group) = &mut self.group {
+            if let Some(last) = group.last_mut() {
+                let before = last.size();
+                match last.merge(command) {
+                    Ok(()) => {
+                        self.size = self.size - before + last.size();
+                        return;
+                    }
+                    Err(unmerged) => command = unmerged,
+                }
+            }
+            self.size += command.size();
+            group.push(command);
+            return;
+        }
+        let at_clean = self.clean == Some(self.undo.len());
+        if let Ok(top) = self.undo.peek_mut() {
+            if !at_clean && !top.is_group {
+                match top.commands[0].merge(command) {
+                    Ok(()) => {
+                        self.size -= top.size;
+                        top.size = top.commands[0].size();
+                        self.size += top.size;
+                        self.enforce_memory_limit();
+                        return;
+                    }
+                    Err(unmerged) => command = unmerged,
+                }
+            }
+        }
+        self.push_undo(Entry::new(vec![command], false));
+    }
+
+    /// Reverts the last step.
+    pub fn undo(&mut self, target: &mut C::Target) -> Result<(), UndoError> {
+        if self.group.is_some() {
+            return Err(UndoError::GroupOpen);
+        }
+        let mut entry = self.undo.pop().map_err(|_| UndoError::NothingToUndo)?;
+        entry.undo(target);
+        self.redo.push(entry);
+        Ok(())
+    }
+
+    /// Re-applies the last undone step.
+    pub fn redo(&mut self, target: &mut C::Target) -> Result<(), UndoError> {
+        if self.group.is_some() {
+            return Err(UndoError::GroupOpen);
+        }
+        let mut entry = self.redo.pop().map_err(|_| UndoError::NothingToRedo)?;
+        entry.redo(target);
+        // Undone steps were on the undo stack, so there is room for them.
+        let _ = self.undo.push(entry);
+        Ok(())
+    }
+
+    /// Opens a group: commands applied until the matching
+    /// [`end_group`](Self::end_group) form one undo step. Groups nest; only
+    /// the outermost one makes a step.
+    pub fn begin_group(&mut self) {
+        self.depth += 1;
+        self.group.get_or_insert_with(Vec::new);
+    }
+
+    /// Closes the innermost open group. Closing the outermost records its
+    /// commands as one step, or nothing if there were none.
+    pub fn end_group(&mut self) -> Result<(), UndoError> {
+        if self.depth == 0 {
+            return Err(UndoError::NoGroup);
+        }
+        self.depth -= 1;
+        if self.depth == 0 {
+            let commands = self.group.take().unwrap_or_default();
+            if !commands.is_empty() {
+                let entry = Entry::new(commands, true);
+                self.size -= entry.size;
+                self.push_undo(entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes every command of the open groups, innermost and outermost
+    /// alike, and closes them, leaving no step behind.
+    pub fn cancel_group(&mut self, target: &mut C::Target) -> Result<(), UndoError> {
+        let commands = self.group.take().ok_or(UndoError::NoGroup)?;
+        self.depth = 0;
+        let mut entry = Entry::new(commands, true);
+        entry.undo(target);
+        self.size -= entry.size;
+        Ok(())
+    }
+
+    /// Marks the current state as clean, typically after saving.
+    pub fn set_clean(&mut self) {
+        self.clean = Some(self.undo.len());
+    }
+
+    /// Returns `true` if undo and redo have returned to the clean state and
+    /// no group is open.
+    pub fn is_clean(&self) -> bool {
+        self.group.is_none() && self.clean == Some(self.undo.len())
+    }
+
+    /// Forgets all history. The current state becomes clean.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+        self.depth = 0;
+        self.clean = Some(0);
+        self.size = 0;
+    }
+
+    fn push_undo(&mut self, entry: Entry<C>) {
+        self.size += entry.size;
+        if let Ok(Some(evicted)) = self.undo.push(entry) {
+            self.evicted(evicted);
+        }
+        self.enforce_memory_limit();
+    }
+
+    fn clear_redo(&mut self) {
+        if self.redo.is_empty() {
+            return;
+        }
+        while let Ok(entry) = self.redo.pop() {
+            self.size -= entry.size;
+        }
+        // A clean state among the dropped steps can no longer be reached.
+        if self.clean.is_some_and(|clean| clean > self.undo.len()) {
+            self.clean = None;
+        }
+    }
+
+    fn enforce_memory_limit(&mut self) {
+        let Some(limit) = self.memory_limit else {
+            return;
+        };
+        while self.size > limit {
+            match self.undo.pop_oldest() {
+                Ok(entry) => self.evicted(entry),
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Accounts for the oldest undo step having been dropped.
+    fn evicted(&mut self, entry: Entry<C>) {
+        self.size -= entry.size;
+        self.clean = self.clean.and_then(|clean| clean.checked_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends text; runs of letters merge, anything next to a space does
+    /// not. Counts one byte per character.
+    #[derive(Debug)]
+    struct Append(String);
+
+    impl Command for Append {
+        type Target = String;
+
+        fn apply(&mut self, text: &mut String) {
+            text.push_str(&self.0);
+        }
+
+        fn undo(&mut self, text: &mut String) {
+            text.truncate(text.len() - self.0.len());
+        }
+
+        fn merge(&mut self, next: Self) -> Result<(), Self> {
+            if self.0.ends_with(' ') || next.0.starts_with(' ') {
+                return Err(next);
+            }
+            self.0.push_str(&next.0);
+            Ok(())
+        }
+
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    fn append(text: &str) -> Append {
+        Append(text.to_owned())
+    }
+
+    fn history(capacity: usize, words: &[&'static str]) -> (UndoStack<Append>, String) {
+        let mut history = UndoStack::new(capacity);
+        let mut text = String::new();
+        for word in words {
+            history.apply(append(word), &mut text);
+        }
+        (history, text)
+    }
+
+    #[test]
+    fn merges_runs_of_letters_into_one_step() {
+        let (mut history, mut text) = history(10, &["ab", "c", " ", "d"]);
+        assert_eq!(text, "abc d");
+        assert_eq!(history.undo_len(), 3);
+        assert_eq!(history.memory_usage(), 5);
+        history.undo(&mut text).unwrap();
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "abc");
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "");
+        assert_eq!(history.undo(&mut text), Err(UndoError::NothingToUndo));
+    }
+
+    #[test]
+    fn never_merges_into_a_closed_group() {
+        let (mut history, mut text) = history(10, &[]);
+        history.begin_group();
+        history.apply(append("a"), &mut text);
+        history.end_group().unwrap();
+        history.apply(append("b"), &mut text);
+        assert_eq!(history.undo_len(), 2);
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "a");
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "");
+        // The group stays a group across undo and redo.
+        history.redo(&mut text).unwrap();
+        history.apply(append("c"), &mut text);
+        assert_eq!(history.undo_len(), 2);
+    }
+
+    #[test]
+    fn never_merges_into_the_clean_point() {
+        let (mut history, mut text) = history(10, &["a"]);
+        history.set_clean();
+        history.apply(append("b"), &mut text);
+        assert_eq!(history.undo_len(), 2);
+        history.undo(&mut text).unwrap();
+        assert!(history.is_clean());
+        assert_eq!(text, "a");
+    }
+
+    #[test]
+    fn groups_undo_and_redo_as_one_step() {
+        let (mut history, mut text) = history(10, &["x "]);
+        history.begin_group();
+        history.apply(append("a "), &mut text);
+        history.begin_group();
+        history.apply(append("b "), &mut text);
+        history.end_group().unwrap();
+        assert_eq!(history.undo(&mut text), Err(UndoError::GroupOpen));
+        assert!(!history.can_undo());
+        history.end_group().unwrap();
+        assert_eq!(history.end_group(), Err(UndoError::NoGroup));
+        assert_eq!(history.undo_len(), 2);
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "x ");
+        history.redo(&mut text).unwrap();
+        assert_eq!(text, "x a b ");
+    }
+
+    #[test]
+    fn cancel_group_reverts_and_records_nothing() {
+        let (mut history, mut text) = history(10, &["x "]);
+        history.begin_group();
+        history.begin_group();
+        history.apply(append("a "), &mut text);
+        history.apply(append("b"), &mut text);
+        assert_eq!(history.memory_usage(), 5);
+        history.cancel_group(&mut text).unwrap();
+        assert_eq!(text, "x ");
+        assert_eq!(history.undo_len(), 1);
+        assert_eq!(history.memory_usage(), 2);
+        assert_eq!(history.end_group(), Err(UndoError::NoGroup));
+        assert_eq!(history.cancel_group(&mut text), Err(UndoError::NoGroup));
+    }
+
+    #[test]
+    fn capacity_drops_the_oldest_steps() {
+        let (mut history, mut text) = history(2, &["a ", "b ", "c "]);
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(history.memory_usage(), 4);
+        history.undo(&mut text).unwrap();
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "a ");
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn memory_limit_drops_the_oldest_steps() {
+        let mut history = UndoStack::new(10).with_memory_limit(6);
+        let mut text = String::new();
+        for word in ["ab ", "cd ", "ef "] {
+            history.apply(append(word), &mut text);
+        }
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(history.memory_usage(), 6);
+        // Merging grows the top step, which pushes older ones out.
+        history.apply(append("g"), &mut text);
+        history.apply(append("hi"), &mut text);
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(history.memory_usage(), 6);
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "ab cd ef ");
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "ab cd ");
+        assert!(!history.can_undo());
+        // Redo steps count until a new command drops them.
+        assert_eq!(history.memory_usage(), 6);
+        history.apply(append(" "), &mut text);
+        assert_eq!(history.memory_usage(), 1);
+    }
+
+    #[test]
+    fn memory_limit_applies_to_existing_history() {
+        let (history, _) = history(10, &["ab ", "cd ", "ef "]);
+        let history = history.with_memory_limit(4);
+        assert_eq!(history.undo_len(), 1);
+        assert_eq!(history.memory_usage(), 3);
+    }
+
+    #[test]
+    fn memory_limit_evicts_whole_groups() {
+        let mut history = UndoStack::new(10).with_memory_limit(6);
+        let mut text = String::new();
+        history.apply(append("ab "), &mut text);
+        history.begin_group();
+        history.apply(append("cd "), &mut text);
+        history.apply(append("ef "), &mut text);
+        // An open group is never evicted from.
+        assert_eq!(history.memory_usage(), 9);
+        assert_eq!(history.undo_len(), 1);
+        history.end_group().unwrap();
+        assert_eq!(history.undo_len(), 1);
+        assert_eq!(history.memory_usage(), 6);
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "ab ");
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn a_group_over_the_memory_limit_is_not_kept() {
+        let mut history = UndoStack::new(10).with_memory_limit(4);
+        let mut text = String::new();
+        history.begin_group();
+        history.apply(append("ab "), &mut text);
+        history.apply(append("cd "), &mut text);
+        history.end_group().unwrap();
+        assert_eq!(text, "ab cd ");
+        assert_eq!(history.undo_len(), 0);
+        assert_eq!(history.memory_usage(), 0);
+    }
+
+    #[test]
+    fn capacity_counts_a_group_as_one_step() {
+        let mut history = UndoStack::new(1);
+        let mut text = String::new();
+        history.apply(append("x "), &mut text);
+        history.begin_group();
+        history.apply(append("a "), &mut text);
+        history.apply(append("b "), &mut text);
+        history.end_group().unwrap();
+        assert_eq!(history.undo_len(), 1);
+        assert_eq!(history.memory_usage(), 4);
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "x ");
+    }
+
+    #[test]
+    fn clean_point_is_tracked_through_undo_and_redo() {
+        let (mut history, mut text) = history(10, &["a "]);
+        assert!(!history.is_clean());
+        history.set_clean();
+        history.apply(append("b "), &mut text);
+        history.apply(append("c "), &mut text);
+        assert!(!history.is_clean());
+        history.undo(&mut text).unwrap();
+        history.undo(&mut text).unwrap();
+        assert!(history.is_clean());
+        history.undo(&mut text).unwrap();
+        assert!(!history.is_clean());
+        history.redo(&mut text).unwrap();
+        assert!(history.is_clean());
+        history.begin_group();
+        assert!(!history.is_clean());
+    }
+
+    #[test]
+    fn dropping_the_redo_steps_loses_a_clean_point_among_them() {
+        let (mut history, mut text) = history(10, &["a ", "b "]);
+        history.set_clean();
+        history.undo(&mut text).unwrap();
+        history.apply(append("c "), &mut text);
+        for _ in 0..2 {
+            assert!(!history.is_clean());
+            history.undo(&mut text).unwrap();
+        }
+        assert!(!history.is_clean());
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn dropping_the_redo_steps_keeps_an_earlier_clean_point() {
+        let (mut history, mut text) = history(10, &["a "]);
+        history.set_clean();
+        history.apply(append("b "), &mut text);
+        history.apply(append("c "), &mut text);
+        history.undo(&mut text).unwrap();
+        history.apply(append("d "), &mut text);
+        assert!(!history.is_clean());
+        history.undo(&mut text).unwrap();
+        history.undo(&mut text).unwrap();
+        assert!(history.is_clean());
+        assert_eq!(text, "a ");
+    }
+
+    #[test]
+    fn evicting_the_clean_state_loses_it() {
+        let (mut history, mut text) = history(2, &[]);
+        history.set_clean();
+        for word in ["a ", "b ", "c "] {
+            history.apply(append(word), &mut text);
+        }
+        history.undo(&mut text).unwrap();
+        history.undo(&mut text).unwrap();
+        assert_eq!(text, "a ");
+        assert!(!history.is_clean());
+    }
+
+    #[test]
+    fn evicting_older_steps_keeps_the_clean_state() {
+        let mut history = UndoStack::new(10).with_memory_limit(4);
+        let mut text = String::new();
+        history.apply(append("a "), &mut text);
+        history.set_clean();
+        history.apply(append("b "), &mut text);
+        history.apply(append("c "), &mut text);
+        assert_eq!(history.undo_len(), 2);
+        history.undo(&mut text).unwrap();
+        assert!(!history.is_clean());
+        history.undo(&mut text).unwrap();
+        assert!(history.is_clean());
+        assert_eq!(text, "a ");
+    }
+
+    #[test]
+    fn clear_forgets_everything_and_is_clean() {
+        let (mut history, mut text) = history(10, &["a ", "b "]);
+        history.undo(&mut text).unwrap();
+        history.begin_group();
+        history.clear();
+        assert!(history.is_clean());
+        assert_eq!(history.memory_usage(), 0);
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+        assert_eq!(history.end_group(), Err(UndoError::NoGroup));
+    }
+
+    #[test]
+    fn drops_every_command_once() {
+        use crate::testing::DropCounter;
+
+        struct Noop<T>(T);
+
+        impl<T> Command for Noop<T> {
+            type Target = ();
+
+            fn apply(&mut self, _: &mut ()) {}
+
+            fn undo(&mut self, _: &mut ()) {}
+        }
+
+        let counter = DropCounter::new();
+        let mut history = UndoStack::new(3);
+        for i in 0..5 {
+            history.apply(Noop(counter.item(i)), &mut ());
+        }
+        assert_eq!(counter.dropped(), 2);
+        history.undo(&mut ()).unwrap();
+        history.begin_group();
+        history.apply(Noop(counter.item(5)), &mut ());
+        assert_eq!(counter.dropped(), 3);
+        history.cancel_group(&mut ()).unwrap();
+        assert_eq!(counter.dropped(), 4);
+        drop(history);
+        assert_eq!(counter.dropped(), 6);
+    }
+}
@@ -0,0 +1,806 @@
+//! Unrolled linked list: a linked list of small inline arrays.
+//!
+//! `Linear/Array.md` and `Linear/LinkedList.md` present contiguous storage
+//! and one-node-per-element as opposites. An unrolled list sits in between:
+//! each node of a [`DoublyLinkedList`] holds up to `B` elements inline, so
+//! traversal touches `n / B` nodes and insertions in the middle shift at most
+//! `B` elements. Nodes are split when they overflow and merged with their
+//! neighbour when they drop below half full, which keeps every node except
+//! possibly the last at least half full.
+
+use std::fmt;
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::{ptr, slice};
+
+use super::doubly_linked_list::{self, DoublyLinkedList};
+use super::ListError;
+
+/// Fixed-capacity inline buffer stored in every node.
+struct Chunk<T, const B: usize> {
+    len: usize,
+    items: [MaybeUninit<T>; B],
+}
+
+impl<T, const B: usize> Chunk<T, B> {
+    fn new() -> Self {
+        Chunk {
+            len: 0,
+            items: [const { MaybeUninit::uninit() }; B],
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == B
+    }
+
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts(self.items.as_ptr().cast(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts_mut(self.items.as_mut_ptr().cast(), self.len) }
+    }
+
+    fn insert(&mut self, index: usize, value: T) {
+        assert!(self.len < B && index <= self.len);
+        // SAFETY: `index <= len < B`, so shifting `len - index` items one
+        // slot right stays in bounds.
+        unsafe {
+            let p = self.items.as_mut_ptr().add(index).cast::<T>();
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+        // SAFETY: `index < len`; the item is read out before the tail is
+        // shifted over it.
+        let value = unsafe {
+            let p = self.items.as_mut_ptr().add(index).cast::<T>();
+            let value = p.read();
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            value
+        };
+        self.len -= 1;
+        value
+    }
+
+    /// Moves the items from `at` onwards into a new chunk.
+    fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len);
+        let mut other = Chunk::new();
+        // SAFETY: the moved range is initialized here and the destination
+        // is empty; ownership moves with the lengths.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.items.as_ptr().add(at),
+                other.items.as_mut_ptr(),
+                self.len - at,
+            );
+        }
+        other.len = self.len - at;
+        self.len = at;
+        other
+    }
+
+    /// Moves every item of `other` to the end of this chunk.
+    fn append(&mut self, other: &mut Self) {
+        assert!(self.len + other.len <= B);
+        // SAFETY: the destination has room and the source range is
+        // initialized; ownership moves with the lengths.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.items.as_ptr(),
+                self.items.as_mut_ptr().add(self.len),
+                other.len,
+            );
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+}
+
+impl<T, const B: usize> Drop for Chunk<T, B> {
+    fn drop(&mut self) {
+        // SAFETY: drops exactly the initialized items.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T: Clone, const B: usize> Clone for Chunk<T, B> {
+    fn clone(&self) -> Self {
+        let mut chunk = Chunk::new();
+        for item in self.as_slice() {
+            chunk.insert(chunk.len, item.clone());
+        }
+        chunk
+    }
+}
+
+/// An unrolled linked list whose nodes hold up to `B` elements.
+///
+/// | Operation                          | Cost         |
+/// |------------------------------------|--------------|
+/// | `get`, `get_mut`                   | O(n / B)     |
+/// | `insert`, `remove`                 | O(n / B + B) |
+/// | `insert_at_beginning`/`_at_end`    | O(B)         |
+/// | `delete_from_beginning`/`_from_end`| O(B)         |
+///
+/// `B` must be at least 2; larger nodes favour traversal and indexing,
+/// smaller ones favour edits in the middle.
+pub struct UnrolledList<T, const B: usize = 16> {
+    chunks: DoublyLinkedList<Chunk<T, B>>,
+    len: usize,
+}
+
+impl<T, const B: usize> UnrolledList<T, B> {
+    const MIN_FILL: usize = B / 2;
+
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        const {
+            assert!(
+                B >= 2,
+                "UnrolledList nodes need room for at least two elements"
+            )
+        };
+        UnrolledList {
+            chunks: DoublyLinkedList::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of nodes currently allocated.
+    pub fn node_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns a reference to the first element.
+    pub fn head(&self) -> Option<&T> {
+        self.chunks.head()?.as_slice().first()
+    }
+
+    /// Returns a reference to the last element.
+    pub fn tail(&self) -> Option<&T> {
+        self.chunks.tail()?.as_slice().last()
+    }
+
+    /// Returns the element at `index`, walking O(n / B) nodes.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let mut offset = index;
+        for chunk in &self.chunks {
+            if offset < chunk.len {
+                return chunk.as_slice().get(offset);
+            }
+            offset -= chunk.len;
+        }
+        None
+    }
+
+    /// Returns the element at `index` mutably, walking O(n / B) nodes.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let mut offset = index;
+        for chunk in &mut self.chunks {
+            if offset < chunk.len {
+                return chunk.as_mut_slice().get_mut(offset);
+            }
+            offset -= chunk.len;
+        }
+        None
+    }
+
+    /// Adds an element to the front of the list.
+    ///
+    /// A full head node is split rather than preceded by a nearly empty one.
+    pub fn insert_at_beginning(&mut self, value: T) {
+        if self.is_empty() {
+            self.insert_at_end(value);
+        } else {
+            self.insert(0, value).expect("0 is in bounds");
+        }
+    }
+
+    /// Adds an element to the back of the list.
+    ///
+    /// Only the tail node may be less than half full, so this simply opens
+    /// a new node once the tail is full.
+    pub fn insert_at_end(&mut self, value: T) {
+        if self.chunks.tail().is_none_or(Chunk::is_full) {
+            self.chunks.insert_at_end(Chunk::new());
+        }
+        let tail = self.chunks.tail_mut().expect("tail chunk exists");
+        tail.insert(tail.len, value);
+        self.len += 1;
+    }
+
+    /// Inserts `value` at `index`, shifting later elements back.
+    ///
+    /// A full node is split in half first, so the insert shifts at most
+    /// `B / 2` elements.
+    pub fn insert(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        if index > self.len {
+            return Err(ListError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+        if index == self.len {
+            self.insert_at_end(value);
+            return Ok(());
+        }
+        let mut cursor = self.chunks.cursor_front_mut();
+        let mut offset = index;
+        while let Some(chunk) = cursor.current() {
+            // Stop on the chunk holding `index`; inserting at the end of a
+            // chunk that still has room avoids touching the next one.
+            if offset < chunk.len || (offset == chunk.len && !chunk.is_full()) {
+                break;
+            }
+            offset -= chunk.len;
+            cursor.move_next();
+        }
+        let chunk = cursor.current().expect("index < len");
+        if chunk.is_full() {
+            let half = chunk.split_off(B / 2);
+            let split_at = chunk.len;
+            cursor.insert_after(half);
+            if offset > split_at {
+                offset -= split_at;
+                cursor.move_next();
+            }
+        }
+        cursor
+            .current()
+            .expect("chunk exists")
+            .insert(offset, value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index`, shifting later elements
+    /// forward.
+    ///
+    /// A node that drops below half full borrows from or merges with its
+    /// successor.
+    pub fn remove(&mut self, index: usize) -> Result<T, ListError> {
+        if index >= self.len {
+            return Err(ListError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+        let mut cursor = self.chunks.cursor_front_mut();
+        let mut offset = index;
+        while let Some(chunk) = cursor.current() {
+            if offset < chunk.len {
+                break;
+            }
+            offset -= chunk.len;
+            cursor.move_next();
+        }
+        let value = cursor.current().expect("index < len").remove(offset);
+        Self::rebalance(&mut cursor);
+        self.len -= 1;
+        Ok(value)
+    }
+
+    /// Removes and returns the first element.
+    pub fn delete_from_beginning(&mut self) -> Result<T, ListError> {
+        if self.is_empty() {
+            return Err(ListError::Empty);
+        }
+        self.remove(0)
+    }
+
+    /// Removes and returns the last element.
+    pub fn delete_from_end(&mut self) -> Result<T, ListError> {
+        let tail = self.chunks.tail_mut().ok_or(ListError::Empty)?;
+        let value = tail.remove(tail.len - 1);
+        if tail.len == 0 {
+            self.chunks.delete_from_end()?;
+        }
+        self.len -= 1;
+        Ok(value)
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.len = 0;
+    }
+
+    /// Returns `true` if the list contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    /// Returns a double-ended iterator over references.
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        Iter {
+            chunks: self.chunks.iter(),
+            front: [].iter(),
+            back: [].iter(),
+            len: self.len,
+        }
+    }
+
+    /// Returns a double-ended iterator over mutable references.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, B> {
+        IterMut {
+            chunks: self.chunks.iter_mut(),
+            front: [].iter_mut(),
+            back: [].iter_mut(),
+            len: self.len,
+        }
+    }
+
+    /// Restores the fill invariant of the chunk under `cursor` after a
+    /// removal: refill it from its successor, or merge the two when they fit
+    /// in one node. Empty chunks are dropped.
+    fn rebalance(cursor: &mut doubly_linked_list::CursorMut<'_, Chunk<T, B>>) {
+        let current_len = cursor.current().expect("chunk exists").len;
+        if current_len >= Self::MIN_FILL {
+            return;
+        }
+        let Some(next_len) = cursor.peek_next().map(|next| next.len) else {
+            if current_len == 0 {
+                cursor.remove_current();
+            }
+            return;
+        };
+        if current_len + next_len <= B {
+            cursor.move_next();
+            let mut next = cursor.remove_current().expect("next chunk exists");
+            cursor.move_prev();
+            cursor.current().expect("chunk exists").append(&mut next);
+        } else {
+            let moved = cursor.peek_next().expect("next chunk exists").remove(0);
+            let current = cursor.current().expect("chunk exists");
+            current.insert(current.len, moved);
+        }
+    }
+}
+
+impl<T, const B: usize> Default for UnrolledList<T, B> {
+    fn default() -> Self {
+        UnrolledList::new()
+    }
+}
+
+impl<T: Clone, const B: usize> Clone for UnrolledList<T, B> {
+    fn clone(&self) -> Self {
+        UnrolledList {
+            chunks: self.chunks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const B: usize> fmt::Debug for UnrolledList<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const B: usize> PartialEq for UnrolledList<T, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const B: usize> Eq for UnrolledList<T, B> {}
+
+impl<T, const B: usize> Extend<T> for UnrolledList<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert_at_end(item);
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for UnrolledList<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = UnrolledList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, const B: usize> IntoIterator for UnrolledList<T, B> {
+    type Item = T;
+    type IntoIter = IntoIter<T, B>;
+
+    fn into_iter(self) -> IntoIter<T, B> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a UnrolledList<T, B> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Iter<'a, T, B> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a mut UnrolledList<T, B> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, B>;
+
+    fn into_iter(self) -> IterMut<'a, T, B> {
+        self.iter_mut()
+    }
+}
+
+/// Borrowing iterator returned by [`UnrolledList::iter`].
+pub struct Iter<'a, T, const B: usize> {
+    chunks: doubly_linked_list::Iter<'a, Chunk<T, B>>,
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<'a, T, const B: usize> Iterator for Iter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.front.next() {
+                self.len -= 1;
+                return Some(item);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.front = chunk.as_slice().iter(),
+                None => {
+                    let item = self.back.next()?;
+                    self.len -= 1;
+                    return Some(item);
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const B: usize> DoubleEndedIterator for Iter<'a, T, B> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.back.next_back() {
+                self.len -= 1;
+                return Some(item);
+            }
+            match self.chunks.next_back() {
+                Some(chunk) => self.back = chunk.as_slice().iter(),
+                None => {
+                    let item = self.front.next_back()?;
+                    self.len -= 1;
+                    return Some(item);
+                }
+            }
+        }
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for Iter<'_, T, B> {}
+impl<T, const B: usize> FusedIterator for Iter<'_, T, B> {}
+
+/// Mutable iterator returned by [`UnrolledList::iter_mut`].
+pub struct IterMut<'a, T, const B: usize> {
+    chunks: doubly_linked_list::IterMut<'a, Chunk<T, B>>,
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+    len: usize,
+}
+
+impl<'a, T, const B: usize> Iterator for IterMut<'a, T, B> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        loop {
+            if let Some(item) = self.front.next() {
+                self.len -= 1;
+                return Some(item);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.front = chunk.as_mut_slice().iter_mut(),
+                None => {
+                    let item = self.back.next()?;
+                    self.len -= 1;
+                    return Some(item);
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const B: usize> DoubleEndedIterator for IterMut<'a, T, B> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        loop {
+            if let Some(item) = self.back.next_back() {
+                self.len -= 1;
+                return Some(item);
+            }
+            match self.chunks.next_back() {
+                Some(chunk) => self.back = chunk.as_mut_slice().iter_mut(),
+                None => {
+                    let item = self.front.next_back()?;
+                    self.len -= 1;
+                    return Some(item);
+                }
+            }
+        }
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for IterMut<'_, T, B> {}
+impl<T, const B: usize> FusedIterator for IterMut<'_, T, B> {}
+
+/// Owning iterator returned by [`UnrolledList::into_iter`].
+pub struct IntoIter<T, const B: usize> {
+    list: UnrolledList<T, B>,
+}
+
+impl<T, const B: usize> Iterator for IntoIter<T, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_from_beginning().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, const B: usize> DoubleEndedIterator for IntoIter<T, B> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.delete_from_end().ok()
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for IntoIter<T, B> {}
+impl<T, const B: usize> FusedIterator for IntoIter<T, B> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::DropCounter;
+
+    /// Checks `len` and that every node but the last is at least half full
+    /// and none is empty; returns the node fill levels.
+    fn check<T, const B: usize>(list: &UnrolledList<T, B>) -> Vec<usize> {
+        let fills: Vec<usize> = list.chunks.iter().map(|chunk| chunk.len).collect();
+        assert_eq!(fills.iter().sum::<usize>(), list.len);
+        assert!(fills.iter().all(|&fill| 0 < fill && fill <= B), "{fills:?}");
+        if let Some((_, full)) = fills.split_last() {
+            assert!(full.iter().all(|&fill| fill >= B / 2), "{fills:?}");
+        }
+        fills
+    }
+
+    fn items<T: Clone, const B: usize>(list: &UnrolledList<T, B>) -> Vec<T> {
+        check(list);
+        let forward: Vec<T> = list.iter().cloned().collect();
+        let mut backward: Vec<T> = list.iter().rev().cloned().collect();
+        backward.reverse();
+        assert_eq!(forward.len(), backward.len());
+        forward
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: UnrolledList<i32, 4> = UnrolledList::new();
+        assert_eq!(check(&list), []);
+        assert_eq!(list.delete_from_beginning(), Err(ListError::Empty));
+        assert_eq!(list.delete_from_end(), Err(ListError::Empty));
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn out_of_bounds_indices() {
+        let mut list: UnrolledList<_, 4> = (0..5).collect();
+        assert_eq!(
+            list.insert(6, 9),
+            Err(ListError::IndexOutOfBounds { index: 6, len: 5 })
+        );
+        assert_eq!(
+            list.remove(5),
+            Err(ListError::IndexOutOfBounds { index: 5, len: 5 })
+        );
+        assert_eq!(list.get(5), None);
+        assert_eq!(list.get_mut(5), None);
+        assert_eq!(list.insert(5, 5), Ok(()));
+        assert_eq!(items(&list), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn appending_opens_a_node_per_b_elements() {
+        let mut list: UnrolledList<_, 4> = UnrolledList::new();
+        for i in 0..9 {
+            list.insert_at_end(i);
+        }
+        assert_eq!(check(&list), [4, 4, 1]);
+        assert_eq!((list.head(), list.tail()), (Some(&0), Some(&8)));
+        assert_eq!(list.get(4), Some(&4));
+    }
+
+    #[test]
+    fn insert_into_full_node_splits_it() {
+        let mut list: UnrolledList<_, 4> = (0..8).collect();
+        list.insert(1, 10).unwrap();
+        assert_eq!(check(&list), [3, 2, 4]);
+        list.insert(6, 11).unwrap();
+        assert_eq!(check(&list), [3, 2, 3, 2]);
+        assert_eq!(items(&list), [0, 10, 1, 2, 3, 4, 11, 5, 6, 7]);
+    }
+
+    #[test]
+    fn insert_at_a_node_boundary_fills_the_earlier_node() {
+        let mut list: UnrolledList<_, 4> = (0..8).collect();
+        list.remove(3).unwrap();
+        assert_eq!(check(&list), [3, 4]);
+        list.insert(3, 3).unwrap();
+        assert_eq!(check(&list), [4, 4]);
+        list.insert(4, 9).unwrap();
+        assert_eq!(check(&list), [4, 3, 2]);
+        assert_eq!(items(&list), [0, 1, 2, 3, 9, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn insert_at_beginning_splits_a_full_head() {
+        let mut list: UnrolledList<_, 4> = UnrolledList::new();
+        for i in 0..5 {
+            list.insert_at_beginning(i);
+        }
+        assert_eq!(check(&list), [3, 2]);
+        assert_eq!(items(&list), [4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn remove_borrows_from_the_next_node() {
+        let mut list: UnrolledList<_, 4> = (0..8).collect();
+        list.remove(0).unwrap();
+        list.remove(0).unwrap();
+        assert_eq!(check(&list), [2, 4]);
+        list.remove(0).unwrap();
+        assert_eq!(check(&list), [2, 3]);
+        assert_eq!(items(&list), [3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn remove_merges_nodes_that_fit_together() {
+        let mut list: UnrolledList<_, 4> = (0..8).collect();
+        list.remove(7).unwrap();
+        list.remove(6).unwrap();
+        assert_eq!(check(&list), [4, 2]);
+        list.remove(0).unwrap();
+        list.remove(0).unwrap();
+        assert_eq!(check(&list), [2, 2]);
+        list.remove(0).unwrap();
+        assert_eq!(check(&list), [3]);
+        assert_eq!(items(&list), [3, 4, 5]);
+        assert_eq!(list.node_count(), 1);
+    }
+
+    #[test]
+    fn delete_from_end_drops_empty_tail_nodes() {
+        let mut list: UnrolledList<_, 4> = (0..5).collect();
+        assert_eq!(list.delete_from_end(), Ok(4));
+        assert_eq!(check(&list), [4]);
+        for expected in (0..4).rev() {
+            assert_eq!(list.delete_from_end(), Ok(expected));
+            check(&list);
+        }
+        assert_eq!(list.node_count(), 0);
+    }
+
+    #[test]
+    fn matches_a_vec_under_random_edits() {
+        fn run<const B: usize>() {
+            let mut state = 0x9e37_79b9_u32;
+            let mut next = |bound: usize| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as usize % bound
+            };
+            let mut list: UnrolledList<usize, B> = UnrolledList::new();
+            let mut model = Vec::new();
+            for step in 0..400 {
+                if model.is_empty() || next(3) != 0 {
+                    let index = next(model.len() + 1);
+                    list.insert(index, step).unwrap();
+                    model.insert(index, step);
+                } else {
+                    let index = next(model.len());
+                    assert_eq!(list.remove(index), Ok(model.remove(index)));
+                }
+                check(&list);
+            }
+            assert_eq!(items(&list), model);
+        }
+        run::<2>();
+        run::<3>();
+        run::<4>();
+        run::<16>();
+    }
+
+    #[test]
+    fn iterators_from_both_ends() {
+        let mut list: UnrolledList<_, 4> = (0..10).collect();
+        for item in list.iter_mut().rev().take(3) {
+            *item += 100;
+        }
+        let mut iter = list.iter();
+        assert_eq!(iter.next_back(), Some(&109));
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.len(), 8);
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(109));
+        assert_eq!(into_iter.next(), Some(0));
+        assert_eq!(into_iter.len(), 8);
+    }
+
+    #[test]
+    fn drop_frees_partially_filled_nodes() {
+        let counter = DropCounter::new();
+        let mut list: UnrolledList<_, 4> = (0..10).map(|i| counter.item(i)).collect();
+        list.insert(1, counter.item(10)).unwrap();
+        drop(list.remove(5).unwrap());
+        assert_eq!(counter.dropped(), 1);
+        assert_eq!(check(&list), [3, 2, 3, 2]);
+        drop(list);
+        assert_eq!(counter.dropped(), 11);
+    }
+
+    #[test]
+    fn drop_of_partly_consumed_into_iter() {
+        let counter = DropCounter::new();
+        let list: UnrolledList<_, 4> = (0..7).map(|i| counter.item(i)).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next().map(|item| item.value), Some(0));
+        assert_eq!(iter.next_back().map(|item| item.value), Some(6));
+        drop(iter);
+        assert_eq!(counter.dropped(), 7);
+    }
+
+    #[test]
+    fn clone_is_deep() {
+        let counter = DropCounter::new();
+        let list: UnrolledList<_, 4> = (0..6).map(|i| counter.item(i)).collect();
+        let copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(check(&copy), [4, 2]);
+        drop(list);
+        assert_eq!(counter.dropped(), 6);
+        drop(copy);
+        assert_eq!(counter.dropped(), 12);
+    }
+}
@@ -0,0 +1,55 @@
+//! Helpers shared by the unit tests.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Hands out [`Counted`] values and counts how many of them were dropped.
+#[derive(Debug, Default)]
+pub(crate) struct DropCounter {
+    drops: Rc<Cell<usize>>,
+}
+
+impl DropCounter {
+    pub(crate) fn new() -> Self {
+        DropCounter::default()
+    }
+
+    pub(crate) fn item<T>(&self, value: T) -> Counted<T> {
+        Counted {
+            value,
+            drops: Rc::clone(&self.drops),
+        }
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.drops.get()
+    }
+}
+
+/// A value that reports its drop to the [`DropCounter`] that made it.
+#[derive(Debug)]
+pub(crate) struct Counted<T> {
+    pub(crate) value: T,
+    drops: Rc<Cell<usize>>,
+}
+
+impl<T: Clone> Clone for Counted<T> {
+    fn clone(&self) -> Self {
+        Counted {
+            value: self.value.clone(),
+            drops: Rc::clone(&self.drops),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Counted<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Drop for Counted<T> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
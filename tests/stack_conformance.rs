@@ -0,0 +1,312 @@
+//! Behaviour every stack implementation must share.
+//!
+//! `conformance!` expands to the same set of tests for each backend, so a new
+//! backend is covered by adding one line at the bottom of this file. The
+//! tests drive the backends through [`Backend`], which the [`Stack`]
+//! implementations get from the trait itself and the stacks outside it
+//! (bounded stacks, whose push can fail, and the shared `TreiberStack`)
+//! implement by hand. Backends that can peek and print also get the
+//! [`Inspect`] tests.
+
+use std::fmt;
+
+use data_structure::linear::stack::{Max, OverflowPolicy};
+use data_structure::linear::{
+    AggStack, ArrayStack, BoundedStack, InlineBoundedStack, ListStack, Stack, StackError,
+    TreiberStack,
+};
+
+/// The operations every backend supports.
+trait Backend: Sized {
+    /// Most items the backend can hold; tests stay within it.
+    const CAPACITY: usize = usize::MAX;
+
+    fn new() -> Self;
+    fn push(&mut self, item: u32);
+    fn pop(&mut self) -> Result<u32, StackError>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+
+    fn from_items(items: impl IntoIterator<Item = u32>) -> Self {
+        let mut stack = Self::new();
+        stack.extend_items(items);
+        stack
+    }
+
+    fn extend_items(&mut self, items: impl IntoIterator<Item = u32>) {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+/// Backends that can look at their items without popping them.
+trait Inspect: Backend + fmt::Display {
+    fn peek(&self) -> Result<&u32, StackError>;
+    /// Items from the top of the stack to the bottom.
+    fn items(&self) -> Vec<u32>;
+}
+
+macro_rules! stack_backend {
+    ($($stack:ty),*) => {$(
+        impl Backend for $stack {
+            fn new() -> Self {
+                <$stack>::default()
+            }
+
+            fn push(&mut self, item: u32) {
+                Stack::push(self, item);
+            }
+
+            fn pop(&mut self) -> Result<u32, StackError> {
+                Stack::pop(self)
+            }
+
+            fn len(&self) -> usize {
+                Stack::len(self)
+            }
+
+            fn is_empty(&self) -> bool {
+                Stack::is_empty(self)
+            }
+
+            fn from_items(items: impl IntoIterator<Item = u32>) -> Self {
+                items.into_iter().collect()
+            }
+
+            fn extend_items(&mut self, items: impl IntoIterator<Item = u32>) {
+                self.extend(items);
+            }
+        }
+
+        impl Inspect for $stack {
+            fn peek(&self) -> Result<&u32, StackError> {
+                Stack::peek(self)
+            }
+
+            fn items(&self) -> Vec<u32> {
+                self.iter().copied().collect()
+            }
+        }
+    )*};
+}
+
+stack_backend!(AggStack<u32, Max>, ArrayStack<u32>, ListStack<u32>);
+
+macro_rules! bounded_backend {
+    ($stack:ty, $capacity:expr, $new:expr) => {
+        impl Backend for $stack {
+            const CAPACITY: usize = $capacity;
+
+            fn new() -> Self {
+                $new
+            }
+
+            fn push(&mut self, item: u32) {
+                assert!(
+                    matches!(<$stack>::push(self, item), Ok(None)),
+                    "push below capacity"
+                );
+            }
+
+            fn pop(&mut self) -> Result<u32, StackError> {
+                <$stack>::pop(self)
+            }
+
+            fn len(&self) -> usize {
+                <$stack>::len(self)
+            }
+
+            fn is_empty(&self) -> bool {
+                <$stack>::is_empty(self)
+            }
+        }
+
+        impl Inspect for $stack {
+            fn peek(&self) -> Result<&u32, StackError> {
+                <$stack>::peek(self)
+            }
+
+            fn items(&self) -> Vec<u32> {
+                self.iter().copied().collect()
+            }
+        }
+    };
+}
+
+bounded_backend!(
+    BoundedStack<u32>,
+    1 << 20,
+    BoundedStack::new(1 << 20, OverflowPolicy::Error)
+);
+bounded_backend!(InlineBoundedStack<u32, 1024>, 1024, InlineBoundedStack::default());
+
+impl Backend for TreiberStack<u32> {
+    fn new() -> Self {
+        TreiberStack::new()
+    }
+
+    fn push(&mut self, item: u32) {
+        TreiberStack::push(self, item);
+    }
+
+    fn pop(&mut self) -> Result<u32, StackError> {
+        TreiberStack::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        TreiberStack::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        TreiberStack::is_empty(self)
+    }
+
+    fn from_items(items: impl IntoIterator<Item = u32>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+fn starts_empty<S: Backend>() {
+    let stack = S::new();
+    assert!(stack.is_empty());
+    assert_eq!(stack.len(), 0);
+}
+
+fn pops_in_reverse_push_order<S: Backend>() {
+    let mut stack = S::new();
+    for item in 1..=3 {
+        stack.push(item);
+    }
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.pop(), Ok(3));
+    assert_eq!(stack.pop(), Ok(2));
+    assert_eq!(stack.pop(), Ok(1));
+    assert!(stack.is_empty());
+}
+
+fn underflow_is_an_error<S: Backend>() {
+    let mut stack = S::new();
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+    stack.push(1);
+    stack.pop().unwrap();
+    assert_eq!(stack.pop(), Err(StackError::Underflow));
+    assert!(stack.is_empty());
+}
+
+fn collect_pushes_in_order<S: Backend>() {
+    let mut stack = S::from_items(1..=4);
+    stack.extend_items([5, 6]);
+    assert_eq!(stack.len(), 6);
+    let popped: Vec<_> = std::iter::from_fn(|| stack.pop().ok()).collect();
+    assert_eq!(popped, [6, 5, 4, 3, 2, 1]);
+}
+
+fn interleaved_operations_match_vec<S: Backend>() {
+    let mut stack = S::new();
+    let mut model = Vec::new();
+    for step in 0..1000u32 {
+        if step % 3 == 2 {
+            assert_eq!(stack.pop().ok(), model.pop());
+        } else {
+            stack.push(step);
+            model.push(step);
+        }
+        assert_eq!(stack.len(), model.len());
+        assert_eq!(stack.is_empty(), model.is_empty());
+    }
+}
+
+fn drops_long_stacks<S: Backend>() {
+    let len = S::CAPACITY.min(1_000_000);
+    let stack = S::from_items(0..len as u32);
+    assert_eq!(stack.len(), len);
+    drop(stack);
+}
+
+fn peek_does_not_remove<S: Inspect>() {
+    let mut stack = S::new();
+    assert_eq!(stack.peek(), Err(StackError::Underflow));
+    stack.push(7);
+    assert_eq!(stack.peek(), Ok(&7));
+    assert_eq!(stack.len(), 1);
+    stack.push(8);
+    assert_eq!(stack.peek(), Ok(&8));
+    stack.pop().unwrap();
+    stack.pop().unwrap();
+    assert_eq!(stack.peek(), Err(StackError::Underflow));
+}
+
+fn display_lists_top_first<S: Inspect>() {
+    let mut stack = S::new();
+    assert_eq!(stack.to_string(), "Stack:");
+    stack.push(1);
+    assert_eq!(stack.to_string(), "Stack: 1");
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.to_string(), "Stack: 3 <- 2 <- 1");
+}
+
+fn iterates_top_first<S: Inspect>() {
+    let mut stack = S::from_items(1..=4);
+    stack.extend_items([5, 6]);
+    assert_eq!(stack.items(), [6, 5, 4, 3, 2, 1]);
+}
+
+fn peek_tracks_a_model<S: Inspect>() {
+    let mut stack = S::new();
+    let mut model = Vec::new();
+    for step in 0..1000u32 {
+        if step % 3 == 2 {
+            assert_eq!(stack.pop().ok(), model.pop());
+        } else {
+            stack.push(step);
+            model.push(step);
+        }
+        assert_eq!(stack.peek().ok(), model.last());
+    }
+    model.reverse();
+    assert_eq!(stack.items(), model);
+}
+
+macro_rules! tests {
+    ($stack:ty: $($test:ident),*) => {$(
+        #[test]
+        fn $test() {
+            super::$test::<$stack>();
+        }
+    )*};
+}
+
+macro_rules! conformance {
+    ($module:ident, $stack:ty $(, $inspect:ident)?) => {
+        mod $module {
+            use super::*;
+
+            tests!($stack:
+                starts_empty,
+                pops_in_reverse_push_order,
+                underflow_is_an_error,
+                collect_pushes_in_order,
+                interleaved_operations_match_vec,
+                drops_long_stacks
+            );
+            $(conformance!(@$inspect $stack);)?
+        }
+    };
+    (@inspect $stack:ty) => {
+        tests!($stack:
+            peek_does_not_remove,
+            display_lists_top_first,
+            iterates_top_first,
+            peek_tracks_a_model
+        );
+    };
+}
+
+conformance!(agg_stack, AggStack<u32, Max>, inspect);
+conformance!(array_stack, ArrayStack<u32>, inspect);
+conformance!(list_stack, ListStack<u32>, inspect);
+conformance!(bounded_stack, BoundedStack<u32>, inspect);
+conformance!(inline_bounded_stack, InlineBoundedStack<u32, 1024>, inspect);
+conformance!(treiber_stack, TreiberStack<u32>);
@@ -0,0 +1,88 @@
+//! Exhaustive interleaving checks for `TreiberStack`.
+//!
+//! Run with
+//! `LOOM_MAX_PREEMPTIONS=3 RUSTFLAGS="--cfg loom" cargo test --release --test treiber_loom`;
+//! without the preemption bound the pop races take hours to exhaust.
+
+#![cfg(loom)]
+
+use data_structure::linear::{StackError, TreiberStack};
+use loom::sync::Arc;
+use loom::thread;
+
+#[test]
+fn concurrent_pushes_are_all_kept() {
+    loom::model(|| {
+        let stack = Arc::new(TreiberStack::new());
+        let handles: Vec<_> = (0..2)
+            .map(|item| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.push(item))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let mut items = [stack.pop().unwrap(), stack.pop().unwrap()];
+        items.sort_unstable();
+        assert_eq!(items, [0, 1]);
+        assert_eq!(stack.pop(), Err(StackError::Underflow));
+    });
+}
+
+#[test]
+fn concurrent_pops_take_each_item_once() {
+    loom::model(|| {
+        let stack = Arc::new(TreiberStack::new());
+        stack.push(1);
+        stack.push(2);
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.pop().unwrap())
+            })
+            .collect();
+        let mut items: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        items.sort_unstable();
+        assert_eq!(items, [1, 2]);
+        assert!(stack.is_empty());
+    });
+}
+
+#[test]
+fn push_races_pop() {
+    loom::model(|| {
+        let stack = Arc::new(TreiberStack::new());
+        stack.push(1);
+        let pusher = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                stack.push(2);
+                stack.pop().unwrap()
+            })
+        };
+        let first = stack.pop().unwrap();
+        let second = pusher.join().unwrap();
+        let mut items = [first, second];
+        items.sort_unstable();
+        assert_eq!(items, [1, 2]);
+        assert_eq!(stack.len(), 0);
+    });
+}
+
+#[test]
+fn pop_on_empty_races_push() {
+    loom::model(|| {
+        let stack = Arc::new(TreiberStack::new());
+        let popper = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.pop())
+        };
+        stack.push(7);
+        match popper.join().unwrap() {
+            Ok(item) => assert_eq!(item, 7),
+            Err(StackError::Underflow) => assert_eq!(stack.pop(), Ok(7)),
+            Err(error) => panic!("unexpected {error}"),
+        }
+    });
+}
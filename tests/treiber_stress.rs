@@ -0,0 +1,78 @@
+//! Many threads hammering one `TreiberStack`.
+
+#![cfg(not(loom))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use data_structure::linear::TreiberStack;
+
+const THREADS: usize = 8;
+const PER_THREAD: usize = 20_000;
+
+#[test]
+fn every_item_is_popped_exactly_once() {
+    let stack = Arc::new(TreiberStack::new());
+    // Spawn every thread before joining any, so they really run at once.
+    let handles: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                for i in 0..PER_THREAD {
+                    stack.push(thread * PER_THREAD + i);
+                    if i % 2 == 1 {
+                        popped.extend(stack.pop());
+                        popped.extend(stack.pop());
+                    }
+                }
+                popped
+            })
+        })
+        .collect();
+    let mut all: Vec<usize> = Vec::new();
+    for handle in handles {
+        all.extend(handle.join().unwrap());
+    }
+    while let Ok(item) = stack.pop() {
+        all.push(item);
+    }
+    all.sort_unstable();
+    assert_eq!(all, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+    assert!(stack.is_empty());
+    assert_eq!(stack.len(), 0);
+}
+
+#[test]
+fn items_are_dropped_once() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let stack = Arc::new(TreiberStack::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    stack.push(Counted);
+                    if i % 3 == 0 {
+                        drop(stack.pop());
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    drop(stack);
+    assert_eq!(DROPS.load(Ordering::Relaxed), THREADS * PER_THREAD);
+}